@@ -24,7 +24,21 @@ impl NbtCompound {
         }
     }
 
+    pub fn get(&self, name: &str) -> Option<&NbtTag> {
+        self.children
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
     pub fn deserialize_content<R: Buf>(reader: &mut R) -> Result<NbtCompound, Error> {
+        Self::deserialize_content_nested(reader, 0)
+    }
+
+    pub(crate) fn deserialize_content_nested<R: Buf>(
+        reader: &mut R,
+        depth: usize,
+    ) -> Result<NbtCompound, Error> {
         let mut compound = NbtCompound::new();
 
         loop {
@@ -37,7 +51,7 @@ impl NbtCompound {
             }
 
             let name = get_nbt_string(reader)?;
-            let tag = NbtTag::deserialize_data(reader, tag_id)?;
+            let tag = NbtTag::deserialize_data_nested(reader, tag_id, depth)?;
             compound.insert(&name, tag);
         }
 
@@ -6,7 +6,7 @@ use serde::{
 
 use crate::{
     BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, END_ID, Error, INT_ARRAY_ID, INT_ID, LIST_ID,
-    LONG_ARRAY_ID, LONG_ID, NbtTag, Result,
+    LONG_ARRAY_ID, LONG_ID, MAX_DEPTH, NbtTag, Result,
 };
 
 #[allow(unused)]
@@ -26,6 +26,7 @@ pub struct Deserializer<R: Buf> {
     named: bool,
     tag_to_deserialize_stack: Option<u8>,
     in_list: bool,
+    depth: usize,
 }
 
 impl<R: Buf> Deserializer<R> {
@@ -35,6 +36,7 @@ impl<R: Buf> Deserializer<R> {
             named,
             tag_to_deserialize_stack: None,
             in_list: false,
+            depth: 0,
         }
     }
 }
@@ -45,6 +47,14 @@ where
     Self: Buf,
 {
     fn read_boxed_slice(&mut self, count: usize) -> Result<Box<[u8]>> {
+        if count > self.remaining() {
+            return Err(Error::Incomplete(format!(
+                "requested {} bytes, {} available",
+                count,
+                self.remaining()
+            )));
+        }
+
         let mut buf = vec![0u8; count];
         self.copy_to_slice(&mut buf);
         Ok(buf.into())
@@ -83,32 +93,43 @@ impl<'de, R: Buf> de::Deserializer<'de> for &mut Deserializer<R> {
             ));
         };
 
+        if self.depth > MAX_DEPTH {
+            return Err(Error::TooDeep(MAX_DEPTH));
+        }
+
         match tag_to_deserialize {
             END_ID => Err(Error::SerdeError(
                 "Trying to deserialize an END tag!".to_string(),
             )),
             LIST_ID | INT_ARRAY_ID | LONG_ARRAY_ID | BYTE_ARRAY_ID => {
                 let list_type = match tag_to_deserialize {
-                    LIST_ID => self.reader.try_get_u8().unwrap(),
+                    LIST_ID => self.reader.try_get_u8()?,
                     INT_ARRAY_ID => INT_ID,
                     LONG_ARRAY_ID => LONG_ID,
                     BYTE_ARRAY_ID => BYTE_ID,
                     _ => unreachable!(),
                 };
 
-                let remaining_values = self.reader.try_get_i32().unwrap();
+                let remaining_values = self.reader.try_get_i32()?;
                 if remaining_values < 0 {
                     return Err(Error::NegativeLength(remaining_values));
                 }
 
+                self.depth += 1;
                 let result = visitor.visit_seq(ListAccess {
-                    de: self,
+                    de: &mut *self,
                     list_type,
                     remaining_values: remaining_values as usize,
-                })?;
-                Ok(result)
+                });
+                self.depth -= 1;
+                result
+            }
+            COMPOUND_ID => {
+                self.depth += 1;
+                let result = visitor.visit_map(CompoundAccess { de: &mut *self });
+                self.depth -= 1;
+                result
             }
-            COMPOUND_ID => visitor.visit_map(CompoundAccess { de: self }),
             _ => {
                 let result = match NbtTag::deserialize_data(&mut self.reader, tag_to_deserialize)? {
                     NbtTag::Byte(value) => visitor.visit_i8::<Error>(value)?,
@@ -129,8 +150,8 @@ impl<'de, R: Buf> de::Deserializer<'de> for &mut Deserializer<R> {
     where
         V: Visitor<'de>,
     {
-        if self.tag_to_deserialize_stack.unwrap() == BYTE_ID {
-            let value = self.reader.try_get_u8().unwrap();
+        if self.tag_to_deserialize_stack == Some(BYTE_ID) {
+            let value = self.reader.try_get_u8()?;
             if value != 0 {
                 return visitor.visit_bool(true);
             }
@@ -143,7 +164,7 @@ impl<'de, R: Buf> de::Deserializer<'de> for &mut Deserializer<R> {
         V: Visitor<'de>,
     {
         if self.in_list {
-            let value = self.reader.try_get_u8().unwrap();
+            let value = self.reader.try_get_u8()?;
             visitor.visit_u8::<Error>(value)
         } else {
             Err(Error::UnsupportedType(
@@ -175,14 +196,14 @@ impl<'de, R: Buf> de::Deserializer<'de> for &mut Deserializer<R> {
                 )));
             }
         } else {
-            let next_byte = self.reader.try_get_u8().unwrap();
+            let next_byte = self.reader.try_get_u8()?;
             if next_byte != COMPOUND_ID {
                 return Err(Error::MissingRootCompound(next_byte));
             }
 
             if self.named {
                 // Consume struct name, similar to get_nbt_string but without cesu8::from_java_cesu8
-                let length = self.reader.try_get_u16().unwrap() as usize;
+                let length = self.reader.try_get_u16()? as usize;
                 let _ = self.reader.read_boxed_slice(length)?;
             }
         }
@@ -242,9 +263,9 @@ impl<'de, R: Buf> de::Deserializer<'de> for &mut Deserializer<R> {
 }
 
 pub fn get_nbt_string<R: Buf>(bytes: &mut R) -> Result<String> {
-    let length = bytes.try_get_u16().unwrap() as usize;
+    let length = bytes.try_get_u16()? as usize;
     let bytes = bytes.read_boxed_slice(length)?;
-    Ok(String::from_utf8(bytes.to_vec()).unwrap())
+    String::from_utf8(bytes.to_vec()).map_err(|e| Error::SerdeError(e.to_string()))
 }
 
 struct CompoundAccess<'a, R: Buf> {
@@ -255,7 +276,7 @@ impl<'de, R: Buf> MapAccess<'de> for CompoundAccess<'_, R> {
     type Error = Error;
 
     fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
-        let tag = self.de.reader.try_get_u8().unwrap();
+        let tag = self.de.reader.try_get_u8()?;
         self.de.tag_to_deserialize_stack = Some(tag);
 
         if tag == END_ID {
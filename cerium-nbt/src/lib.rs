@@ -13,7 +13,7 @@ use thiserror::Error;
 pub use deserialize::{from_bytes_named, from_bytes_unnamed};
 pub use serialize::{to_bytes_named, to_bytes_unnamed};
 
-use crate::serialize::WriteExt;
+use crate::{deserialize::get_nbt_string, serialize::WriteExt};
 
 pub const END_ID: u8 = 0;
 pub const BYTE_ID: u8 = 1;
@@ -47,8 +47,19 @@ pub enum Error {
     LargeLength(usize),
     #[error("Encountered an unknown NBT tag id: {0}.")]
     UnknownTag(u8),
+    #[error("NBT is nested deeper than {0} levels")]
+    TooDeep(usize),
 }
 
+impl From<bytes::TryGetError> for Error {
+    fn from(value: bytes::TryGetError) -> Self {
+        Self::Incomplete(value.to_string())
+    }
+}
+
+/// The maximum nesting depth of compounds and lists, the same as the vanilla limit.
+pub const MAX_DEPTH: usize = 512;
+
 impl de::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -81,6 +92,23 @@ impl Nbt {
         }
     }
 
+    /// Reads a named root compound, the way NBT files are stored.
+    pub fn read_named<R: bytes::Buf>(reader: &mut R) -> Result<Self> {
+        let tag_id = reader.try_get_u8()?;
+        if tag_id != COMPOUND_ID {
+            return Err(Error::MissingRootCompound(tag_id));
+        }
+        let name = get_nbt_string(reader)?;
+        Ok(Nbt::new(name, NbtCompound::deserialize_content(reader)?))
+    }
+
+    pub fn write_named<W: WriteExt>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8_be(COMPOUND_ID)?;
+        NbtTag::String(self.name.clone()).serialize_data(writer)?;
+        self.root.serialize_content(writer)?;
+        Ok(())
+    }
+
     pub fn write_unnamed<W: WriteExt>(&self, writer: &mut W) -> Result<()> {
         writer.write_u8_be(COMPOUND_ID)?;
         self.root.serialize_content(writer)?;
@@ -140,4 +168,23 @@ mod tests {
 
         assert_eq!(test, recreated_struct);
     }
+
+    #[test]
+    fn test_named_root() {
+        let mut compound = NbtCompound::new();
+        compound.insert("Width", 3i16);
+        compound.insert("Name", "arena");
+        let nbt = Nbt::new("Schematic".to_string(), compound);
+
+        let mut bytes = Vec::new();
+        nbt.write_named(&mut bytes).unwrap();
+        let read = Nbt::read_named(&mut Cursor::new(bytes.as_slice())).unwrap();
+        assert_eq!(read, nbt);
+        assert_eq!(read.get("Width"), Some(&NbtTag::Short(3)));
+
+        assert!(matches!(
+            Nbt::read_named(&mut Cursor::new([STRING_ID].as_slice())),
+            Err(Error::MissingRootCompound(STRING_ID))
+        ));
+    }
 }
@@ -8,7 +8,7 @@ use serde::{
 
 use crate::{
     BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, END_ID, Error, FLOAT_ID, INT_ARRAY_ID, INT_ID,
-    LIST_ID, LONG_ARRAY_ID, LONG_ID, NbtCompound, SHORT_ID, STRING_ID,
+    LIST_ID, LONG_ARRAY_ID, LONG_ID, MAX_DEPTH, NbtCompound, SHORT_ID, STRING_ID,
     deserialize::{ReadExt, get_nbt_string},
     serialize::WriteExt,
 };
@@ -39,34 +39,46 @@ impl NbtTag {
     }
 
     pub fn deserialize_data<R: Buf>(reader: &mut R, tag_id: u8) -> Result<NbtTag, Error> {
+        Self::deserialize_data_nested(reader, tag_id, 0)
+    }
+
+    pub(crate) fn deserialize_data_nested<R: Buf>(
+        reader: &mut R,
+        tag_id: u8,
+        depth: usize,
+    ) -> Result<NbtTag, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::TooDeep(MAX_DEPTH));
+        }
+
         match tag_id {
             END_ID => Ok(NbtTag::End),
             BYTE_ID => {
-                let byte = reader.try_get_i8().unwrap();
+                let byte = reader.try_get_i8()?;
                 Ok(NbtTag::Byte(byte))
             }
             SHORT_ID => {
-                let short = reader.try_get_i16().unwrap();
+                let short = reader.try_get_i16()?;
                 Ok(NbtTag::Short(short))
             }
             INT_ID => {
-                let int = reader.try_get_i32().unwrap();
+                let int = reader.try_get_i32()?;
                 Ok(NbtTag::Int(int))
             }
             LONG_ID => {
-                let long = reader.try_get_i64().unwrap();
+                let long = reader.try_get_i64()?;
                 Ok(NbtTag::Long(long))
             }
             FLOAT_ID => {
-                let float = reader.try_get_f32().unwrap();
+                let float = reader.try_get_f32()?;
                 Ok(NbtTag::Float(float))
             }
             DOUBLE_ID => {
-                let double = reader.try_get_f64().unwrap();
+                let double = reader.try_get_f64()?;
                 Ok(NbtTag::Double(double))
             }
             BYTE_ARRAY_ID => {
-                let len = reader.try_get_i32().unwrap();
+                let len = reader.try_get_i32()?;
                 if len < 0 {
                     return Err(Error::NegativeLength(len));
                 }
@@ -76,45 +88,48 @@ impl NbtTag {
             }
             STRING_ID => Ok(NbtTag::String(get_nbt_string(reader)?)),
             LIST_ID => {
-                let tag_type_id = reader.try_get_u8().unwrap();
-                let len = reader.try_get_i32().unwrap();
+                let tag_type_id = reader.try_get_u8()?;
+                let len = reader.try_get_i32()?;
                 if len < 0 {
                     return Err(Error::NegativeLength(len));
                 }
 
-                let mut list = Vec::with_capacity(len as usize);
+                let mut list = Vec::with_capacity((len as usize).min(reader.remaining()));
                 for _ in 0..len {
-                    let tag = NbtTag::deserialize_data(reader, tag_type_id)?;
+                    let tag = NbtTag::deserialize_data_nested(reader, tag_type_id, depth + 1)?;
                     assert_eq!(tag.id(), tag_type_id);
                     list.push(tag);
                 }
                 Ok(NbtTag::List(list))
             }
-            COMPOUND_ID => Ok(NbtTag::Compound(NbtCompound::deserialize_content(reader)?)),
+            COMPOUND_ID => Ok(NbtTag::Compound(NbtCompound::deserialize_content_nested(
+                reader,
+                depth + 1,
+            )?)),
             INT_ARRAY_ID => {
-                let len = reader.try_get_i32().unwrap();
+                let len = reader.try_get_i32()?;
                 if len < 0 {
                     return Err(Error::NegativeLength(len));
                 }
 
                 let len = len as usize;
-                let mut int_array = Vec::with_capacity(len);
+                let mut int_array = Vec::with_capacity(len.min(reader.remaining() / 4));
                 for _ in 0..len {
-                    let int = reader.try_get_i32().unwrap();
+                    let int = reader.try_get_i32()?;
                     int_array.push(int);
                 }
                 Ok(NbtTag::IntArray(int_array))
             }
             LONG_ARRAY_ID => {
-                let len = reader.try_get_i32().unwrap();
+                let len = reader.try_get_i32()?;
                 if len < 0 {
                     return Err(Error::NegativeLength(len));
                 }
 
                 let len = len as usize;
-                let mut long_array = Vec::with_capacity(len);
+                let mut long_array = Vec::with_capacity(len.min(reader.remaining() / 8));
                 for _ in 0..len {
-                    let long = reader.try_get_i64().unwrap();
+                    let long = reader.try_get_i64()?;
                     long_array.push(long);
                 }
                 Ok(NbtTag::LongArray(long_array))
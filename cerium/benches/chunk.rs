@@ -0,0 +1,53 @@
+use std::hint::black_box;
+
+use bytes::BytesMut;
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use cerium::protocol::{encode::Encode, packet::ChunkDataAndUpdateLightPacket};
+use cerium::registry::DimensionType;
+use cerium::world::{Block, World};
+
+fn encode_chunk(c: &mut Criterion) {
+    let dimension = DimensionType::OVERWORLD;
+    let world = World::new(&dimension);
+    let chunk = world.load_chunk((0, 0));
+    for y in -64..64 {
+        for z in 0..16 {
+            for x in 0..16 {
+                let block = match (x + y + z) % 4 {
+                    0 => Block::Stone,
+                    1 => Block::Dirt,
+                    2 => Block::Andesite,
+                    _ => Block::Granite,
+                };
+                world.set_block(x, y, z, block);
+            }
+        }
+    }
+
+    c.bench_function("chunk encode", |b| {
+        let mut buffer = BytesMut::new();
+        b.iter(|| {
+            buffer.clear();
+            let packet: ChunkDataAndUpdateLightPacket = (&chunk).into();
+            ChunkDataAndUpdateLightPacket::encode(&mut buffer, &packet).unwrap();
+            black_box(buffer.len())
+        })
+    });
+
+    c.bench_function("chunk packet cached", |b| {
+        b.iter(|| black_box(chunk.packet().unwrap().frame(256).unwrap()))
+    });
+
+    c.bench_function("chunk packet changed", |b| {
+        let mut stone = false;
+        b.iter(|| {
+            stone = !stone;
+            chunk.set_block(0, 0, 0, if stone { &Block::Stone } else { &Block::Dirt });
+            black_box(chunk.packet().unwrap().frame(256).unwrap())
+        })
+    });
+}
+
+criterion_group!(benches, encode_chunk);
+criterion_main!(benches);
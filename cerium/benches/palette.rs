@@ -0,0 +1,64 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use cerium::world::palette::Palette;
+
+/// A section with a few hundred different blocks, like terrain with ores and plants.
+fn mixed_palette() -> Palette {
+    let mut palette = Palette::blocks();
+    for y in 0..16 {
+        for z in 0..16 {
+            for x in 0..16 {
+                palette.set(x, y, z, ((x * 7 + y * 13 + z * 3) % 200) as u16 + 1);
+            }
+        }
+    }
+    palette
+}
+
+fn set_block(c: &mut Criterion) {
+    c.bench_function("palette set single value", |b| {
+        let mut palette = Palette::blocks();
+        b.iter(|| {
+            for y in 0..16 {
+                palette.set(0, y, 0, black_box(0));
+            }
+        })
+    });
+
+    c.bench_function("palette fill section", |b| {
+        b.iter(|| black_box(mixed_palette()))
+    });
+
+    c.bench_function("palette set mixed", |b| {
+        let mut palette = mixed_palette();
+        let mut value = 0;
+        b.iter(|| {
+            for y in 0..16 {
+                value = (value + 1) % 200;
+                palette.set(3, y, 5, black_box(value + 1));
+            }
+        })
+    });
+}
+
+fn get_block(c: &mut Criterion) {
+    let palette = mixed_palette();
+    c.bench_function("palette get section", |b| {
+        b.iter(|| {
+            let mut sum = 0u32;
+            for y in 0..16 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        sum += palette.get(x, y, z) as u32;
+                    }
+                }
+            }
+            black_box(sum)
+        })
+    });
+}
+
+criterion_group!(benches, set_block, get_block);
+criterion_main!(benches);
@@ -32,12 +32,82 @@ pub fn generate() {
         })
         .collect();
 
+    let to_key_arms: TokenStream = variants
+        .iter()
+        .map(|(_, ident, key)| {
+            quote! {
+                Self::#ident => #key,
+            }
+        })
+        .collect();
+
+    let from_key_arms: TokenStream = variants
+        .iter()
+        .map(|(_, ident, key)| {
+            quote! {
+                #key => Some(Self::#ident),
+            }
+        })
+        .collect();
+
+    let dimension_arms = |field: &str| -> TokenStream {
+        variants
+            .iter()
+            .map(|(_, ident, key)| {
+                let value = entries[key][field].as_f64().unwrap_or(0.) as f32;
+                quote! {
+                    Self::#ident => #value,
+                }
+            })
+            .collect()
+    };
+    let width_arms = dimension_arms("width");
+    let height_arms = dimension_arms("height");
+    let eye_height_arms = dimension_arms("eyeHeight");
+
     let out = quote! {
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         #[repr(i32)]
         pub enum EntityType {
             #enum_variants
         }
+
+        impl EntityType {
+            pub fn key(&self) -> &'static str {
+                match self {
+                    #to_key_arms
+                }
+            }
+
+            /// Returns the width of the hitbox of the entity when standing.
+            pub fn width(&self) -> f32 {
+                match self {
+                    #width_arms
+                }
+            }
+
+            /// Returns the height of the hitbox of the entity when standing.
+            pub fn height(&self) -> f32 {
+                match self {
+                    #height_arms
+                }
+            }
+
+            /// Returns the height of the eyes of the entity above its feet when standing.
+            pub fn eye_height(&self) -> f32 {
+                match self {
+                    #eye_height_arms
+                }
+            }
+
+            /// Returns the entity type with the key, e.g. `minecraft:zombie`.
+            pub fn from_key(key: &str) -> Option<EntityType> {
+                match key {
+                    #from_key_arms
+                    _ => None,
+                }
+            }
+        }
     };
 
     write_file(&out, "entity_types.rs");
@@ -1,11 +1,12 @@
 use cerium::Server;
+use cerium::ServerConfig;
 use cerium::entity::GameMode;
 use cerium::event::player::{PlayerConfigEvent, PlayerEvent, PlayerSpawnEvent};
 use cerium::registry::DimensionType;
 use cerium::world::{BlockState, World};
 
 fn main() {
-    let server = Server::new();
+    let server = Server::new(ServerConfig::default()).unwrap();
 
     let world = World::new(&DimensionType::OVERWORLD);
 
@@ -1,10 +1,11 @@
 use cerium::Server;
+use cerium::ServerConfig;
 use cerium::event::player::PlayerConfigEvent;
 use cerium::registry::DimensionType;
 use cerium::world::{Block, World};
 
 fn main() {
-    let server = Server::new();
+    let server = Server::new(ServerConfig::default()).unwrap();
 
     let world = World::new(&DimensionType::OVERWORLD);
 
@@ -1,4 +1,5 @@
 use cerium::Server;
+use cerium::ServerConfig;
 use cerium::event::player::{PlayerConfigEvent, PlayerEvent, PlayerSpawnEvent};
 use cerium::inventory::{Inventory, InventoryType};
 use cerium::item::{ItemStack, Material};
@@ -6,7 +7,7 @@ use cerium::registry::DimensionType;
 use cerium::world::{Block, World};
 
 fn main() {
-    let server = Server::new();
+    let server = Server::new(ServerConfig::default()).unwrap();
 
     let world = World::new(&DimensionType::OVERWORLD);
 
@@ -1,4 +1,5 @@
 use cerium::Server;
+use cerium::ServerConfig;
 use cerium::entity::{Entity, EntityLike as _, EntityType, GameMode};
 use cerium::event::player::{PlayerConfigEvent, PlayerEvent as _, PlayerSpawnEvent};
 use cerium::protocol::packet::{
@@ -9,7 +10,7 @@ use cerium::util::Viewable;
 use cerium::world::{Block, World};
 
 fn main() {
-    let server = Server::new();
+    let server = Server::new(ServerConfig::default()).unwrap();
 
     let world = World::new(&DimensionType::OVERWORLD);
 
@@ -1,4 +1,5 @@
 use cerium::Server;
+use cerium::ServerConfig;
 use cerium::entity::Player;
 use cerium::event::player::{PlayerConfigEvent, PlayerEvent, PlayerSpawnEvent};
 use cerium::registry::DimensionType;
@@ -8,7 +9,7 @@ use cerium::util::Viewable;
 use cerium::world::{Block, World};
 
 fn main() {
-    let server = Server::new();
+    let server = Server::new(ServerConfig::default()).unwrap();
 
     let world = World::new(&DimensionType::OVERWORLD);
 
@@ -24,6 +24,10 @@ pub enum AuthError {
     FailedRequest,
     #[error("Failed to parse profile json")]
     MalformedJson,
+    #[error("Invalid RSA key size: {0} bits")]
+    InvalidKeySize(usize),
+    #[error("Failed to generate RSA key: {0}")]
+    KeyGenerationError(rsa::Error),
 }
 
 #[derive(Debug)]
@@ -33,11 +37,17 @@ pub struct KeyStore {
 }
 
 impl KeyStore {
-    pub fn new() -> Self {
+    /// Generates a key with the size in bits, which must be between 512 and 4096.
+    pub fn new(bits: usize) -> Result<Self, AuthError> {
         use rsa::{RsaPrivateKey, traits::PublicKeyParts as _};
 
+        if !(512..=4096).contains(&bits) {
+            return Err(AuthError::InvalidKeySize(bits));
+        }
+
         let mut rand = rand::thread_rng();
-        let private_key = RsaPrivateKey::new(&mut rand, 1024).unwrap();
+        let private_key =
+            RsaPrivateKey::new(&mut rand, bits).map_err(AuthError::KeyGenerationError)?;
 
         let public_key_der = rsa_der::public_key_to_der(
             &private_key.n().to_bytes_be(),
@@ -45,10 +55,10 @@ impl KeyStore {
         )
         .into_boxed_slice();
 
-        Self {
+        Ok(Self {
             private_key,
             public_key_der,
-        }
+        })
     }
 
     pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, AuthError> {
@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+    time::Duration,
+};
+
+use crate::network::{MAX_DECOMPRESSED_SIZE, MAX_PACKET_SIZE};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("std::io::Error: {0}")]
+    IoError(std::io::Error),
+    #[error("Failed to parse config: {0}")]
+    ParseError(toml::de::Error),
+}
+
+/// Configuration of a [`Server`](crate::Server).
+///
+/// A config can either be created with [`ServerConfig::builder`] or loaded from a TOML file.
+/// Every missing value falls back to its default.
+///
+/// # Example
+/// ```no_run
+/// use cerium::{Server, ServerConfig};
+///
+/// let config = ServerConfig::builder()
+///     .online_mode(true)
+///     .view_distance(12)
+///     .build();
+/// let server = Server::new(config).unwrap();
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Whether players are authenticated with the Mojang session servers.
+    pub online_mode: bool,
+    /// How player information is forwarded by a proxy in front of the server.
+    pub forwarding: ForwardingMode,
+    /// The view distance (in chunks) used to send chunks to players.
+    pub view_distance: i32,
+    /// The simulation distance (in chunks) sent to players.
+    pub simulation_distance: i32,
+    /// The first 8 bytes of the SHA-256 hash of the world seed.
+    pub hashed_seed: i64,
+    pub network: NetworkConfig,
+    pub limits: LimitsConfig,
+}
+
+impl ServerConfig {
+    pub fn builder() -> ServerConfigBuilder {
+        ServerConfigBuilder::new()
+    }
+
+    /// Loads the config from a TOML file.
+    pub fn from_file<P>(path: P) -> Result<Self, ConfigError>
+    where
+        P: AsRef<Path>,
+    {
+        let content = std::fs::read_to_string(path).map_err(ConfigError::IoError)?;
+        Self::from_toml(&content)
+    }
+
+    /// Parses the config from a TOML string.
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let config: ServerConfig = toml::from_str(content).map_err(ConfigError::ParseError)?;
+        Ok(config.normalized())
+    }
+
+    /// Clamps all values to the ranges accepted by the client.
+    fn normalized(mut self) -> Self {
+        self.view_distance = self.view_distance.clamp(2, 32);
+        self.simulation_distance = self.simulation_distance.clamp(2, 32);
+        self
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            online_mode: false,
+            forwarding: ForwardingMode::None,
+            view_distance: 10,
+            simulation_distance: 8,
+            hashed_seed: 93522819,
+            network: NetworkConfig::default(),
+            limits: LimitsConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardingMode {
+    /// Players connect directly to the server.
+    None,
+    /// BungeeCord style forwarding, where the proxy appends the address,
+    /// uuid and properties of the player to the server address of the handshake.
+    Legacy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// The address the server listens on, see [`Server::run`](crate::Server::run).
+    pub address: IpAddr,
+    pub port: u16,
+    /// Packets with at least this size are compressed. A negative value disables compression.
+    pub compression_threshold: i32,
+    /// The interval in which keep alive packets are sent.
+    #[serde(with = "duration_secs")]
+    pub keep_alive_interval: Duration,
+    /// Players that do not answer a keep alive packet within this time are disconnected.
+    #[serde(with = "duration_secs")]
+    pub keep_alive_timeout: Duration,
+    /// How long a shutdown waits for connections to send their remaining packets.
+    #[serde(with = "duration_secs")]
+    pub shutdown_timeout: Duration,
+    /// The size of the RSA key used for encryption.
+    pub rsa_key_bits: usize,
+}
+
+impl NetworkConfig {
+    /// Returns the address and port the server listens on.
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.address, self.port)
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 25565,
+            compression_threshold: 256,
+            keep_alive_interval: Duration::from_secs(20),
+            keep_alive_timeout: Duration::from_secs(30),
+            shutdown_timeout: Duration::from_secs(10),
+            rsa_key_bits: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// The maximum number of players that can be online at the same time.
+    pub max_players: u32,
+    /// The maximum size of a packet sent by a client, in bytes.
+    pub max_packet_size: usize,
+    /// The maximum size of a decompressed packet sent by a client, in bytes.
+    pub max_decompressed_size: usize,
+    /// Clients sending more packets per second are disconnected. `0` disables the limit.
+    pub packets_per_second: u32,
+    /// The minimum time between two login attempts from the same IP address.
+    #[serde(with = "duration_secs")]
+    pub login_throttle: Duration,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_players: 20,
+            max_packet_size: MAX_PACKET_SIZE,
+            max_decompressed_size: MAX_DECOMPRESSED_SIZE,
+            packets_per_second: 500,
+            login_throttle: Duration::from_secs(4),
+        }
+    }
+}
+
+pub struct ServerConfigBuilder {
+    config: ServerConfig,
+}
+
+impl ServerConfigBuilder {
+    fn new() -> Self {
+        Self {
+            config: ServerConfig::default(),
+        }
+    }
+
+    pub fn online_mode(mut self, value: bool) -> Self {
+        self.config.online_mode = value;
+        self
+    }
+
+    pub fn forwarding(mut self, value: ForwardingMode) -> Self {
+        self.config.forwarding = value;
+        self
+    }
+
+    pub fn view_distance(mut self, value: i32) -> Self {
+        self.config.view_distance = value;
+        self
+    }
+
+    pub fn simulation_distance(mut self, value: i32) -> Self {
+        self.config.simulation_distance = value;
+        self
+    }
+
+    pub fn hashed_seed(mut self, value: i64) -> Self {
+        self.config.hashed_seed = value;
+        self
+    }
+
+    pub fn address(mut self, value: IpAddr) -> Self {
+        self.config.network.address = value;
+        self
+    }
+
+    pub fn port(mut self, value: u16) -> Self {
+        self.config.network.port = value;
+        self
+    }
+
+    pub fn compression_threshold(mut self, value: i32) -> Self {
+        self.config.network.compression_threshold = value;
+        self
+    }
+
+    pub fn keep_alive_interval(mut self, value: Duration) -> Self {
+        self.config.network.keep_alive_interval = value;
+        self
+    }
+
+    pub fn keep_alive_timeout(mut self, value: Duration) -> Self {
+        self.config.network.keep_alive_timeout = value;
+        self
+    }
+
+    pub fn shutdown_timeout(mut self, value: Duration) -> Self {
+        self.config.network.shutdown_timeout = value;
+        self
+    }
+
+    pub fn rsa_key_bits(mut self, value: usize) -> Self {
+        self.config.network.rsa_key_bits = value;
+        self
+    }
+
+    pub fn max_players(mut self, value: u32) -> Self {
+        self.config.limits.max_players = value;
+        self
+    }
+
+    pub fn max_packet_size(mut self, value: usize) -> Self {
+        self.config.limits.max_packet_size = value;
+        self
+    }
+
+    pub fn max_decompressed_size(mut self, value: usize) -> Self {
+        self.config.limits.max_decompressed_size = value;
+        self
+    }
+
+    pub fn packets_per_second(mut self, value: u32) -> Self {
+        self.config.limits.packets_per_second = value;
+        self
+    }
+
+    pub fn login_throttle(mut self, value: Duration) -> Self {
+        self.config.limits.login_throttle = value;
+        self
+    }
+
+    pub fn build(self) -> ServerConfig {
+        self.config.normalized()
+    }
+}
+
+/// (De)serializes a [`Duration`] as whole seconds.
+mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(value.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_secs(u64::deserialize(d)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = ServerConfig::from_toml(
+            r#"
+            online_mode = true
+            forwarding = "legacy"
+            view_distance = 64
+
+            [network]
+            address = "127.0.0.1"
+            port = 25566
+            compression_threshold = -1
+            keep_alive_interval = 10
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.online_mode);
+        assert_eq!(config.forwarding, ForwardingMode::Legacy);
+        assert_eq!(config.view_distance, 32);
+        assert_eq!(config.simulation_distance, 8);
+        assert_eq!(
+            config.network.socket_addr(),
+            "127.0.0.1:25566".parse().unwrap()
+        );
+        assert_eq!(config.network.compression_threshold, -1);
+        assert_eq!(config.network.keep_alive_interval, Duration::from_secs(10));
+        assert_eq!(config.network.keep_alive_timeout, Duration::from_secs(30));
+        assert_eq!(config.limits.max_players, 20);
+        assert_eq!(config.limits.max_packet_size, MAX_PACKET_SIZE);
+    }
+
+    #[test]
+    fn test_builder() {
+        let config = ServerConfig::builder()
+            .view_distance(1)
+            .max_players(100)
+            .build();
+
+        assert_eq!(config.view_distance, 2);
+        assert_eq!(config.limits.max_players, 100);
+    }
+}
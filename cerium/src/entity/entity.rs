@@ -1,4 +1,5 @@
 use parking_lot::Mutex;
+use std::collections::BTreeMap;
 use std::sync::{
     Arc,
     atomic::{AtomicBool, AtomicI32, Ordering},
@@ -6,9 +7,12 @@ use std::sync::{
 use uuid::Uuid;
 
 use crate::{
-    entity::{EntityType, Player, meta::entity::EntityMeta},
-    protocol::packet::{RemoveEntitiesPacket, SetEntityMetadataPacket, SpawnEntityPacket},
-    util::{EntityPose, Position, Viewable, Viewers},
+    entity::{EntityDimensions, EntityType, EquipmentSlot, Player, meta::entity::EntityMeta},
+    item::{ItemStack, Material},
+    protocol::packet::{
+        RemoveEntitiesPacket, SetEntityMetadataPacket, SetEquipmentPacket, SpawnEntityPacket,
+    },
+    util::{BoundingBox, EntityPose, Position, Viewable, Viewers},
     world::World,
 };
 
@@ -47,6 +51,27 @@ impl Entity {
         self.0.set_on_fire(value)
     }
 
+    /// Returns the item of an item entity.
+    pub fn item(&self) -> ItemStack {
+        self.0.item()
+    }
+
+    /// Sets the item of an item entity.
+    pub fn set_item(&self, item: ItemStack) {
+        self.0.set_item(item)
+    }
+
+    /// Returns the item the entity holds or wears in the slot.
+    pub fn equipment(&self, slot: EquipmentSlot) -> ItemStack {
+        self.0.equipment(slot)
+    }
+
+    /// Sets the item the entity holds or wears in the slot, like the armor of a zombie or the held item
+    /// of an npc.
+    pub fn set_equipment(&self, slot: EquipmentSlot, item: ItemStack) {
+        self.0.set_equipment(slot, item)
+    }
+
     pub fn is_sneaking(&self) -> bool {
         self.0.is_sneaking()
     }
@@ -67,6 +92,16 @@ impl Entity {
         self.0.pose()
     }
 
+    /// Returns the dimensions of the entity in its current pose.
+    pub fn dimensions(&self) -> EntityDimensions {
+        self.r#type().dimensions(self.pose())
+    }
+
+    /// Returns the hitbox of the entity, sized by its type and pose.
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.dimensions().bounding_box(self.position())
+    }
+
     pub fn is_on_ground(&self) -> bool {
         self.0.is_on_ground()
     }
@@ -123,6 +158,7 @@ pub(crate) struct Inner {
     position: Mutex<Position>,
     head_rotation: Mutex<f32>,
     meta: Mutex<EntityMeta>,
+    equipment: Mutex<BTreeMap<EquipmentSlot, ItemStack>>,
     on_ground: AtomicBool,
     viewers: Viewers,
 }
@@ -136,6 +172,7 @@ impl Inner {
             position: Mutex::new(Position::ZERO),
             head_rotation: Mutex::new(0.),
             meta: Mutex::new(EntityMeta::new()),
+            equipment: Mutex::new(BTreeMap::new()),
             on_ground: AtomicBool::default(),
             viewers: Viewers::new(),
         }
@@ -179,6 +216,56 @@ impl Inner {
         self.refresh_meta();
     }
 
+    pub fn item(&self) -> ItemStack {
+        self.meta.lock().get_item()
+    }
+
+    pub fn set_item(&self, item: ItemStack) {
+        {
+            let mut meta = self.meta.lock();
+            meta.set_item(item);
+        }
+        self.refresh_meta();
+    }
+
+    pub fn equipment(&self, slot: EquipmentSlot) -> ItemStack {
+        self.equipment
+            .lock()
+            .get(&slot)
+            .cloned()
+            .unwrap_or(ItemStack::EMPTY)
+    }
+
+    pub fn set_equipment(&self, slot: EquipmentSlot, item: ItemStack) {
+        {
+            let mut equipment = self.equipment.lock();
+            if item.material() == Material::Air || item.amount() <= 0 {
+                equipment.remove(&slot);
+            } else {
+                equipment.insert(slot, item.clone());
+            }
+        }
+        self.send_packet_to_viewers(&SetEquipmentPacket {
+            entity_id: self.id(),
+            equipment: vec![(slot, item.into())],
+        });
+    }
+
+    /// Returns the packet showing all equipment, or `None` if the entity has none.
+    fn equipment_packet(&self) -> Option<SetEquipmentPacket> {
+        let equipment = self.equipment.lock();
+        if equipment.is_empty() {
+            return None;
+        }
+        Some(SetEquipmentPacket {
+            entity_id: self.id(),
+            equipment: equipment
+                .iter()
+                .map(|(slot, item)| (*slot, item.clone().into()))
+                .collect(),
+        })
+    }
+
     pub fn is_sneaking(&self) -> bool {
         self.meta.lock().is_sneaking()
     }
@@ -259,8 +346,14 @@ impl Viewable for Inner {
     fn add_viewer(&self, player: Player) {
         self.viewers.add_viewer(player.clone());
 
-        player.send_packet(&self.spawn_packet());
-        player.send_packet(&self.metadata_packet());
+        // Bundled, so the client never renders the entity without its metadata and equipment
+        player.send_bundle(|bundle| {
+            bundle.add(&self.spawn_packet());
+            bundle.add(&self.metadata_packet());
+            if let Some(packet) = self.equipment_packet() {
+                bundle.add(&packet);
+            }
+        });
     }
 
     fn remove_viewer(&self, player: Player) {
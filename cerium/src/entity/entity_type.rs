@@ -1 +1,115 @@
+use crate::util::{BoundingBox, EntityPose, Position};
+
 include!("../registry/generated/entity_types.rs");
+
+/// The size of the hitbox of an entity and the height of its eyes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityDimensions {
+    pub width: f32,
+    pub height: f32,
+    pub eye_height: f32,
+}
+
+impl EntityDimensions {
+    pub const fn new(width: f32, height: f32, eye_height: f32) -> Self {
+        Self {
+            width,
+            height,
+            eye_height,
+        }
+    }
+
+    /// Returns the hitbox of an entity with the dimensions standing at the position.
+    pub fn bounding_box(&self, position: Position) -> BoundingBox {
+        BoundingBox::at_feet(position, self.width as f64, self.height as f64)
+    }
+}
+
+impl EntityType {
+    /// Returns the dimensions of the entity in the pose, like the lower hitbox of a sneaking player.
+    pub fn dimensions(&self, pose: EntityPose) -> EntityDimensions {
+        const SLEEPING: EntityDimensions = EntityDimensions::new(0.2, 0.2, 0.2);
+
+        match (self, pose) {
+            (_, EntityPose::Sleeping) => SLEEPING,
+            (EntityType::Player, EntityPose::Dying) => SLEEPING,
+            (EntityType::Player, EntityPose::Sneaking) => EntityDimensions::new(0.6, 1.5, 1.27),
+            (
+                EntityType::Player,
+                EntityPose::FallFlying | EntityPose::Swimming | EntityPose::SpinAttack,
+            ) => EntityDimensions::new(0.6, 0.6, 0.4),
+            _ => EntityDimensions::new(self.width(), self.height(), self.eye_height()),
+        }
+    }
+
+    /// Returns whether blocks can not be placed inside of the entity, which is the case for players, mobs
+    /// and vehicles but not for items, experience orbs, projectiles and markers.
+    pub fn blocks_building(&self) -> bool {
+        !matches!(
+            self,
+            EntityType::AreaEffectCloud
+                | EntityType::Arrow
+                | EntityType::BlockDisplay
+                | EntityType::BreezeWindCharge
+                | EntityType::DragonFireball
+                | EntityType::Egg
+                | EntityType::EnderPearl
+                | EntityType::EvokerFangs
+                | EntityType::ExperienceBottle
+                | EntityType::ExperienceOrb
+                | EntityType::EyeOfEnder
+                | EntityType::Fireball
+                | EntityType::FireworkRocket
+                | EntityType::FishingBobber
+                | EntityType::GlowItemFrame
+                | EntityType::Interaction
+                | EntityType::Item
+                | EntityType::ItemDisplay
+                | EntityType::ItemFrame
+                | EntityType::LeashKnot
+                | EntityType::LightningBolt
+                | EntityType::LingeringPotion
+                | EntityType::LlamaSpit
+                | EntityType::Marker
+                | EntityType::OminousItemSpawner
+                | EntityType::Painting
+                | EntityType::ShulkerBullet
+                | EntityType::SmallFireball
+                | EntityType::Snowball
+                | EntityType::SpectralArrow
+                | EntityType::SplashPotion
+                | EntityType::TextDisplay
+                | EntityType::Trident
+                | EntityType::WindCharge
+                | EntityType::WitherSkull
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimensions() {
+        let standing = EntityType::Player.dimensions(EntityPose::Standing);
+        assert_eq!(standing, EntityDimensions::new(0.6, 1.8, 1.62));
+        assert_eq!(
+            EntityType::Player.dimensions(EntityPose::Sneaking).height,
+            1.5
+        );
+        assert_eq!(
+            EntityType::Player.dimensions(EntityPose::Swimming).height,
+            0.6
+        );
+
+        // Only players change their hitbox when sneaking
+        let pig = EntityType::Pig.dimensions(EntityPose::Standing);
+        assert_eq!(EntityType::Pig.dimensions(EntityPose::Sneaking), pig);
+        assert_eq!(EntityType::Pig.dimensions(EntityPose::Sleeping).height, 0.2);
+
+        let hitbox = standing.bounding_box(Position::new(0.5, 64., 0.5, 0., 0.));
+        assert!((hitbox.max_y() - 65.8).abs() < 1e-6);
+        assert!((hitbox.min_x() - 0.2).abs() < 1e-6);
+    }
+}
@@ -2,6 +2,7 @@ use rustc_hash::FxHashMap;
 
 use crate::{
     entity::meta::{MetadataHolder, MetadataRef},
+    item::ItemStack,
     util::EntityPose,
 };
 
@@ -48,6 +49,16 @@ impl EntityMeta {
         self.holder.set(MetadataRef::POSE, pose);
     }
 
+    pub fn set_item(&mut self, item: ItemStack) {
+        self.holder.set(MetadataRef::ITEM, item);
+    }
+
+    pub fn get_item(&self) -> ItemStack {
+        self.holder
+            .get(MetadataRef::ITEM)
+            .unwrap_or(ItemStack::EMPTY)
+    }
+
     pub fn get_pose(&self) -> EntityPose {
         self.holder
             .get(MetadataRef::POSE)
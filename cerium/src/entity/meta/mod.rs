@@ -5,6 +5,7 @@ use rustc_hash::FxHashMap;
 pub mod entity;
 
 use crate::item::DataType2;
+use crate::item::ItemStack;
 use crate::item::VarInt;
 
 use crate::protocol::decode::DecodeError;
@@ -163,6 +164,8 @@ impl MetadataRef<()> {
     pub const POSE: MetadataRef<EntityPose>                 = MetadataRef::new(6, ValueType::POSE, EntityPose::Standing);
     pub const TICKS_FROZEN_IN_POWDER_SNOW: MetadataRef<i32> = MetadataRef::new(7, ValueType::VAR_INT, 0);
 
+    pub const ITEM: MetadataRef<ItemStack>                  = MetadataRef::new(8, ValueType::SLOT, ItemStack::EMPTY);
+
     pub const WEATHERING_COPPER_STATE: MetadataRef<WeatheringCopperState> = MetadataRef::new(
         16,
         ValueType::WEATHERING_COPPER_STATE,
@@ -208,7 +211,7 @@ macro_rules! define_types {
                 let type_id = r.read_varint()?;
                 let value: Arc<dyn Any + Send + Sync> = match type_id {
                     $($id => decode!(r, $ty $(, $ser)?),)*
-                    _ => panic!("error")
+                    _ => return Err(DecodeError::Decode("Unknown metadata type"))
                 };
                 Ok(AnyValue {
                     type_id,
@@ -222,12 +225,12 @@ macro_rules! define_types {
                     $($id => {
                         let value = match self.value.downcast_ref::<$ty>() {
                             Some(v) => v,
-                            None => panic!("error"),
+                            None => return Err(EncodeError::Encode(format!("Invalid value for metadata type {}", $id))),
                         };
 
                         encode!(w, value, $ty $(, $ser)?)
                     },)*
-                    _ => panic!("error")
+                    _ => return Err(EncodeError::Encode(format!("Unknown metadata type {}", self.type_id())))
                 }
                 Ok(())
             }
@@ -260,6 +263,7 @@ define_types! {
     const VAR_INT: ValueType<i32, VarInt> = ValueType::new(1);
 
     const OPTIONAL_TEXT_COMPONENT: ValueType<Option<TextComponent>> = ValueType::new(6);
+    const SLOT: ValueType<ItemStack> = ValueType::new(7);
     const POSE: ValueType<EntityPose> = ValueType::new(20);
 
     const COPPER_GOLEM_STATE: ValueType<CopperGolemState> = ValueType::new(32);
@@ -323,7 +327,8 @@ impl TryFrom<i32> for CopperGolemState {
 
 impl DataType2<CopperGolemState> for CopperGolemState {
     fn decode<R: PacketRead>(r: &mut R) -> Result<CopperGolemState, DecodeError> {
-        CopperGolemState::try_from(r.read_varint()?).map_err(|_| todo!())
+        CopperGolemState::try_from(r.read_varint()?)
+            .map_err(|_| DecodeError::Decode("Invalid CopperGolemState"))
     }
 
     fn encode<W: PacketWrite>(w: &mut W, this: &CopperGolemState) -> Result<(), EncodeError> {
@@ -356,7 +361,8 @@ impl TryFrom<i32> for WeatheringCopperState {
 
 impl DataType2<WeatheringCopperState> for WeatheringCopperState {
     fn decode<R: PacketRead>(r: &mut R) -> Result<WeatheringCopperState, DecodeError> {
-        WeatheringCopperState::try_from(r.read_varint()?).map_err(|_| todo!())
+        WeatheringCopperState::try_from(r.read_varint()?)
+            .map_err(|_| DecodeError::Decode("Invalid WeatheringCopperState"))
     }
 
     fn encode<W: PacketWrite>(w: &mut W, this: &WeatheringCopperState) -> Result<(), EncodeError> {
@@ -366,10 +372,32 @@ impl DataType2<WeatheringCopperState> for WeatheringCopperState {
 
 impl DataType2<EntityPose> for EntityPose {
     fn decode<R: PacketRead>(r: &mut R) -> Result<EntityPose, DecodeError> {
-        EntityPose::try_from(r.read_varint()?).map_err(|_| todo!())
+        EntityPose::try_from(r.read_varint()?)
+            .map_err(|_| DecodeError::Decode("Invalid EntityPose"))
     }
 
     fn encode<W: PacketWrite>(w: &mut W, this: &EntityPose) -> Result<(), EncodeError> {
         w.write_varint(*this as i32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_invalid_enum() {
+        let data = [99];
+        let result = <EntityPose as DataType2<EntityPose>>::decode(&mut Cursor::new(&data[..]));
+        assert!(matches!(
+            result,
+            Err(DecodeError::Decode("Invalid EntityPose"))
+        ));
+
+        let data = [1];
+        let result = <EntityPose as DataType2<EntityPose>>::decode(&mut Cursor::new(&data[..]));
+        assert_eq!(result.unwrap(), EntityPose::FallFlying);
+    }
+}
@@ -8,7 +8,7 @@ mod player;
 
 pub use entity::{Entity, EntityLike};
 pub use entity_animation::EntityAnimation;
-pub use entity_type::EntityType;
+pub use entity_type::{EntityDimensions, EntityType};
 pub use game_mode::GameMode;
 pub use hand::Hand;
-pub use player::Player;
+pub use player::{EquipmentSlot, Player};
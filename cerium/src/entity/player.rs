@@ -6,7 +6,6 @@ use std::{
         Arc,
         atomic::{AtomicBool, AtomicI32, AtomicU8, Ordering},
     },
-    time::{Duration, Instant},
 };
 use uuid::Uuid;
 
@@ -19,22 +18,36 @@ use crate::{
     },
     event::{Cancellable, inventory::InventoryOpenEvent},
     inventory::{Inventory, PlayerInventory},
-    item::ItemStack,
+    item::{DataComponent, Enchantments, ItemStack, Material},
     network::client::Connection,
-    protocol::packet::{
-        ChunkBatchStartPacket, ChunkDataAndUpdateLightPacket, EntityPositionRotationPacket,
-        EntityRotationPacket, GameEventPacket, Packet, PlayerAbilities, PlayerAction, PlayerEntry,
-        PlayerInfoFlags, PlayerInfoRemovePacket, PlayerInfoUpdatePacket, ServerPacket,
-        SetCenterChunkPacket, SetHeadRotationPacket, SetTablistHeaderFooterPacket,
-        SyncPlayerPositionPacket, SystemChatMessagePacket, UnloadChunkPacket,
-        server::{PlayerAbilitiesPacket, SetHeldItemPacket, play::KeepAlivePacket},
+    protocol::{
+        Bundle, FramedPacket,
+        packet::{
+            ChunkBatchStartPacket, EntityPositionRotationPacket, EntityRotationPacket,
+            GameEventPacket, Packet, PlayerAbilities, PlayerAction, PlayerEntry, PlayerInfoFlags,
+            PlayerInfoRemovePacket, PlayerInfoUpdatePacket, RespawnPacket, ServerPacket,
+            SetBlockDestroyStagePacket, SetCenterChunkPacket, SetEquipmentPacket,
+            SetHeadRotationPacket, SetTablistHeaderFooterPacket, SyncPlayerPositionPacket,
+            SystemChatMessagePacket, UnloadChunkPacket,
+            server::{PlayerAbilitiesPacket, SetHeldItemPacket},
+        },
     },
+    registry::REGISTRIES,
     text::TextComponent,
     tickable::Tickable,
-    util::{EntityPose, Position, TeleportFlags, Viewable, Viewers},
-    world::{Chunk, World},
+    util::{
+        BlockPosition, BoundingBox, ChunkPos, EntityPose, Position, TeleportFlags, Viewable,
+        Viewers,
+    },
+    world::{
+        BlockHit, Chunk, DigConditions, Digging, EntityHit, EntityTarget, World, break_progress,
+    },
 };
 
+/// How far players can reach blocks, measured from their eyes.
+const BLOCK_INTERACTION_RANGE: f64 = 4.5;
+const CREATIVE_BLOCK_INTERACTION_RANGE: f64 = 5.;
+
 #[derive(Clone, PartialEq)]
 pub struct Player(pub(crate) Arc<Inner>);
 
@@ -51,6 +64,11 @@ impl Player {
         self.0.name()
     }
 
+    /// Returns the latency of the player in milliseconds, measured with keep alive packets.
+    pub fn ping(&self) -> i32 {
+        self.0.connection.latency()
+    }
+
     pub fn game_mode(&self) -> GameMode {
         self.0.game_mode()
     }
@@ -74,6 +92,23 @@ impl Player {
         self.0.send_packet(packet)
     }
 
+    /// Sends the packets added to the bundle, which the client applies at once.
+    pub fn send_bundle<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Bundle),
+    {
+        let mut bundle = Bundle::new();
+        f(&mut bundle);
+        for frame in bundle.frames() {
+            self.0.connection.send_framed_packet(frame);
+        }
+    }
+
+    /// Sends a packet that was encoded beforehand, e.g. to send the same packet to many players.
+    pub fn send_framed_packet(&self, packet: &Arc<FramedPacket>) {
+        self.0.connection.send_framed_packet(packet.clone())
+    }
+
     pub fn server(&self) -> &Arc<Server> {
         &self.0.server()
     }
@@ -118,6 +153,26 @@ impl Player {
         self.0.set_held_slot(slot)
     }
 
+    // ===== World ======
+
+    /// Moves the player into another world.
+    ///
+    /// The client respawns into the dimension of the world, the chunks around the position are sent,
+    /// and only players of the new world stay visible.
+    pub fn set_world(&self, world: World, position: impl Into<Position>) {
+        self.0.set_world(world, position.into())
+    }
+
+    /// Returns the time of day this player sees instead of the time of its world.
+    pub fn time_override(&self) -> Option<i64> {
+        self.0.time_override()
+    }
+
+    /// Shows the player a fixed time of day, or the time of its world again if `None`.
+    pub fn set_time_override(&self, time: Option<i64>) {
+        self.0.set_time_override(time)
+    }
+
     // ===== Position & Movement ======
 
     pub fn refresh_position(&self, new_position: Position) {
@@ -218,6 +273,47 @@ impl Player {
         self.0.is_sneaking()
     }
 
+    /// Returns the hitbox of the player, which is lower while sneaking or swimming.
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.0.entity.bounding_box()
+    }
+
+    /// Returns the position of the eyes of the player, looking where the player looks.
+    pub fn eye_position(&self) -> Position {
+        let position = self.position();
+        position.with_y(position.y() + self.0.eye_height())
+    }
+
+    // ===== Targeting =====
+
+    /// Returns the block the player looks at within the distance.
+    pub fn target_block(&self, max_distance: f64) -> Option<BlockHit> {
+        let eyes = self.eye_position();
+        self.world()
+            .raycast_block(eyes, eyes.direction(), max_distance)
+    }
+
+    /// Returns the entity or player the player looks at within the distance.
+    ///
+    /// Spectators are not targeted, and neither is anything behind the block the player looks at.
+    pub fn target_entity(&self, max_distance: f64) -> Option<EntityHit> {
+        let eyes = self.eye_position();
+        let max_distance = self
+            .target_block(max_distance)
+            .map_or(max_distance, |hit| hit.distance);
+        self.world().raycast_entity(
+            eyes,
+            eyes.direction(),
+            max_distance,
+            |target| match target {
+                EntityTarget::Player(player) => {
+                    player != self && player.game_mode() != GameMode::Spectator
+                }
+                EntityTarget::Entity(_) => true,
+            },
+        )
+    }
+
     // ===== Scoreboard =====
 
     /// Changes the tablist header for the player.
@@ -343,7 +439,7 @@ pub(crate) struct Inner {
     game_profile: GameProfile,
     entity: Entity,
     world: Mutex<Option<World>>,
-    last_keep_alive: Mutex<Instant>,
+    time_override: Mutex<Option<i64>>,
     game_mode: Mutex<GameMode>,
     pub(crate) chunk_queue: Mutex<ChunkQueue>,
     teleport_id: AtomicI32,
@@ -356,6 +452,8 @@ pub(crate) struct Inner {
     open_inventory: Mutex<Option<Inventory>>,
     held_slot: AtomicU8,
 
+    digging: Mutex<Option<Digging>>,
+
     server: Arc<Server>,
 }
 
@@ -367,7 +465,7 @@ impl Inner {
             game_profile: game_profile.clone(),
             entity: Entity::new_with_uuid(EntityType::Player, game_profile.uuid),
             world: Mutex::new(None),
-            last_keep_alive: Mutex::new(Instant::now()),
+            time_override: Mutex::new(None),
             game_mode: Mutex::new(GameMode::Survival),
             chunk_queue: Mutex::new(ChunkQueue::new()),
             teleport_id: AtomicI32::default(),
@@ -375,6 +473,7 @@ impl Inner {
             inventory: Arc::new(PlayerInventory::new()),
             open_inventory: Mutex::new(None),
             held_slot: AtomicU8::default(),
+            digging: Mutex::new(None),
             server,
         }
     }
@@ -448,31 +547,42 @@ impl Inner {
     }
 
     pub(crate) fn load_chunks(&self) {
-        let chunk = Chunk::to_chunk_pos(self.position());
-        let view_distance = 32;
+        let chunk = ChunkPos::from(self.position());
+        let view_distance = self.server.config().view_distance;
 
         let world = self.world();
         let chunks = Chunk::chunks_in_range(chunk, view_distance);
 
-        for (cx, cz) in chunks {
-            let chunk = match world.get_chunk(cx, cz) {
-                Some(chunk) => chunk,
-                None => world.load_chunk(cx, cz),
-            };
-
+        let player = self.player();
+        for pos in chunks {
+            let chunk = world.view_chunk(pos, player.clone());
             self.send_chunk(chunk);
         }
 
         self.send_pending_chunks();
     }
 
-    fn keep_alive(&self) {
-        self.send_packet(&KeepAlivePacket { keep_alive_id: 0 });
+    pub(crate) fn handle_keep_alive(&self, keep_alive_id: i64) {
+        let Some(ping) = self.connection.handle_keep_alive(keep_alive_id) else {
+            return;
+        };
+
+        let packet = PlayerInfoUpdatePacket {
+            actions: PlayerInfoFlags::UPDATE_LATENCY.bits(),
+            players: vec![PlayerEntry {
+                uuid: self.uuid(),
+                player_actions: vec![PlayerAction::UpdateLatency { ping }],
+            }],
+        };
+        self.server.broadcast_packet(&packet);
     }
 
     pub(crate) fn add_to_list_packet(&self) -> PlayerInfoUpdatePacket {
         PlayerInfoUpdatePacket {
-            actions: (PlayerInfoFlags::ADD_PLAYER | PlayerInfoFlags::UPDATE_LISTED).bits(),
+            actions: (PlayerInfoFlags::ADD_PLAYER
+                | PlayerInfoFlags::UPDATE_LISTED
+                | PlayerInfoFlags::UPDATE_LATENCY)
+                .bits(),
             players: vec![PlayerEntry {
                 uuid: self.uuid(),
                 player_actions: vec![
@@ -481,6 +591,9 @@ impl Inner {
                         properties: self.game_profile.properties.clone(),
                     },
                     PlayerAction::UpdateListed { listed: true },
+                    PlayerAction::UpdateLatency {
+                        ping: self.connection.latency(),
+                    },
                 ],
             }],
         }
@@ -548,6 +661,31 @@ impl Inner {
         self.inventory.get_item_stack(slot_id as i32)
     }
 
+    /// Returns the packet showing the held item and armor of the inventory, or `None` if the player has none.
+    fn equipment_packet(&self) -> Option<SetEquipmentPacket> {
+        let equipment = [
+            EquipmentSlot::MainHand,
+            EquipmentSlot::OffHand,
+            EquipmentSlot::Boots,
+            EquipmentSlot::Leggings,
+            EquipmentSlot::Chestplate,
+            EquipmentSlot::Helmet,
+        ]
+        .into_iter()
+        .filter_map(|slot| {
+            let item = self.get_equipment(slot)?;
+            (item.material() != Material::Air && item.amount() > 0).then(|| (slot, item.into()))
+        })
+        .collect::<Vec<_>>();
+        if equipment.is_empty() {
+            return None;
+        }
+        Some(SetEquipmentPacket {
+            entity_id: self.id(),
+            equipment,
+        })
+    }
+
     fn set_held_slot(&self, slot: u8) {
         self.update_held_slot(slot);
         self.send_packet(&SetHeldItemPacket { slot: slot.into() });
@@ -557,36 +695,172 @@ impl Inner {
         self.held_slot.store(slot, Ordering::Release);
     }
 
+    // ===== Digging ======
+
+    fn eye_height(&self) -> f64 {
+        self.entity.dimensions().eye_height as f64
+    }
+
+    /// Returns whether the block is within the interaction range of the player, with the same tolerance vanilla
+    /// gives for latency.
+    pub(crate) fn can_reach_block(&self, position: BlockPosition) -> bool {
+        let range = match self.game_mode() {
+            GameMode::Creative => CREATIVE_BLOCK_INTERACTION_RANGE,
+            _ => BLOCK_INTERACTION_RANGE,
+        } + 1.;
+
+        // The distance to the closest point of the block
+        let feet = self.position();
+        let eyes = [feet.x(), feet.y() + self.eye_height(), feet.z()];
+        let block = [position.x(), position.y(), position.z()].map(|v| v as f64);
+        let distance = eyes
+            .iter()
+            .zip(block)
+            .map(|(eye, min)| (min - eye).max(eye - (min + 1.)).max(0.))
+            .map(|d| d * d)
+            .sum::<f64>();
+        distance < range * range
+    }
+
+    fn dig_conditions(&self, world: &World) -> DigConditions {
+        let position = self.position();
+        let eyes = BlockPosition::new(
+            position.x().floor() as i64,
+            (position.y() + self.eye_height()).floor() as i64,
+            position.z().floor() as i64,
+        );
+        let eyes_in_water = world
+            .get_block_at(eyes)
+            .is_some_and(|block| block.has_fluid() && block.name() != "minecraft:lava");
+        let aqua_affinity = self
+            .get_equipment(EquipmentSlot::Helmet)
+            .zip(Enchantments::id("minecraft:aqua_affinity"))
+            .and_then(|(helmet, id)| {
+                helmet
+                    .get(DataComponent::ENCHANTMENTS)
+                    .map(|enchantments| enchantments.level(id))
+            })
+            .is_some_and(|level| level > 0);
+
+        DigConditions {
+            on_ground: self.is_on_ground(),
+            underwater: eyes_in_water && !aqua_affinity,
+        }
+    }
+
+    /// Starts digging a block and returns whether it breaks instantly.
+    pub(crate) fn start_digging(&self, position: BlockPosition) -> bool {
+        self.cancel_digging();
+
+        let world = self.world();
+        let Some(block) = world.get_block_at(position) else {
+            return false;
+        };
+        let item = self.get_item_in_hand(Hand::MainHand);
+        let progress = break_progress(block, item.as_ref(), self.dig_conditions(&world));
+        if progress >= 1. {
+            return true;
+        }
+
+        *self.digging.lock() = Some(Digging::new(position, world.age(), progress));
+        false
+    }
+
+    /// Stops digging and removes the destroy stage the viewers see.
+    pub(crate) fn cancel_digging(&self) {
+        let Some(digging) = self.digging.lock().take() else {
+            return;
+        };
+        if digging.stage >= 0 {
+            self.send_destroy_stage(digging.position, -1);
+        }
+    }
+
+    /// Stops digging and returns whether the player dug the block long enough to break it.
+    pub(crate) fn finish_digging(&self, position: BlockPosition) -> bool {
+        let digging = *self.digging.lock();
+        self.cancel_digging();
+
+        digging.is_some_and(|digging| {
+            digging.position == position && digging.is_done_at(self.world().age())
+        })
+    }
+
+    fn tick_digging(&self) {
+        let Some(world) = self.world.lock().clone() else {
+            return;
+        };
+
+        let update = {
+            let mut digging = self.digging.lock();
+            digging.as_mut().and_then(|digging| {
+                let stage = digging.stage_at(world.age());
+                (stage != digging.stage).then(|| {
+                    digging.stage = stage;
+                    (digging.position, stage)
+                })
+            })
+        };
+        if let Some((position, stage)) = update {
+            self.send_destroy_stage(position, stage);
+        }
+    }
+
+    /// Shows the destroy stage of a block to the viewers, where a negative stage removes it.
+    fn send_destroy_stage(&self, position: BlockPosition, stage: i8) {
+        self.send_packet_to_viewers(&SetBlockDestroyStagePacket {
+            entitiy_id: self.id(),
+            location: position,
+            destroy_stage: stage as u8,
+        });
+    }
+
     // ===== World ======
 
-    fn update_chunks(&self, new_chunk: (i32, i32), old_chunk: (i32, i32)) {
-        let view_distance = 8;
+    fn update_chunks(&self, new_chunk: ChunkPos, old_chunk: ChunkPos) {
+        let view_distance = self.server.config().view_distance;
 
-        Chunk::difference(new_chunk, old_chunk, view_distance, |cx, cz| {
-            self.load_chunk(cx, cz);
+        Chunk::difference(new_chunk, old_chunk, view_distance, |pos| {
+            self.load_chunk(pos);
         });
 
-        Chunk::difference(old_chunk, new_chunk, view_distance, |cx, cz| {
-            self.unload_chunk(cx, cz);
+        Chunk::difference(old_chunk, new_chunk, view_distance, |pos| {
+            self.unload_chunk(pos);
         });
     }
 
-    fn load_chunk(&self, cx: i32, cz: i32) {
-        let world = self.world();
+    fn load_chunk(&self, pos: ChunkPos) {
+        let chunk = self.world().view_chunk(pos, self.player());
+        self.send_chunk(chunk);
+    }
+
+    fn unload_chunk(&self, pos: ChunkPos) {
+        if let Some(chunk) = self.world().get_chunk(pos)
+            && let Some(player) = self.player()
+        {
+            chunk.remove_viewer(player);
+        }
+        self.send_packet(&UnloadChunkPacket { chunk: pos });
+    }
 
-        let chunk = match world.get_chunk(cx, cz) {
-            Some(chunk) => chunk,
-            None => world.load_chunk(cx, cz),
+    /// Stops viewing all chunks in view distance without unloading them on the client.
+    fn release_chunks(&self) {
+        let (Some(world), Some(player)) = (self.world.lock().clone(), self.player()) else {
+            return;
         };
 
-        self.send_chunk(chunk);
+        let view_distance = self.server.config().view_distance;
+        for pos in Chunk::chunks_in_range(ChunkPos::from(self.position()), view_distance) {
+            if let Some(chunk) = world.get_chunk(pos) {
+                chunk.remove_viewer(player.clone());
+            }
+        }
+        self.chunk_queue.lock().queue.clear();
     }
 
-    fn unload_chunk(&self, cx: i32, cz: i32) {
-        self.send_packet(&UnloadChunkPacket {
-            chunk_x: cx,
-            chunk_z: cz,
-        });
+    /// Returns the handle of this player, which is available once the player joined.
+    fn player(&self) -> Option<Player> {
+        self.connection.player.lock().clone()
     }
 
     fn send_chunk(&self, chunk: SyncChunk) {
@@ -612,8 +886,10 @@ impl Inner {
         while queue.pending_chunks >= 1.
             && let Some(chunk) = queue.dequeue()
         {
-            let packet: ChunkDataAndUpdateLightPacket = (&chunk).into();
-            self.send_packet(&packet);
+            match chunk.packet() {
+                Ok(packet) => self.connection.send_framed_packet(packet),
+                Err(e) => log::error!("Failed to encode chunk {:?}: {}", chunk.pos(), e),
+            }
 
             queue.pending_chunks -= 1.;
             // batch_size += 1;
@@ -625,10 +901,82 @@ impl Inner {
         // queue.lead += 1;
     }
 
-    pub(crate) fn set_world(&self, world: World) {
+    fn set_world(&self, world: World, position: Position) {
+        let Some(player) = self.player() else {
+            self.update_world(world);
+            self.set_position(position);
+            return;
+        };
+
+        // Leave the old world
+        self.release_chunks();
+        if let Some(old_world) = self.world.lock().clone() {
+            old_world.remove_player(&player);
+            old_world.hide_entities(&player);
+        }
+        for viewer in self.viewers() {
+            self.entity.remove_viewer(viewer.clone());
+            viewer.0.entity.remove_viewer(player.clone());
+        }
+
+        self.update_world(world.clone());
+        self.set_position(position);
+
+        let config = self.server.config();
+        self.send_packet(&RespawnPacket {
+            dimension_type: REGISTRIES
+                .dimension_type
+                .get_id(world.dimension())
+                .unwrap_or(0) as i32,
+            dimension_name: world.name().clone(),
+            hashed_seed: config.hashed_seed,
+            game_mode: self.game_mode() as u8,
+            previous_game_mode: -1,
+            is_debug: false,
+            is_flat: false,
+            death_location: None,
+            portal_cooldown: 0,
+            sea_level: 64,
+            data_kept: RespawnPacket::KEEP_ATTRIBUTES | RespawnPacket::KEEP_METADATA,
+        });
+        self.refresh_abilities();
+        self.synchronize_position(position, Position::ZERO, TeleportFlags::empty());
+        world.add_player(player.clone());
+        world.send_environment(&player);
+
+        self.send_packet(&GameEventPacket::START_WAITING_FOR_CHUNKS);
+        self.send_packet(&SetCenterChunkPacket {
+            chunk: ChunkPos::from(position),
+        });
+
+        // Players keep their tab list entries, only the entities are exchanged
+        for other in self.server.players.lock().clone() {
+            if other != player && other.world() == world {
+                self.entity.add_viewer(other.clone());
+                other.0.entity.add_viewer(player.clone());
+            }
+        }
+
+        self.load_chunks();
+        world.show_entities(&player);
+    }
+
+    pub(crate) fn update_world(&self, world: World) {
         (*self.world.lock()) = Some(world)
     }
 
+    fn time_override(&self) -> Option<i64> {
+        *self.time_override.lock()
+    }
+
+    fn set_time_override(&self, time: Option<i64>) {
+        *self.time_override.lock() = time;
+
+        if let (Some(world), Some(player)) = (self.world.lock().clone(), self.player()) {
+            world.send_time(&player);
+        }
+    }
+
     // ===== Position & Movement ======
 
     fn update_position(&self, new_position: Position) {
@@ -637,14 +985,11 @@ impl Inner {
         self.set_position(new_position);
         self.set_head_roation(new_position.yaw());
 
-        let old_chunk = Chunk::to_chunk_pos(old_position);
-        let new_chunk = Chunk::to_chunk_pos(new_position);
+        let old_chunk = ChunkPos::from(old_position);
+        let new_chunk = ChunkPos::from(new_position);
 
         if old_chunk != new_chunk {
-            self.send_packet(&SetCenterChunkPacket {
-                chunk_x: new_chunk.0,
-                chunk_z: new_chunk.1,
-            });
+            self.send_packet(&SetCenterChunkPacket { chunk: new_chunk });
             self.update_chunks(new_chunk, old_chunk);
         }
 
@@ -852,6 +1197,10 @@ impl Inner {
         for viewer in self.viewers() {
             self.remove_viewer(viewer);
         }
+        self.release_chunks();
+        if let (Some(world), Some(player)) = (self.world.lock().clone(), self.player()) {
+            world.remove_player(&player);
+        }
     }
 
     // ===== Scoreboard =====
@@ -864,16 +1213,13 @@ impl Inner {
 impl Tickable for Inner {
     fn tick(&self) {
         // Keep Alive
-        {
-            let mut last_keep_alive = self.last_keep_alive.lock();
-            if last_keep_alive.elapsed() > Duration::from_secs(20) {
-                self.keep_alive();
-                *last_keep_alive = Instant::now();
-            }
-        }
+        self.connection.tick_keep_alive();
 
         // Chunks
         self.send_pending_chunks();
+
+        // Digging
+        self.tick_digging();
     }
 }
 
@@ -881,7 +1227,15 @@ impl Viewable for Inner {
     fn add_viewer(&self, player: Player) {
         player.send_packet(&self.add_to_list_packet());
 
-        self.entity.add_viewer(player);
+        self.entity.viewers().add_viewer(player.clone());
+        // Bundled, so the client never renders the player without its metadata and equipment
+        player.send_bundle(|bundle| {
+            bundle.add(&self.entity.0.spawn_packet());
+            bundle.add(&self.entity.0.metadata_packet());
+            if let Some(packet) = self.equipment_packet() {
+                bundle.add(&packet);
+            }
+        });
     }
 
     fn remove_viewer(&self, player: Player) {
@@ -938,11 +1292,75 @@ impl EquipmentSlot {
     pub fn slot_id(&self) -> i32 {
         match self {
             Self::MainHand => 0,
-            Self::OffHand => 0,
-            Self::Boots => 0,
-            Self::Leggings => 0,
-            Self::Chestplate => 0,
-            Self::Helmet => 0,
+            Self::OffHand => 1,
+            Self::Boots => 2,
+            Self::Leggings => 3,
+            Self::Chestplate => 4,
+            Self::Helmet => 5,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::item::{ItemStack, Material};
+    use crate::protocol::{
+        ProtocolState, SendablePacket,
+        decode::PacketRead as _,
+        encode::packet_id,
+        packet::{
+            BundleDelimiterPacket, PlayerInfoUpdatePacket, SetEntityMetadataPacket,
+            SetEquipmentPacket, SpawnEntityPacket,
+        },
+    };
+    use crate::test_util::{connect_player, run_with_server};
+
+    #[test]
+    fn test_player_spawn_bundle() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let mut viewer = connect_player(&server).await;
+            let player = client.player.clone();
+            player
+                .inventory()
+                .set_item_stack(36, ItemStack::of(Material::Stone));
+            player
+                .inventory()
+                .set_item_stack(41, ItemStack::of(Material::IronHelmet));
+
+            player.add_viewer(viewer.player.clone());
+
+            let mut packets = Vec::new();
+            while let Ok(packet) = viewer.rx.try_recv() {
+                let data = match packet {
+                    SendablePacket::Encoded(data) => data.freeze(),
+                    SendablePacket::Framed(packet) => packet.data().clone(),
+                };
+                packets.push(data);
+            }
+            let id = |data: &bytes::Bytes| Cursor::new(&data[..]).read_varint().unwrap();
+            let ids = packets.iter().map(id).collect::<Vec<_>>();
+            let delimiter = packet_id::<BundleDelimiterPacket>(&ProtocolState::Play).unwrap();
+            assert_eq!(
+                ids,
+                [
+                    packet_id::<PlayerInfoUpdatePacket>(&ProtocolState::Play).unwrap(),
+                    delimiter,
+                    packet_id::<SpawnEntityPacket>(&ProtocolState::Play).unwrap(),
+                    packet_id::<SetEntityMetadataPacket>(&ProtocolState::Play).unwrap(),
+                    packet_id::<SetEquipmentPacket>(&ProtocolState::Play).unwrap(),
+                    delimiter,
+                ]
+            );
+
+            // The held item and the helmet, with the top bit set on all but the last entry
+            let mut equipment = Cursor::new(&packets[4][..]);
+            equipment.read_varint().unwrap();
+            assert_eq!(equipment.read_varint().unwrap(), player.id());
+            assert_eq!(equipment.read_u8().unwrap(), 0x80);
+        });
+    }
+}
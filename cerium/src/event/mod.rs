@@ -1,3 +1,5 @@
+use crate::text::TextComponent;
+
 mod events;
 pub use events::Events;
 
@@ -26,6 +28,25 @@ impl ServerListPingEvent {
 
 impl Event for ServerListPingEvent {}
 
+/// Fired when the server shuts down, after all players were kicked.
+///
+/// This is the last chance to save worlds before [`Server::bind`](crate::Server::bind) returns.
+pub struct ServerShutdownEvent {
+    reason: TextComponent,
+}
+
+impl ServerShutdownEvent {
+    pub fn new(reason: TextComponent) -> Self {
+        Self { reason }
+    }
+
+    pub fn get_reason(&self) -> &TextComponent {
+        &self.reason
+    }
+}
+
+impl Event for ServerShutdownEvent {}
+
 pub trait Cancellable: Event {
     fn set_cancelled(&mut self, value: bool);
 
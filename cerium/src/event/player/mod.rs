@@ -1,8 +1,10 @@
 use crate::{entity::Player, event::Event};
 
+mod player_border_damage;
 mod player_config;
 mod player_spawn;
 
+pub use player_border_damage::PlayerBorderDamageEvent;
 pub use player_config::PlayerConfigEvent;
 pub use player_spawn::PlayerSpawnEvent;
 
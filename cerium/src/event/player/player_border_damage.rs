@@ -0,0 +1,27 @@
+use crate::{
+    entity::Player,
+    event::{Event, player::PlayerEvent},
+};
+
+/// Fired for every player that is outside of the safe zone of a damaging
+/// [`WorldBorder`](crate::world::WorldBorder).
+///
+/// The server does not track the health of players, so it is up to the listener to apply the damage.
+pub struct PlayerBorderDamageEvent {
+    pub(crate) player: Player,
+    pub(crate) damage: f32,
+}
+
+impl PlayerBorderDamageEvent {
+    pub fn get_damage(&self) -> f32 {
+        self.damage
+    }
+}
+
+impl Event for PlayerBorderDamageEvent {}
+
+impl PlayerEvent for PlayerBorderDamageEvent {
+    fn get_player(&self) -> &Player {
+        &self.player
+    }
+}
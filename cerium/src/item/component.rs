@@ -19,6 +19,7 @@ use cerium_nbt::Nbt;
 mod armor_trim;
 mod consumable;
 mod custom_model_data;
+mod enchantments;
 mod equippable;
 mod food;
 mod tool;
@@ -28,10 +29,11 @@ mod weapon;
 pub use armor_trim::ArmorTrim;
 pub use consumable::{Consumable, ConsumeEffect};
 pub use custom_model_data::CustomModelData;
+pub use enchantments::Enchantments;
 pub use equippable::Equippable;
 pub use food::Food;
 use rustc_hash::{FxBuildHasher, FxHashMap};
-pub use tool::Tool;
+pub use tool::{BlockSet, Tool, ToolRule};
 pub use tooltip_display::TooltipDisplay;
 pub use weapon::Weapon;
 
@@ -142,7 +144,7 @@ macro_rules! define_components {
             pub fn decode_value<R: PacketRead>(self, r: &mut R) -> Result<Arc<dyn Any + Send + Sync>, DecodeError> {
                 match self.id() {
                     $($id => decode!(r, $ty $(, $ser)?),)*
-                    _ => panic!("error")
+                    _ => Err(DecodeError::Decode("Unknown data component"))
                 }
             }
 
@@ -151,12 +153,12 @@ macro_rules! define_components {
                     $($id => {
                         let value = match this.downcast_ref::<$ty>() {
                             Some(v) => v,
-                            None => panic!("error"),
+                            None => return Err(EncodeError::Encode(format!("Invalid value for data component {}", $key))),
                         };
 
                         encode!(w, value, $ty $(, $ser)?)
                     },)*
-                    _ => panic!("error")
+                    _ => return Err(EncodeError::Encode(format!("Unknown data component {}", self.id())))
                 }
                 Ok(())
             }
@@ -196,7 +198,7 @@ define_components! {
     const ITEM_MODEL: DataComponent<TextComponent>                      = DataComponent::new(7, "minecraft:item_model");
     const LORE: DataComponent<Vec<TextComponent>>                       = DataComponent::new(8, "minecraft:lore");
     const RARITY: DataComponent<i32, VarInt>                            = DataComponent::new(9, "minecraft:rarity");
-    // const ENCHANTMENTS: DataComponent<Vec<Enchantment>>                = DataComponent::new(10, "minecraft:enchantments");
+    const ENCHANTMENTS: DataComponent<Enchantments>                     = DataComponent::new(10, "minecraft:enchantments");
     // const CAN_PLACE_ON: DataComponent<Vec<BlockPredicate>>             = DataComponent::new(11, "minecraft:can_place_on");
     // const CAN_BREAK: DataComponent<Vec<BlockPredicate>>                = DataComponent::new(12, "minecraft:can_break");
     // const ATTRIBUTE_MODIFIERS: DataComponent<Vec<AttributeModifier>>   = DataComponent::new(13, "minecraft:attribute_modifiers");
@@ -426,91 +428,91 @@ impl DataType for bool {
 
 impl DataType for ItemStack {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for WolfVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for WolfSoundVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for PigVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for CowVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for ChickenVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for FrogVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for PaintingVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
 impl DataType for CatVariant {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
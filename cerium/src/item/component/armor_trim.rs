@@ -9,10 +9,10 @@ pub struct ArmorTrim {}
 
 impl DataType for ArmorTrim {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
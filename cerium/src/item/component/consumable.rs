@@ -17,11 +17,11 @@ pub struct Consumable {
 
 impl DataType for Consumable {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
 
@@ -30,10 +30,10 @@ pub enum ConsumeEffect {}
 
 impl DataType for ConsumeEffect {
     fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+        Err(DecodeError::Decode("Unsupported data component"))
     }
 
     fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+        Err(EncodeError::Encode("Unsupported data component".to_string()))
     }
 }
@@ -0,0 +1,72 @@
+use crate::protocol::{
+    DataType,
+    decode::{DecodeError, PacketRead},
+    encode::{EncodeError, PacketWrite},
+};
+use crate::registry::{REGISTRIES, RegistryKey};
+
+/// The enchantments of an item, by their id in the enchantment registry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enchantments {
+    levels: Vec<(i32, i32)>,
+}
+
+impl Enchantments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, enchantment: i32, level: i32) -> Self {
+        self.levels.retain(|(id, _)| *id != enchantment);
+        self.levels.push((enchantment, level));
+        self
+    }
+
+    /// Returns the id of the enchantment with the key, e.g. `minecraft:efficiency`, in the enchantment registry.
+    pub fn id(key: &str) -> Option<i32> {
+        REGISTRIES
+            .enchantment
+            .get_id(&RegistryKey::of(key))
+            .map(|id| id as i32)
+    }
+
+    /// Returns the level of the enchantment, or `0` if the item does not have it.
+    pub fn level(&self, enchantment: i32) -> i32 {
+        self.levels
+            .iter()
+            .find(|(id, _)| *id == enchantment)
+            .map_or(0, |(_, level)| *level)
+    }
+}
+
+impl DataType for Enchantments {
+    fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
+        Ok(Self {
+            levels: r.read_array(|r| Ok((r.read_varint()?, r.read_varint()?)))?,
+        })
+    }
+
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_array(&this.levels, |w, (id, level)| {
+            w.write_varint(*id)?;
+            w.write_varint(*level)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enchantment_ids() {
+        assert_eq!(Enchantments::id("minecraft:aqua_affinity"), Some(0));
+        assert_eq!(Enchantments::id("minecraft:efficiency"), Some(8));
+        assert_eq!(Enchantments::id("minecraft:unknown"), None);
+
+        let efficiency = Enchantments::id("minecraft:efficiency").unwrap();
+        let enchantments = Enchantments::new().with(efficiency, 3).with(efficiency, 5);
+        assert_eq!(enchantments.level(efficiency), 5);
+        assert_eq!(enchantments.level(0), 0);
+    }
+}
@@ -1,17 +1,294 @@
-use crate::protocol::{
-    DataType,
-    decode::{DecodeError, PacketRead},
-    encode::{EncodeError, PacketWrite},
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
 };
-#[derive(Debug)]
-pub struct Tool;
+
+use crate::{
+    item::Material,
+    protocol::{
+        DataType,
+        decode::{DecodeError, PacketRead},
+        encode::{EncodeError, PacketWrite},
+    },
+    util::Identifier,
+    world::BlockState,
+};
+
+/// Makes an item mine some blocks faster, and decides which blocks drop items when mined with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tool {
+    rules: Vec<ToolRule>,
+    default_mining_speed: f32,
+    damage_per_block: i32,
+    can_destroy_blocks_in_creative: bool,
+}
+
+impl Tool {
+    pub fn new(default_mining_speed: f32) -> Self {
+        Self {
+            rules: Vec::new(),
+            default_mining_speed,
+            damage_per_block: 1,
+            can_destroy_blocks_in_creative: true,
+        }
+    }
+
+    /// Returns the tool an item of the material has by default, like the speed of a pickaxe.
+    pub fn of(material: Material) -> Option<&'static Tool> {
+        DEFAULT_TOOLS.get(material.key())
+    }
+
+    pub fn with_rule(mut self, rule: ToolRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn with_damage_per_block(mut self, damage: i32) -> Self {
+        self.damage_per_block = damage;
+        self
+    }
+
+    pub fn with_can_destroy_blocks_in_creative(mut self, value: bool) -> Self {
+        self.can_destroy_blocks_in_creative = value;
+        self
+    }
+
+    pub fn rules(&self) -> &[ToolRule] {
+        &self.rules
+    }
+
+    pub fn default_mining_speed(&self) -> f32 {
+        self.default_mining_speed
+    }
+
+    pub fn damage_per_block(&self) -> i32 {
+        self.damage_per_block
+    }
+
+    pub fn can_destroy_blocks_in_creative(&self) -> bool {
+        self.can_destroy_blocks_in_creative
+    }
+
+    /// Returns the speed the tool mines the block with, from the first rule containing the block that sets one.
+    pub fn mining_speed(&self, block: &BlockState) -> f32 {
+        self.rules
+            .iter()
+            .filter(|rule| rule.blocks.contains(block))
+            .find_map(|rule| rule.speed)
+            .unwrap_or(self.default_mining_speed)
+    }
+
+    /// Returns whether the block drops items when mined with the tool.
+    pub fn is_correct_for_drops(&self, block: &BlockState) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| rule.blocks.contains(block))
+            .find_map(|rule| rule.correct_for_drops)
+            .unwrap_or(false)
+    }
+}
 
 impl DataType for Tool {
-    fn decode<R: PacketRead>(_r: &mut R) -> Result<Self, DecodeError> {
-        todo!()
+    fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
+        Ok(Self {
+            rules: r.read_array(ToolRule::decode)?,
+            default_mining_speed: r.read_f32()?,
+            damage_per_block: r.read_varint()?,
+            can_destroy_blocks_in_creative: r.read_bool()?,
+        })
     }
 
-    fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        todo!()
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_array(&this.rules, ToolRule::encode)?;
+        w.write_f32(this.default_mining_speed)?;
+        w.write_varint(this.damage_per_block)?;
+        w.write_bool(this.can_destroy_blocks_in_creative)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRule {
+    pub blocks: BlockSet,
+    /// Overrides the default mining speed of the tool.
+    pub speed: Option<f32>,
+    /// Overrides whether the blocks drop items.
+    pub correct_for_drops: Option<bool>,
+}
+
+impl DataType for ToolRule {
+    fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
+        Ok(Self {
+            blocks: BlockSet::decode(r)?,
+            speed: r.read_option(|r| r.read_f32())?,
+            correct_for_drops: r.read_option(|r| r.read_bool())?,
+        })
+    }
+
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        BlockSet::encode(w, &this.blocks)?;
+        w.write_option(&this.speed, |w, v| w.write_f32(*v))?;
+        w.write_option(&this.correct_for_drops, |w, v| w.write_bool(*v))?;
+        Ok(())
+    }
+}
+
+/// A set of blocks, either named by a tag or listed by their ids.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockSet {
+    Tag(Identifier),
+    Blocks(Vec<i32>),
+}
+
+impl BlockSet {
+    /// Returns whether the set contains the block.
+    ///
+    /// Only the block tags used by the default tools are known, other tags contain no blocks.
+    pub fn contains(&self, block: &BlockState) -> bool {
+        match self {
+            BlockSet::Tag(tag) => BLOCK_TAGS
+                .get(&tag.to_string())
+                .is_some_and(|blocks| blocks.contains(&block.id())),
+            BlockSet::Blocks(blocks) => blocks.contains(&block.id()),
+        }
+    }
+}
+
+impl DataType for BlockSet {
+    fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
+        match r.read_varint()? {
+            0 => Ok(BlockSet::Tag(r.read_identifier()?)),
+            len => {
+                let blocks = (1..len)
+                    .map(|_| r.read_varint())
+                    .collect::<Result<_, _>>()?;
+                Ok(BlockSet::Blocks(blocks))
+            }
+        }
+    }
+
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        match this {
+            BlockSet::Tag(tag) => {
+                w.write_varint(0)?;
+                w.write_identifier(tag)?;
+            }
+            BlockSet::Blocks(blocks) => {
+                w.write_varint(blocks.len() as i32 + 1)?;
+                for block in blocks {
+                    w.write_varint(*block)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The ids of the blocks in the block tags used by tools, like `minecraft:mineable/pickaxe`.
+static BLOCK_TAGS: LazyLock<HashMap<String, HashSet<i32>>> = LazyLock::new(|| {
+    let tags: HashMap<String, Vec<String>> =
+        serde_json::from_str(include_str!("../../../data/block_tags.json")).unwrap();
+
+    tags.into_iter()
+        .map(|(tag, keys)| {
+            let blocks = keys
+                .into_iter()
+                .filter_map(|key| BlockState::from_key(key).map(|block| block.id()))
+                .collect();
+            (tag, blocks)
+        })
+        .collect()
+});
+
+static DEFAULT_TOOLS: LazyLock<HashMap<String, Tool>> = LazyLock::new(|| {
+    let entries: HashMap<String, serde_json::Value> =
+        serde_json::from_str(include_str!("../../../data/item.json")).unwrap();
+
+    entries
+        .into_iter()
+        .filter_map(|(key, item)| {
+            let tool = item["components"].get("minecraft:tool")?;
+            Some((key, tool_from_json(tool)))
+        })
+        .collect()
+});
+
+fn tool_from_json(json: &serde_json::Value) -> Tool {
+    let block_id = |key: &str| BlockState::from_key(key.to_string()).map(|block| block.id());
+
+    let rules = json["rules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|rule| {
+            let blocks = match &rule["blocks"] {
+                serde_json::Value::String(tag) if tag.starts_with('#') => {
+                    BlockSet::Tag(Identifier::of(&tag[1..]))
+                }
+                serde_json::Value::String(key) => {
+                    BlockSet::Blocks(block_id(key).into_iter().collect())
+                }
+                keys => BlockSet::Blocks(
+                    keys.as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|key| block_id(key.as_str()?))
+                        .collect(),
+                ),
+            };
+            ToolRule {
+                blocks,
+                speed: rule["speed"].as_f64().map(|speed| speed as f32),
+                correct_for_drops: rule["correct_for_drops"].as_bool(),
+            }
+        })
+        .collect();
+
+    Tool {
+        rules,
+        default_mining_speed: json["default_mining_speed"].as_f64().unwrap_or(1.) as f32,
+        damage_per_block: json["damage_per_block"].as_i64().unwrap_or(1) as i32,
+        can_destroy_blocks_in_creative: json["can_destroy_blocks_in_creative"]
+            .as_bool()
+            .unwrap_or(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Block;
+
+    #[test]
+    fn test_default_tools() {
+        let shears = Tool::of(Material::Shears).unwrap();
+        assert_eq!(shears.mining_speed(&Block::Cobweb), 15.);
+        assert!(shears.is_correct_for_drops(&Block::Cobweb));
+
+        let pickaxe = Tool::of(Material::DiamondPickaxe).unwrap();
+        assert_eq!(pickaxe.mining_speed(&Block::Stone), 8.);
+        assert!(pickaxe.is_correct_for_drops(&Block::Stone));
+        assert!(pickaxe.is_correct_for_drops(&Block::Obsidian));
+        assert_eq!(pickaxe.mining_speed(&Block::OakPlanks), 1.);
+        assert!(!pickaxe.is_correct_for_drops(&Block::OakPlanks));
+
+        // The tier of the pickaxe decides which ores drop
+        let pickaxe = Tool::of(Material::WoodenPickaxe).unwrap();
+        assert!(pickaxe.is_correct_for_drops(&Block::CoalOre));
+        assert!(!pickaxe.is_correct_for_drops(&Block::IronOre));
+        let pickaxe = Tool::of(Material::IronPickaxe).unwrap();
+        assert!(pickaxe.is_correct_for_drops(&Block::DiamondOre));
+        assert!(!pickaxe.is_correct_for_drops(&Block::Obsidian));
+
+        // Tags without data contain no blocks
+        let tool = Tool::new(1.).with_rule(ToolRule {
+            blocks: BlockSet::Tag(Identifier::of("minecraft:unknown")),
+            speed: Some(4.),
+            correct_for_drops: Some(true),
+        });
+        assert_eq!(tool.mining_speed(&Block::Stone), 1.);
+        assert!(!tool.is_correct_for_drops(&Block::Stone));
+
+        assert!(Tool::of(Material::Stick).is_none());
     }
 }
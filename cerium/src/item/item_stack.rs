@@ -4,7 +4,7 @@ use rustc_hash::{FxBuildHasher, FxHashMap};
 
 use crate::{
     inventory::Slot,
-    item::{ComponentMap, DataComponent, Material},
+    item::{ComponentMap, DataComponent, Material, Tool},
 };
 
 #[derive(Debug, Clone)]
@@ -80,6 +80,12 @@ impl ItemStack {
         self.get(component).is_some()
     }
 
+    /// Returns the tool component of the item, or the tool its material has by default.
+    pub fn tool(&self) -> Option<&Tool> {
+        self.get(DataComponent::TOOL)
+            .or_else(|| Tool::of(self.material))
+    }
+
     pub fn material(&self) -> Material {
         self.material
     }
@@ -97,14 +103,13 @@ impl From<Material> for ItemStack {
 
 impl From<Slot> for ItemStack {
     fn from(value: Slot) -> Self {
-        if let Some(item_id) = value.item_id {
-            Self {
-                material: Material::from_id(item_id).unwrap(),
+        match value.item_id.and_then(Material::from_id) {
+            Some(material) => Self {
+                material,
                 amount: value.item_count,
                 components: value.to_add,
-            }
-        } else {
-            ItemStack::EMPTY
+            },
+            None => ItemStack::EMPTY,
         }
     }
 }
@@ -1 +1,17 @@
+use std::{collections::HashMap, sync::LazyLock};
+
 include!("../registry/generated/materials.rs");
+
+static BY_KEY: LazyLock<HashMap<&'static str, Material>> = LazyLock::new(|| {
+    (0..)
+        .map_while(Material::from_id)
+        .map(|material| (material.key(), material))
+        .collect()
+});
+
+impl Material {
+    /// Returns the material with the key, e.g. `minecraft:stone`.
+    pub fn from_key(key: &str) -> Option<Material> {
+        BY_KEY.get(key).copied()
+    }
+}
@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod config;
 pub mod entity;
 pub mod event;
 pub mod inventory;
@@ -14,5 +15,9 @@ pub mod advancement;
 
 mod server;
 pub use server::Server;
+pub use config::ServerConfig;
 
 mod network;
+
+#[cfg(test)]
+mod test_util;
@@ -1,15 +1,19 @@
 use bytes::BytesMut;
+use futures::FutureExt as _;
 use parking_lot::{Mutex, RwLock};
 use std::{
     io::Cursor,
     net::SocketAddr,
+    panic::AssertUnwindSafe,
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicI32, Ordering},
     },
+    time::{Duration, Instant},
 };
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio::{
+    io::AsyncWriteExt as _,
     net::{
         TcpStream,
         tcp::{OwnedReadHalf, OwnedWriteHalf},
@@ -21,11 +25,11 @@ use crate::{
     Server,
     auth::KeyStore,
     entity::Player,
-    network::{reader::StreamReader, writer::StreamWriter},
+    network::{listener::ForwardedData, reader::StreamReader, writer::StreamWriter},
     protocol::{
-        ProtocolState,
+        ProtocolState, SendablePacket,
         encode::{EncodeError, packet_id},
-        packet::{DisconnectPacket, Packet},
+        packet::{DisconnectPacket, LoginDisconnectPacket, Packet, server::KeepAlivePacket},
     },
     text::TextComponent,
 };
@@ -38,36 +42,59 @@ pub struct Connection {
     addr: SocketAddr,
     sreader: tokio::sync::Mutex<StreamReader<OwnedReadHalf>>,
     swriter: tokio::sync::Mutex<StreamWriter<OwnedWriteHalf>>,
-    packet_tx: Sender<BytesMut>,
+    packet_tx: Sender<SendablePacket>,
     state: RwLock<ProtocolState>,
+    /// The profile of the player, set once the login success was sent.
     pub(crate) game_profile: Mutex<Option<GameProfile>>,
+    /// The name of a player in online mode, until it is verified in the encryption response.
+    pub(crate) pending_login: Mutex<Option<String>>,
     pub(crate) key_store: Arc<KeyStore>,
     pub(crate) verify_token: Mutex<[u8; 4]>,
     pub(crate) player: Mutex<Option<Player>>,
-    closed: AtomicBool,
+    pub(crate) forwarded: Mutex<Option<ForwardedData>>,
+    keep_alive: Mutex<KeepAlive>,
+    latency: AtomicI32,
+    closed: watch::Sender<bool>,
     server: Arc<Server>,
 }
 
+struct KeepAlive {
+    last_sent: Instant,
+    /// The id and send time of the keep alive the client has not answered yet.
+    pending: Option<(i64, Instant)>,
+}
+
 impl Connection {
     pub fn new(
         addr: SocketAddr,
         stream: TcpStream,
         server: Arc<Server>,
-    ) -> (Arc<Self>, Receiver<BytesMut>) {
+    ) -> (Arc<Self>, Receiver<SendablePacket>) {
         let (rstream, wstream) = stream.into_split();
         let (tx, rx) = mpsc::channel(128);
 
+        let limits = &server.config().limits;
+        let mut reader = StreamReader::new(rstream);
+        reader.set_size_limits(limits.max_packet_size, limits.max_decompressed_size);
+
         let connection = Arc::new(Self {
             addr,
-            sreader: tokio::sync::Mutex::new(StreamReader::new(rstream)),
+            sreader: tokio::sync::Mutex::new(reader),
             swriter: tokio::sync::Mutex::new(StreamWriter::new(wstream)),
             packet_tx: tx,
             state: RwLock::new(ProtocolState::Handshake),
             game_profile: Mutex::new(None),
+            pending_login: Mutex::new(None),
             key_store: server.key_store(),
             verify_token: Mutex::new([0; 4]),
             player: Mutex::new(None),
-            closed: AtomicBool::new(false),
+            forwarded: Mutex::new(None),
+            keep_alive: Mutex::new(KeepAlive {
+                last_sent: Instant::now(),
+                pending: None,
+            }),
+            latency: AtomicI32::new(0),
+            closed: watch::Sender::new(false),
             server,
         });
 
@@ -76,28 +103,38 @@ impl Connection {
 
     pub async fn accept(addr: SocketAddr, stream: TcpStream, server: Arc<Server>) {
         let (conn, mut rx) = Connection::new(addr, stream, server.clone());
+        server.connections.lock().push(conn.clone());
 
-        let rtask = server.handle().spawn({
-            let conn = conn.clone();
-            async move {
-                conn.read_loop().await;
+        // Both loops run in the task of the connection, so aborting it stops them as well
+        let read = async {
+            let result = AssertUnwindSafe(conn.clone().read_loop()).catch_unwind();
+            // A panicking handler must not keep the write loop alive
+            if result.await.is_err() {
+                log::error!("Read loop of {} panicked", conn.addr());
+                conn.close();
             }
-        });
-        let wtask = server.handle().spawn({
-            let conn = conn.clone();
-            async move {
-                conn.write_loop(&mut rx).await;
+        };
+        let write = async {
+            let result = AssertUnwindSafe(conn.clone().write_loop(&mut rx)).catch_unwind();
+            if result.await.is_err() {
+                log::error!("Write loop of {} panicked", conn.addr());
+                conn.close();
             }
-        });
+        };
+        tokio::join!(read, write);
 
-        tokio::try_join!(rtask, wtask).unwrap();
+        conn.remove_from_server();
+    }
 
-        let player = conn.player.lock();
-        if player.is_some() {
-            let player = player.clone().unwrap();
+    /// Despawns the player of the connection and removes both from the server.
+    pub(crate) fn remove_from_server(self: &Arc<Self>) {
+        let player = self.player.lock().clone();
+        if let Some(player) = player {
             player.despawn();
         }
-        server.players.lock().retain(|p| p.addr() != addr);
+        let server = &self.server;
+        server.players.lock().retain(|p| p.addr() != self.addr());
+        server.connections.lock().retain(|c| !Arc::ptr_eq(c, self));
     }
 
     pub async fn set_compression(&self, threshold: i32) {
@@ -111,36 +148,82 @@ impl Connection {
     }
 
     pub async fn read_loop(self: Arc<Self>) {
+        let mut closed = self.closed.subscribe();
+        let packets_per_second = self.server.config().limits.packets_per_second;
+        let mut rate_window = Instant::now();
+        let mut rate_count = 0;
+
         let this = self.clone();
         while !this.closed() {
             let this = this.clone();
             let packet = {
                 let mut reader = this.sreader.lock().await;
-                match reader.read_packet().await {
-                    Ok(v) => v,
-                    Err(_) => break,
+                tokio::select! {
+                    result = reader.read_packet() => match result {
+                        Ok(v) => v,
+                        Err(e) => {
+                            log::debug!("Failed to read packet from {}: {}", this.addr(), e);
+                            break;
+                        }
+                    },
+                    _ = wait_closed(&mut closed) => break,
                 }
             }; // reader (MutexGuard) dropped here
 
+            // Rate limit
+            if packets_per_second > 0 {
+                if rate_window.elapsed() >= Duration::from_secs(1) {
+                    rate_window = Instant::now();
+                    rate_count = 0;
+                }
+                rate_count += 1;
+                if rate_count > packets_per_second {
+                    log::warn!("{} sent too many packets.", this.addr());
+                    this.kick("You are sending too many packets!");
+                    break;
+                }
+            }
+
             if let Err(e) = this
                 .handle_packet(packet.id(), &mut Cursor::new(packet.data()))
                 .await
             {
-                log::error!("Failed to handle packet: {}", e);
+                log::error!("Failed to handle packet from {}: {}", self.addr(), e);
+                self.kick(format!("Invalid packet: {}", e));
                 break;
             }
         }
-    }
 
-    pub async fn write_loop(self: Arc<Self>, rx: &mut Receiver<BytesMut>) {
-        while !self.closed() {
-            let Some(data) = rx.recv().await else {
-                self.close();
-                break;
-            };
+        // Stops the write loop if the client disconnected
+        self.close();
+    }
 
-            self.write_packet(data).await;
+    pub async fn write_loop(self: Arc<Self>, rx: &mut Receiver<SendablePacket>) {
+        let mut closed = self.closed.subscribe();
+        loop {
+            tokio::select! {
+                data = rx.recv() => {
+                    let Some(data) = data else {
+                        self.close();
+                        break;
+                    };
+                    if self.write_packet(data).await.is_err() {
+                        break;
+                    }
+                }
+                _ = wait_closed(&mut closed) => {
+                    // Flush the packets that were queued before closing, e.g. a disconnect packet
+                    while let Ok(data) = rx.try_recv() {
+                        if self.write_packet(data).await.is_err() {
+                            break;
+                        }
+                    }
+                    break;
+                }
+            }
         }
+
+        let _ = self.swriter.lock().await.shutdown().await;
     }
 
     pub fn set_state(&self, state: ProtocolState) {
@@ -151,8 +234,14 @@ impl Connection {
         *self.state.read()
     }
 
+    /// Returns the address of the client.
+    ///
+    /// If the player was forwarded by a proxy, this is the address of the player instead of the proxy.
     pub fn addr(&self) -> SocketAddr {
-        self.addr
+        match &*self.forwarded.lock() {
+            Some(data) => SocketAddr::new(data.address, self.addr.port()),
+            None => self.addr,
+        }
     }
 
     pub fn send_packet<P>(&self, packet: &P)
@@ -167,12 +256,22 @@ impl Connection {
         };
 
         // Enqueue packet
-        if let Err(_) = self.packet_tx.try_send(data) {
+        if let Err(_) = self.packet_tx.try_send(SendablePacket::Encoded(data)) {
             log::warn!("Failed to enqueue packet. ({})", std::any::type_name::<P>());
             self.close();
         }
     }
 
+    /// Sends a packet that was encoded beforehand, without encoding it again.
+    ///
+    /// The packet must have been encoded for the current state of the connection.
+    pub fn send_framed_packet(&self, packet: impl Into<SendablePacket>) {
+        if self.packet_tx.try_send(packet.into()).is_err() {
+            log::warn!("Failed to enqueue framed packet.");
+            self.close();
+        }
+    }
+
     pub async fn send_packet_now<P>(&self, packet: &P)
     where
         P: Packet + ServerPacket + 'static,
@@ -185,14 +284,14 @@ impl Connection {
         };
 
         // Write the packet immediately
-        self.write_packet(data).await;
+        let _ = self.write_packet(SendablePacket::Encoded(data)).await;
     }
 
     fn encode_packet<P>(&self, packet: &P) -> Result<BytesMut, EncodeError>
     where
         P: Packet + ServerPacket + 'static,
     {
-        let state = self.state.try_read().unwrap();
+        let state = self.state();
 
         let Some(packet_id) = packet_id::<P>(&state) else {
             log::error!(
@@ -209,35 +308,105 @@ impl Connection {
         Ok(data)
     }
 
-    async fn write_packet(&self, data: BytesMut) {
+    async fn write_packet(&self, packet: SendablePacket) -> Result<(), EncodeError> {
         let mut swriter = self.swriter.lock().await;
 
-        if let Err(err) = swriter.write_packet(&data).await {
+        let result = match packet {
+            SendablePacket::Encoded(data) => swriter.write_packet(&data).await,
+            SendablePacket::Framed(packet) => match packet.frame(swriter.threshold) {
+                Ok(frame) => swriter.write_frame(&frame).await,
+                Err(err) => Err(err),
+            },
+        };
+        result.inspect_err(|err| {
             log::error!("Failed to send packet: {}", err);
             self.close();
-            return;
-        }
+        })
     }
 
     pub fn kick(&self, reason: impl Into<TextComponent>) {
-        match *self.state.try_read().unwrap() {
-            ProtocolState::Login => {} // LoginDisconnectPacket { reason: todo!() },
-            _ => self.send_packet(&DisconnectPacket {
-                reason: reason.into(),
+        let reason = reason.into();
+        match self.state() {
+            ProtocolState::Handshake | ProtocolState::Status => {}
+            ProtocolState::Login => self.send_packet(&LoginDisconnectPacket {
+                reason: serde_json::to_string(&reason).unwrap_or_default(),
             }),
+            _ => self.send_packet(&DisconnectPacket { reason }),
         }
         self.close();
     }
 
+    /// Sends a new keep alive packet if the interval has passed and
+    /// disconnects the client if the last one was not answered in time.
+    pub(crate) fn tick_keep_alive(&self) {
+        let config = &self.server.config().network;
+
+        let mut keep_alive = self.keep_alive.lock();
+        if let Some((_, sent)) = keep_alive.pending {
+            if sent.elapsed() > config.keep_alive_timeout {
+                drop(keep_alive);
+                self.kick("Timed out");
+            }
+            return;
+        }
+
+        if keep_alive.last_sent.elapsed() > config.keep_alive_interval {
+            let keep_alive_id = rand::random();
+            let now = Instant::now();
+            keep_alive.last_sent = now;
+            keep_alive.pending = Some((keep_alive_id, now));
+            drop(keep_alive);
+
+            self.send_packet(&KeepAlivePacket { keep_alive_id });
+        }
+    }
+
+    /// Validates a keep alive answered by the client.
+    ///
+    /// Returns the updated latency, or `None` if the client was disconnected because of an unknown id.
+    pub(crate) fn handle_keep_alive(&self, keep_alive_id: i64) -> Option<i32> {
+        let pending = self.keep_alive.lock().pending.take();
+
+        let Some((_, sent)) = pending.filter(|(id, _)| *id == keep_alive_id) else {
+            log::warn!("{} sent an unexpected keep alive id.", self.addr());
+            self.kick("Timed out");
+            return None;
+        };
+
+        // Smooth the latency the same way the vanilla server does.
+        let sample = sent.elapsed().as_millis().min(i32::MAX as u128) as i32;
+        let previous = self.latency();
+        let latency = if previous == 0 {
+            sample
+        } else {
+            (previous * 3 + sample) / 4
+        };
+        self.latency.store(latency, Ordering::Release);
+
+        Some(latency)
+    }
+
+    /// Returns the round-trip latency of the client in milliseconds.
+    pub fn latency(&self) -> i32 {
+        self.latency.load(Ordering::Acquire)
+    }
+
     pub fn server(&self) -> &Arc<Server> {
         &self.server
     }
 
     pub fn closed(&self) -> bool {
-        self.closed.load(Ordering::Acquire)
+        *self.closed.borrow()
     }
 
+    /// Closes the connection once all queued packets are written.
     pub fn close(&self) {
-        self.closed.store(true, Ordering::Release);
+        self.closed.send_replace(true);
     }
 }
+
+/// Waits until the value of a closed flag becomes `true`.
+pub(crate) async fn wait_closed(closed: &mut watch::Receiver<bool>) {
+    // The guard returned by `wait_for` is not `Send`, so it must not escape this function
+    let _ = closed.wait_for(|closed| *closed).await;
+}
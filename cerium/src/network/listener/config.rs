@@ -2,9 +2,8 @@ use std::{io::Cursor, sync::Arc};
 
 use crate::entity::{EntityLike as _, Player};
 use crate::event::player::PlayerSpawnEvent;
-use crate::registry::{DimensionType, REGISTRIES};
-use crate::util::{Position, TeleportFlags, Viewable};
-use crate::world::Chunk;
+use crate::registry::REGISTRIES;
+use crate::util::{ChunkPos, Position, TeleportFlags, Viewable};
 use crate::{event::player::PlayerConfigEvent, network::client::Connection};
 use crate::{
     protocol::{
@@ -25,8 +24,8 @@ pub fn handle_packet(client: Arc<Connection>, id: i32, data: &mut Cursor<&[u8]>)
         0x00 => handle_client_info(client, ClientInfoPacket::decode(data)?),
         0x01 => handle_cookie_response(client),
         0x02 => handle_plugin_message(client, PluginMessagePacket::decode(data)?),
-        0x03 => handle_acknowledge_finish_config(client, AcknowledgeFinishConfigPacket::decode(data)?),
-        0x04 => handle_keep_alive(client),
+        0x03 => handle_acknowledge_finish_config(client, AcknowledgeFinishConfigPacket::decode(data)?)?,
+        0x04 => handle_keep_alive(client, client::play::KeepAlivePacket::decode(data)?),
         0x05 => handle_pong(client),
         0x06 => handle_resource_pack_response(client),
         0x07 => handle_client_known_packs(client, client::config::KnownPacksPacket::decode(data)?),
@@ -67,17 +66,14 @@ fn handle_plugin_message(_client: Arc<Connection>, _packet: PluginMessagePacket)
 fn handle_acknowledge_finish_config(
     client: Arc<Connection>,
     _packet: AcknowledgeFinishConfigPacket,
-) {
-    client.set_state(ProtocolState::Play);
+) -> Result<(), DecodeError> {
+    if client.game_profile.lock().is_none() || client.player.lock().is_some() {
+        return Err(DecodeError::Decode(
+            "Unexpected finish configuration acknowledgement",
+        ));
+    }
 
     let player = Player::new(client.clone(), client.server().clone());
-    {
-        let mut players = client.server().players.lock();
-        players.push(player.clone());
-
-        let mut guard = client.player.lock();
-        *guard = Some(player.clone());
-    }
 
     let mut event = PlayerConfigEvent {
         player: player.clone(),
@@ -86,34 +82,51 @@ fn handle_acknowledge_finish_config(
     };
     client.server().events().fire(&mut event);
 
-    if let Some(world) = event.world {
-        player.0.set_world(world);
-    } else {
-        todo!("no world set");
+    let (Some(world), Some(position)) = (event.world, event.position) else {
+        log::error!("No world or position was set in the PlayerConfigEvent");
+        client.kick("No world to spawn in.");
+        return Ok(());
+    };
+    player.0.update_world(world.clone());
+    player.0.set_position(position);
+
+    client.set_state(ProtocolState::Play);
+    {
+        let mut players = client.server().players.lock();
+        players.push(player.clone());
+
+        let mut guard = client.player.lock();
+        *guard = Some(player.clone());
     }
 
-    let position = if let Some(position) = event.position {
-        player.0.set_position(position);
-        position
-    } else {
-        todo!("no position set");
-    };
+    let config = client.server().config();
 
-    let dimension = DimensionType::OVERWORLD.clone();
+    let mut dimension_names = client
+        .server()
+        .worlds()
+        .iter()
+        .map(|world| world.name().clone())
+        .collect::<Vec<_>>();
+    if !dimension_names.contains(world.name()) {
+        dimension_names.push(world.name().clone());
+    }
 
     client.send_packet(&LoginPacket {
         entity_id: player.id(),
         is_hardcore: false,
-        dimension_names: vec![dimension.as_key().clone()],
-        max_players: 20,
-        view_distance: 32,
-        simulation_distance: 8,
+        dimension_names,
+        max_players: config.limits.max_players.try_into().unwrap_or(i32::MAX),
+        view_distance: config.view_distance,
+        simulation_distance: config.simulation_distance,
         reduced_debug_info: false,
         enable_respawn_screen: true,
         do_limited_crafting: false,
-        dimension_type: REGISTRIES.dimension_type.get_id(&dimension).unwrap_or(0) as i32,
-        dimension_name: dimension.as_key().clone(),
-        hashed_seed: 93522819,
+        dimension_type: REGISTRIES
+            .dimension_type
+            .get_id(world.dimension())
+            .unwrap_or(0) as i32,
+        dimension_name: world.name().clone(),
+        hashed_seed: config.hashed_seed,
         game_mode: 0,
         previous_game_mode: -1,
         is_debug: false,
@@ -125,13 +138,13 @@ fn handle_acknowledge_finish_config(
     });
 
     player.synchronize_position(position, Position::ZERO, TeleportFlags::empty());
+    world.add_player(player.clone());
+    world.send_environment(&player);
 
     client.send_packet(&GameEventPacket::START_WAITING_FOR_CHUNKS);
 
-    let (cx, cy) = Chunk::to_chunk_pos(position);
     client.send_packet(&SetCenterChunkPacket {
-        chunk_x: cx,
-        chunk_z: cy,
+        chunk: ChunkPos::from(position),
     });
 
     client.server().events().fire(&mut PlayerSpawnEvent {
@@ -140,11 +153,12 @@ fn handle_acknowledge_finish_config(
 
     let online_players = &*client.server().players.lock();
 
-    // Add player to tab for already playing players.
+    // Add player to tab for already playing players, and show it to players in the same world.
     for online_player in online_players {
-        online_player.send_packet(&player.0.add_to_list_packet());
-        if *online_player != player {
+        if *online_player != player && online_player.world() == world {
             player.add_viewer(online_player.clone());
+        } else {
+            online_player.send_packet(&player.0.add_to_list_packet());
         }
     }
 
@@ -153,13 +167,21 @@ fn handle_acknowledge_finish_config(
         if *online_player == player {
             continue;
         }
-        online_player.add_viewer(player.clone());
+        if online_player.world() == world {
+            online_player.add_viewer(player.clone());
+        } else {
+            player.send_packet(&online_player.0.add_to_list_packet());
+        }
     }
 
     player.0.load_chunks();
+    world.show_entities(&player);
+    Ok(())
 }
 
-fn handle_keep_alive(_client: Arc<Connection>) {}
+fn handle_keep_alive(client: Arc<Connection>, packet: client::play::KeepAlivePacket) {
+    client.handle_keep_alive(packet.keep_alive_id);
+}
 
 fn handle_pong(_client: Arc<Connection>) {}
 
@@ -1,6 +1,10 @@
-use std::{io::Cursor, sync::Arc};
+use std::{io::Cursor, net::IpAddr, sync::Arc};
+
+use uuid::Uuid;
 
 use crate::{
+    auth::Property,
+    config::ForwardingMode,
     network::client::Connection,
     protocol::{
         ProtocolState,
@@ -12,18 +16,75 @@ use crate::{
 #[rustfmt::skip]
 pub fn handle_packet(client: Arc<Connection>, id: i32, data: &mut Cursor<&[u8]>) -> Result<(), DecodeError> {
     match id {
-        0x00 => handle_handshake(client, HandshakePacket::decode(data)?),
+        0x00 => handle_handshake(client, HandshakePacket::decode(data)?)?,
         _ => return Err(DecodeError::UnkownPacket(id)),
     };
     Ok(())
 }
 
-fn handle_handshake(client: Arc<Connection>, packet: HandshakePacket) {
+fn handle_handshake(client: Arc<Connection>, packet: HandshakePacket) -> Result<(), DecodeError> {
     let state = match packet.intent {
         1 => ProtocolState::Status,
-        2 => ProtocolState::Login,
-        3 => unimplemented!("Not yet implemented."),
-        _ => panic!("Invalid next intent"),
+        2 | 3 => ProtocolState::Login,
+        _ => return Err(DecodeError::Decode("Invalid next intent")),
     };
     client.set_state(state);
+
+    if state != ProtocolState::Login {
+        return Ok(());
+    }
+
+    // Transfer
+    if packet.intent == 3 {
+        client.kick("This server does not accept transfers.");
+        return Ok(());
+    }
+
+    let server = client.server().clone();
+    if server.config().forwarding == ForwardingMode::Legacy {
+        match ForwardedData::parse(&packet.server_address) {
+            Some(data) => *client.forwarded.lock() = Some(data),
+            None => {
+                client.kick(
+                    "If you wish to use IP forwarding, please enable it in your proxy config as well!",
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    // Keyed on the forwarded address, so one proxy does not throttle all of its players
+    let delay = server.config().limits.login_throttle;
+    if !server.login_throttle.try_login(client.addr().ip(), delay) {
+        client.kick("Connection throttled! Please wait before reconnecting.");
+    }
+    Ok(())
+}
+
+/// Player information appended to the handshake by a proxy using legacy (BungeeCord) forwarding.
+#[derive(Debug, Clone)]
+pub(crate) struct ForwardedData {
+    pub address: IpAddr,
+    pub uuid: Uuid,
+    pub properties: Vec<Property>,
+}
+
+impl ForwardedData {
+    /// Parses the server address of the form `host\0address\0uuid[\0properties]`.
+    fn parse(server_address: &str) -> Option<Self> {
+        let mut parts = server_address.split('\0');
+        let _host = parts.next()?;
+        let address = parts.next()?.parse().ok()?;
+        let uuid = Uuid::parse_str(parts.next()?).ok()?;
+        let properties = match parts.next() {
+            Some(properties) => serde_json::from_str(properties).ok()?,
+            None => vec![],
+        };
+
+        Some(Self {
+            address,
+            uuid,
+            properties,
+        })
+    }
 }
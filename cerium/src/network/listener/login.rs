@@ -2,6 +2,7 @@ use std::{io::Cursor, sync::Arc};
 
 use crate::{
     auth::{self, GameProfile},
+    config::ForwardingMode,
     entity::EntityLike,
     network::client::Connection,
     protocol::{
@@ -18,9 +19,9 @@ use crate::{
 pub async fn handle_packet(client: Arc<Connection>, id: i32, data: &mut Cursor<&[u8]>) -> Result<(), DecodeError> {
     match id {
         0x00 => handle_login_start(client, LoginStartPacket::decode(data)?).await,
-        0x01 => handle_encryption_response(client, EncryptionResponsePacket::decode(data)?).await,
+        0x01 => handle_encryption_response(client, EncryptionResponsePacket::decode(data)?).await?,
         0x02 => handle_plugin_response(client),
-        0x03 => handle_login_acknowledged(client, LoginAcknowledgePacket::decode(data)?),
+        0x03 => handle_login_acknowledged(client, LoginAcknowledgePacket::decode(data)?)?,
         0x04 => handle_cookie_response(client),
         _ => return Err(DecodeError::UnkownPacket(id)),
     };
@@ -28,33 +29,48 @@ pub async fn handle_packet(client: Arc<Connection>, id: i32, data: &mut Cursor<&
 }
 
 async fn handle_login_start(client: Arc<Connection>, packet: LoginStartPacket) {
-    for player in client.server().players.lock().clone() {
-        if player.uuid() == packet.uuid {
-            client.kick("Already connected.");
-            return;
-        }
+    let config = client.server().config().clone();
+
+    let forwarded = client.forwarded.lock().clone();
+    let (uuid, properties) = match forwarded {
+        Some(data) => (data.uuid, data.properties),
+        None => (packet.uuid, vec![]),
+    };
+
+    let (already_connected, full) = {
+        let players = client.server().players.lock();
+        (
+            players.iter().any(|player| player.uuid() == uuid),
+            players.len() >= config.limits.max_players as usize,
+        )
+    };
+
+    if already_connected {
+        client.kick("Already connected.");
+        return;
     }
 
-    *client.game_profile.lock() = Some(GameProfile {
-        uuid: packet.uuid,
-        name: packet.name,
-        properties: vec![],
-    });
+    if full {
+        client.kick("The server is full!");
+        return;
+    }
 
-    let threshold = 256;
+    let threshold = config.network.compression_threshold;
 
-    if true {
+    if threshold >= 0 {
         client
             .send_packet_now(&SetCompressionPacket { threshold })
             .await;
         client.set_compression(threshold).await;
     }
 
-    // todo: check for online mode
-    if false {
+    // Players forwarded by a proxy are already authenticated by it.
+    if config.online_mode && config.forwarding == ForwardingMode::None {
         // online mode
         let verify_token: [u8; 4] = rand::random();
         *client.verify_token.lock() = verify_token;
+        // The claimed name is only trusted once the session servers verified it
+        *client.pending_login.lock() = Some(packet.name);
 
         client.send_packet(&EncryptionRequestPacket {
             server_id: "".to_owned(),
@@ -64,34 +80,74 @@ async fn handle_login_start(client: Arc<Connection>, packet: LoginStartPacket) {
         });
     } else {
         // offline mode
-        client.send_packet(&LoginSuccessPacket::from(
-            client.game_profile.lock().clone().unwrap(),
-        ));
+        let game_profile = GameProfile {
+            uuid,
+            name: packet.name,
+            properties,
+        };
+        *client.game_profile.lock() = Some(game_profile.clone());
+
+        client.send_packet(&LoginSuccessPacket::from(game_profile));
     }
 }
 
-async fn handle_encryption_response(client: Arc<Connection>, packet: EncryptionResponsePacket) {
-    let shared_secret = client.key_store.decrypt(&packet.shared_secret).unwrap();
+async fn handle_encryption_response(
+    client: Arc<Connection>,
+    packet: EncryptionResponsePacket,
+) -> Result<(), DecodeError> {
+    let Some(username) = client.pending_login.lock().take() else {
+        return Err(DecodeError::Decode("Unexpected encryption response"));
+    };
+
+    let verify_token = client
+        .key_store
+        .decrypt(&packet.verify_token)
+        .map_err(|_| DecodeError::Decode("Invalid verify token"))?;
+    if verify_token != *client.verify_token.lock() {
+        return Err(DecodeError::Decode("Invalid verify token"));
+    }
+
+    let shared_secret = client
+        .key_store
+        .decrypt(&packet.shared_secret)
+        .map_err(|_| DecodeError::Decode("Invalid shared secret"))?;
+    if shared_secret.len() != 16 {
+        return Err(DecodeError::Decode("Invalid shared secret"));
+    }
 
     // enable encryption
     client.set_encryption(&shared_secret).await;
 
-    let mut client_game_profile = client.game_profile.lock();
-
-    let username = &client_game_profile.clone().unwrap().name;
     let hash = &client.key_store.digest_secret(&shared_secret);
 
-    let game_profile = auth::authenthicate(username, hash, None).unwrap();
+    let game_profile = match auth::authenthicate(&username, hash, None) {
+        Ok(v) => v,
+        Err(e) => {
+            log::warn!("Failed to authenticate {}: {}", username, e);
+            client.kick("Failed to verify username!");
+            return Ok(());
+        }
+    };
 
-    *client_game_profile = Some(game_profile.clone());
+    *client.game_profile.lock() = Some(game_profile.clone());
 
     client.send_packet(&LoginSuccessPacket::from(game_profile));
+    Ok(())
 }
 
 fn handle_plugin_response(_client: Arc<Connection>) {}
 
-fn handle_login_acknowledged(client: Arc<Connection>, _packet: LoginAcknowledgePacket) {
+fn handle_login_acknowledged(
+    client: Arc<Connection>,
+    _packet: LoginAcknowledgePacket,
+) -> Result<(), DecodeError> {
+    // The profile is only set once the login success was sent
+    if client.game_profile.lock().is_none() {
+        return Err(DecodeError::Decode("Unexpected login acknowledgement"));
+    }
+
     client.set_state(ProtocolState::Config);
+    Ok(())
 }
 
 fn handle_cookie_response(_client: Arc<Connection>) {}
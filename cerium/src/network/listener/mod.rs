@@ -9,6 +9,8 @@ mod login;
 mod play;
 mod status;
 
+pub(crate) use handshake::ForwardedData;
+
 impl Connection {
     pub async fn handle_packet(
         self: Arc<Self>,
@@ -23,7 +25,9 @@ impl Connection {
             ProtocolState::Play => {
                 let player = {
                     let player = self.player.lock();
-                    player.clone().unwrap()
+                    player
+                        .clone()
+                        .ok_or(DecodeError::Decode("Received play packet without a player"))?
                 };
 
                 play::handle_packet(player, id, data)
@@ -31,3 +35,390 @@ impl Connection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::BytesMut;
+    use rand::{Rng as _, SeedableRng as _, rngs::StdRng};
+    use tokio::{io::AsyncWriteExt as _, net::TcpStream};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        Server, ServerConfig,
+        config::ForwardingMode,
+        entity::{EntityLike as _, GameMode},
+        protocol::{
+            encode::{PacketWrite as _, packet_id},
+            packet::{DisconnectPacket, LoginDisconnectPacket, server::KeepAlivePacket},
+        },
+        test_util::{connect, connect_player, next_packet, run_with_server},
+        tickable::Ticker,
+        util::{BlockPosition, Position},
+        world::BlockFace,
+    };
+
+    fn garbage() -> Vec<Vec<u8>> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut payloads = vec![vec![], vec![0x00; 64], vec![0xFF; 64]];
+        for len in [1, 2, 5, 16, 256] {
+            for _ in 0..4 {
+                payloads.push((0..len).map(|_| rng.r#gen()).collect());
+            }
+        }
+        payloads
+    }
+
+    fn feed_garbage(state: ProtocolState) {
+        let mut rejected = 0;
+        run_with_server(async |server| {
+            for id in 0..0x48 {
+                for payload in garbage() {
+                    let (conn, _rx, _client) = connect(&server, state).await;
+                    match conn.handle_packet(id, &mut Cursor::new(&payload[..])).await {
+                        // Some of the garbage happens to be a valid packet
+                        Ok(()) => {}
+                        Err(DecodeError::UnkownPacket(unknown)) => assert_eq!(unknown, id),
+                        Err(DecodeError::IoError(kind)) => panic!("Unexpected io error {kind:?}"),
+                        Err(_) => rejected += 1,
+                    }
+                }
+            }
+        });
+        assert!(rejected > 0, "No garbage was rejected in {state:?}");
+    }
+
+    /// Writes an uncompressed frame with the packet to the client side of the socket.
+    async fn write_frame(client: &mut TcpStream, id: i32, data: &[u8]) {
+        let mut packet = BytesMut::new();
+        packet.write_varint(id).unwrap();
+        packet.extend_from_slice(data);
+        let mut frame = BytesMut::new();
+        frame.write_varint(packet.len() as i32).unwrap();
+        frame.extend_from_slice(&packet);
+        client.write_all(&frame).await.unwrap();
+    }
+
+    #[test]
+    fn test_malformed_packet_disconnects() {
+        let disconnect = packet_id::<DisconnectPacket>(&ProtocolState::Play).unwrap();
+
+        run_with_server(async |server| {
+            // A player action on the face 9, which does not exist
+            let (conn, mut rx, mut client) = connect(&server, ProtocolState::Play).await;
+            write_frame(&mut client, 0x28, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0]).await;
+            conn.clone().read_loop().await;
+            assert!(conn.closed());
+            let reason = next_packet(&mut rx, disconnect);
+            assert!(String::from_utf8_lossy(&reason).contains("Invalid BlockFace"));
+
+            // A handshake with an unknown intent
+            let (conn, _rx, mut client) = connect(&server, ProtocolState::Handshake).await;
+            write_frame(&mut client, 0x00, &[0, 0, 0x63, 0xDD, 9]).await;
+            let result = conn
+                .clone()
+                .handle_packet(0x00, &mut Cursor::new(&[0, 0, 0x63, 0xDD, 9][..]))
+                .await;
+            assert!(matches!(
+                result,
+                Err(DecodeError::Decode("Invalid next intent"))
+            ));
+            conn.clone().read_loop().await;
+            assert!(conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_packet_rate_limit() {
+        let config = ServerConfig::builder().packets_per_second(3).build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let disconnect = packet_id::<DisconnectPacket>(&ProtocolState::Config).unwrap();
+
+        handle.block_on(async {
+            // Packets within the limit are handled until the client disconnects
+            let (conn, mut rx, mut client) = connect(&server, ProtocolState::Config).await;
+            for _ in 0..3 {
+                write_frame(&mut client, 0x05, &[0, 0, 0, 0]).await;
+            }
+            drop(client);
+            conn.clone().read_loop().await;
+            assert!(rx.try_recv().is_err());
+
+            // One more packet within the same second kicks the client
+            let (conn, mut rx, mut client) = connect(&server, ProtocolState::Config).await;
+            for _ in 0..4 {
+                write_frame(&mut client, 0x05, &[0, 0, 0, 0]).await;
+            }
+            conn.clone().read_loop().await;
+            assert!(conn.closed());
+            let reason = next_packet(&mut rx, disconnect);
+            assert!(String::from_utf8_lossy(&reason).contains("too many packets"));
+        });
+    }
+
+    #[test]
+    fn test_config_keep_alive() {
+        let config = ServerConfig::builder()
+            .keep_alive_interval(Duration::ZERO)
+            .keep_alive_timeout(Duration::ZERO)
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let keep_alive = packet_id::<KeepAlivePacket>(&ProtocolState::Config).unwrap();
+        let disconnect = packet_id::<DisconnectPacket>(&ProtocolState::Config).unwrap();
+
+        handle.block_on(async {
+            let (conn, mut rx, _client) = connect(&server, ProtocolState::Config).await;
+            server.connections.lock().push(conn.clone());
+            let mut ticker = Ticker::new(server.clone());
+
+            std::thread::sleep(Duration::from_millis(1));
+            ticker.tick().await;
+            next_packet(&mut rx, keep_alive);
+            assert!(!conn.closed());
+
+            // A client stalling in the configuration times out like one in play
+            std::thread::sleep(Duration::from_millis(1));
+            ticker.tick().await;
+            next_packet(&mut rx, disconnect);
+            assert!(conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_forwarded_handshake() {
+        let config = ServerConfig::builder()
+            .forwarding(ForwardingMode::Legacy)
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let uuid = Uuid::new_v4();
+
+        let handshake = |address: String| {
+            let mut data = BytesMut::new();
+            data.write_varint(772).unwrap();
+            data.write_string(&address).unwrap();
+            data.write_u16(25565).unwrap();
+            data.write_varint(2).unwrap();
+            data
+        };
+
+        handle.block_on(async {
+            let (conn, _rx, _client) = connect(&server, ProtocolState::Handshake).await;
+            let properties = r#"[{"name":"textures","value":"skin","signature":"sig"}]"#;
+            let address = format!("localhost\01.2.3.4\0{}\0{properties}", uuid.simple());
+            let data = handshake(address);
+            conn.clone()
+                .handle_packet(0x00, &mut Cursor::new(&data[..]))
+                .await
+                .unwrap();
+            assert!(!conn.closed());
+            assert_eq!(
+                conn.addr().ip(),
+                "1.2.3.4".parse::<std::net::IpAddr>().unwrap()
+            );
+
+            // The uuid and properties sent by the proxy replace the ones of the client
+            let mut data = BytesMut::new();
+            data.write_string(&"Test".to_string()).unwrap();
+            data.write_uuid(&Uuid::new_v4()).unwrap();
+            conn.clone()
+                .handle_packet(0x00, &mut Cursor::new(&data[..]))
+                .await
+                .unwrap();
+            let profile = conn.game_profile.lock().clone().unwrap();
+            assert_eq!(profile.uuid, uuid);
+            assert_eq!(profile.properties.len(), 1);
+            assert_eq!(profile.properties[0].value, "skin");
+
+            // Players connecting without the proxy are kicked
+            let (conn, _rx, _client) = connect(&server, ProtocolState::Handshake).await;
+            let data = handshake("localhost".to_string());
+            conn.clone()
+                .handle_packet(0x00, &mut Cursor::new(&data[..]))
+                .await
+                .unwrap();
+            assert!(conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_online_login_requires_encryption() {
+        let config = ServerConfig::builder()
+            .online_mode(true)
+            .compression_threshold(-1)
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let disconnect = packet_id::<LoginDisconnectPacket>(&ProtocolState::Login).unwrap();
+
+        let mut login_start = BytesMut::new();
+        login_start.write_string(&"Notch".to_string()).unwrap();
+        login_start.write_uuid(&Uuid::new_v4()).unwrap();
+
+        handle.block_on(async {
+            // Acknowledging the login without answering the encryption request
+            let (conn, mut rx, mut client) = connect(&server, ProtocolState::Login).await;
+            write_frame(&mut client, 0x00, &login_start).await;
+            write_frame(&mut client, 0x03, &[]).await;
+            conn.clone().read_loop().await;
+            assert!(conn.closed());
+            assert!(conn.game_profile.lock().is_none());
+            assert_eq!(conn.state(), ProtocolState::Login);
+            next_packet(&mut rx, disconnect);
+
+            // An encryption response without a pending request
+            let (conn, _rx, _client) = connect(&server, ProtocolState::Login).await;
+            let result = conn
+                .clone()
+                .handle_packet(0x01, &mut Cursor::new(&[0, 0][..]))
+                .await;
+            assert!(matches!(
+                result,
+                Err(DecodeError::Decode("Unexpected encryption response"))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_creative_break_outside_of_world() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let player = client.player.clone();
+            player.set_game_mode(GameMode::Creative);
+            let world = player.world();
+
+            let start_digging = async |position: BlockPosition| {
+                let mut data = BytesMut::new();
+                data.write_varint(0).unwrap();
+                data.write_position(&position).unwrap();
+                data.write_u8(BlockFace::Top as u8).unwrap();
+                data.write_varint(1).unwrap();
+                client
+                    .conn
+                    .clone()
+                    .handle_packet(0x28, &mut Cursor::new(&data[..]))
+                    .await
+                    .unwrap();
+            };
+
+            // A block within reach in a chunk that is not loaded
+            let unloaded = BlockPosition::new(1, 64, 0);
+            start_digging(unloaded).await;
+            assert!(world.get_chunk(unloaded).is_none());
+
+            // A block within reach above the world
+            world.load_chunk((0, 0));
+            player
+                .0
+                .set_position(Position::new(0.5, 318.0, 0.5, 0.0, 0.0));
+            start_digging(BlockPosition::new(0, 320, 0)).await;
+
+            // Breaking through the world directly is ignored as well
+            let far = BlockPosition::new(10_000, 64, 10_000);
+            world.break_block(player.clone(), far, BlockFace::Top);
+            world.break_block(
+                player.clone(),
+                BlockPosition::new(0, -10_000, 0),
+                BlockFace::Top,
+            );
+            assert!(world.get_chunk(far).is_none());
+            assert!(!client.conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_keep_alive() {
+        let config = ServerConfig::builder()
+            .keep_alive_interval(Duration::ZERO)
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let keep_alive = packet_id::<KeepAlivePacket>(&ProtocolState::Play).unwrap();
+
+        handle.block_on(async {
+            let (conn, mut rx, _client) = connect(&server, ProtocolState::Play).await;
+            std::thread::sleep(Duration::from_millis(1));
+            conn.tick_keep_alive();
+            let data = next_packet(&mut rx, keep_alive);
+
+            // Only one keep alive is pending at a time
+            conn.tick_keep_alive();
+            assert!(rx.try_recv().is_err());
+            let id = i64::from_be_bytes(data[..8].try_into().unwrap());
+
+            // Echoing the id is accepted and allows the next keep alive
+            conn.clone()
+                .handle_packet(0x1B, &mut Cursor::new(&data[..]))
+                .await
+                .unwrap();
+            assert!(!conn.closed());
+            std::thread::sleep(Duration::from_millis(1));
+            conn.tick_keep_alive();
+            let data = next_packet(&mut rx, keep_alive);
+
+            let next_id = i64::from_be_bytes(data[..8].try_into().unwrap());
+            assert_ne!(next_id, id);
+            let wrong = (next_id ^ 1).to_be_bytes();
+            conn.clone()
+                .handle_packet(0x1B, &mut Cursor::new(&wrong[..]))
+                .await
+                .unwrap();
+            assert!(conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_keep_alive_timeout() {
+        let config = ServerConfig::builder()
+            .keep_alive_interval(Duration::ZERO)
+            .keep_alive_timeout(Duration::ZERO)
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let handle = server.handle().clone();
+        let keep_alive = packet_id::<KeepAlivePacket>(&ProtocolState::Play).unwrap();
+        let disconnect = packet_id::<DisconnectPacket>(&ProtocolState::Play).unwrap();
+
+        handle.block_on(async {
+            let (conn, mut rx, _client) = connect(&server, ProtocolState::Play).await;
+            std::thread::sleep(Duration::from_millis(1));
+            conn.tick_keep_alive();
+            next_packet(&mut rx, keep_alive);
+            assert!(!conn.closed());
+
+            // The client never answers
+            std::thread::sleep(Duration::from_millis(1));
+            conn.tick_keep_alive();
+            next_packet(&mut rx, disconnect);
+            assert!(conn.closed());
+        });
+    }
+
+    #[test]
+    fn test_garbage_handshake() {
+        feed_garbage(ProtocolState::Handshake);
+    }
+
+    #[test]
+    fn test_garbage_status() {
+        feed_garbage(ProtocolState::Status);
+    }
+
+    #[test]
+    fn test_garbage_login() {
+        feed_garbage(ProtocolState::Login);
+    }
+
+    #[test]
+    fn test_garbage_config() {
+        feed_garbage(ProtocolState::Config);
+    }
+
+    #[test]
+    fn test_garbage_play() {
+        feed_garbage(ProtocolState::Play);
+    }
+}
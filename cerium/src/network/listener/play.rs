@@ -2,25 +2,26 @@ use std::io::Cursor;
 
 use crate::{
     entity::{EntityAnimation, EntityLike as _, GameMode, Hand, Player},
-    item::ItemStack,
+    item::{ItemStack, Tool},
     protocol::{
         decode::{Decode as _, DecodeError},
         packet::{
-            AcknowledgeBlockChangePacket, ChangeRecipeBookSettingsPacket, ChatCommandPacket,
-            ChunkBatchReceivedPacket, ClickContainerPacket, ClientInfoPacket, ClientTickEndPacket,
-            ConfirmTeleportationPacket, EntityAnimationPacket, InteractPacket,
+            AcknowledgeBlockChangePacket, BlockUpdatePacket, ChangeRecipeBookSettingsPacket,
+            ChatCommandPacket, ChunkBatchReceivedPacket, ClickContainerPacket, ClientInfoPacket,
+            ClientTickEndPacket, ConfirmTeleportationPacket, EntityAnimationPacket, InteractPacket,
             PickItemFromBlockPacket, PlayerActionPacket, PlayerCommand, PlayerCommandPacket,
             PlayerDiggingState, PlayerInputFlags, PlayerInputPacket, PlayerLoadedPacket,
             PlayerMovementFlagsPacket, PlayerPositionAndRotationPacket, PlayerPositionPacket,
             PlayerRotationPacket, PlayerSessionPacket, PluginMessagePacket,
-            SetBlockDestroyStagePacket, SetCreativeModeSlotPacket, SwingArmPacket, UseItemOnPacket,
+            SetCreativeModeSlotPacket, SwingArmPacket, UseItemOnPacket,
             client::play::{
                 CloseContainerPacket, KeepAlivePacket, PingRequestPacket, PlayerAbilitiesPacket,
                 SetHeldItemPacket,
             },
         },
     },
-    util::{Position, Viewable},
+    util::{Position, TeleportFlags, Viewable},
+    world::BorderMode,
 };
 
 #[rustfmt::skip]
@@ -120,8 +121,8 @@ fn handle_plugin_message(_player: Player, _packet: PluginMessagePacket) {
     log::warn!("todo: handle_plugin_message");
 }
 
-fn handle_keep_alive(_player: Player, _packet: KeepAlivePacket) {
-    log::warn!("todo: handle_keep_alive");
+fn handle_keep_alive(player: Player, packet: KeepAlivePacket) {
+    player.0.handle_keep_alive(packet.keep_alive_id);
 }
 
 // ===== Position & Movement ======
@@ -160,6 +161,16 @@ fn handle_movement(player: Player, new_position: Position, on_ground: bool) {
         return;
     }
 
+    // A solid border only allows moves that do not lead further outside of it
+    let border = player.world().border();
+    if border.mode() == BorderMode::Solid
+        && !border.contains(new_position)
+        && border.distance(new_position) < border.distance(old_position)
+    {
+        player.synchronize_position(old_position, Position::ZERO, TeleportFlags::empty());
+        return;
+    }
+
     player.refresh_position(new_position);
     player.refresh_on_ground(on_ground);
 }
@@ -191,41 +202,58 @@ fn handle_player_abilities(player: Player, packet: PlayerAbilitiesPacket) {
 
 fn handle_player_action(player: Player, packet: PlayerActionPacket) {
     let world = player.world();
-    let status = packet.status;
     let position = packet.position;
     let face = packet.face;
 
-    match status {
-        PlayerDiggingState::StartDigging => {
-            if player.game_mode() == GameMode::Creative {
-                // intant break the block
-                world.break_block(player.clone(), position, face);
+    match packet.status {
+        PlayerDiggingState::StartDigging => match player.game_mode() {
+            GameMode::Creative => {
+                let can_destroy = player
+                    .get_item_in_hand(Hand::MainHand)
+                    .and_then(|item| item.tool().map(Tool::can_destroy_blocks_in_creative))
+                    .unwrap_or(true);
+                // The client must not make the server load chunks or look outside of the world
+                let reachable =
+                    world.get_block_at(position).is_some() && player.0.can_reach_block(position);
+                if can_destroy && reachable {
+                    world.break_block(player.clone(), position, face);
+                }
             }
-        }
-        PlayerDiggingState::CancelledDigging => {}
+            GameMode::Survival => {
+                if player.0.start_digging(position) {
+                    world.break_block(player.clone(), position, face);
+                }
+            }
+            GameMode::Adventure | GameMode::Spectator => {}
+        },
+        PlayerDiggingState::CancelledDigging => player.0.cancel_digging(),
         PlayerDiggingState::FinishedDigging => {
-            world.break_block(player.clone(), position, face);
+            if player.0.finish_digging(position) {
+                world.break_block(player.clone(), position, face);
+            } else if let Some(block) = world.get_block_at(position) {
+                // The block was broken too fast, so the client has to show it again
+                player.send_packet(&BlockUpdatePacket {
+                    position,
+                    block_id: block.state_id(),
+                });
+            }
         }
-        PlayerDiggingState::DropItemStack => {}
-        PlayerDiggingState::DropItem => {}
-        PlayerDiggingState::ItemUpdated => {}
-        PlayerDiggingState::SwapItemInHand => {}
+        PlayerDiggingState::DropItemStack => return,
+        PlayerDiggingState::DropItem => return,
+        PlayerDiggingState::ItemUpdated => return,
+        PlayerDiggingState::SwapItemInHand => return,
     }
 
-    let packet = SetBlockDestroyStagePacket {
-        entitiy_id: player.id(),
-        location: position,
-        destroy_stage: status as u8,
-    };
-    player.send_packet(&packet);
-    player.send_packet_to_viewers(&packet);
+    player.send_packet(&AcknowledgeBlockChangePacket {
+        sequence_id: packet.sequence,
+    });
 }
 
 fn handle_player_command(player: Player, packet: PlayerCommandPacket) {
     match packet.action_id {
         PlayerCommand::StartSprinting => player.0.set_sprinting(true),
         PlayerCommand::StopSprinting => player.0.set_sprinting(false),
-        _ => todo!(),
+        action => log::warn!("todo: handle_player_command {:?}", action),
     }
 }
 
@@ -270,6 +298,16 @@ fn handle_use_item_on(player: Player, packet: UseItemOnPacket) {
     let world = player.world();
     let position = packet.position;
 
+    let cursor = (packet.cursor_x, packet.cursor_y, packet.cursor_z);
+    if !player.is_sneaking()
+        && world.interact_block(&player, position, packet.hand, packet.face, cursor)
+    {
+        player.send_packet(&AcknowledgeBlockChangePacket {
+            sequence_id: packet.sequence,
+        });
+        return;
+    }
+
     let Some(placed_block) = player.get_item_in_hand(packet.hand) else {
         return;
     };
@@ -278,7 +316,7 @@ fn handle_use_item_on(player: Player, packet: UseItemOnPacket) {
         return;
     };
 
-    world.place_block(player.clone(), position, packet.face, block.clone());
+    world.place_block(player.clone(), position, packet.face, cursor, block);
     player.send_packet(&AcknowledgeBlockChangePacket {
         sequence_id: packet.sequence,
     });
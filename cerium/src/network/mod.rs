@@ -2,4 +2,8 @@ pub mod client;
 
 pub(crate) mod listener;
 mod reader;
+mod throttle;
 mod writer;
+
+pub use reader::{MAX_DECOMPRESSED_SIZE, MAX_PACKET_SIZE};
+pub(crate) use throttle::LoginThrottle;
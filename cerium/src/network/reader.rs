@@ -9,7 +9,19 @@ use async_compression::tokio::bufread::ZlibDecoder;
 
 use tokio::io::{AsyncRead, AsyncReadExt, BufReader, ReadBuf};
 
-use crate::{auth::Decryptor, protocol::packet::RawPacket};
+use crate::{
+    auth::Decryptor,
+    protocol::{
+        decode::{DecodeError, PacketRead},
+        packet::RawPacket,
+    },
+};
+
+/// The maximum size of a packet frame accepted by the vanilla server.
+pub const MAX_PACKET_SIZE: usize = 2097151;
+
+/// The maximum size of a decompressed packet accepted by the vanilla server.
+pub const MAX_DECOMPRESSED_SIZE: usize = 8388608;
 
 pub struct StreamReader<R>
 where
@@ -19,6 +31,8 @@ where
 
     cipher: Option<Decryptor>,
     threshold: i32,
+    max_packet_size: usize,
+    max_decompressed_size: usize,
 }
 
 impl<R> StreamReader<R>
@@ -31,6 +45,8 @@ where
 
             cipher: None,
             threshold: -1,
+            max_packet_size: MAX_PACKET_SIZE,
+            max_decompressed_size: MAX_DECOMPRESSED_SIZE,
         }
     }
 
@@ -43,57 +59,82 @@ where
         self.threshold = threshold;
     }
 
-    #[inline]
-    fn varint_size(value: i32) -> i32 {
-        match value {
-            0 => 1,
-            n => ((31 - n.leading_zeros()) / 7 + 1) as i32,
-        }
+    /// Sets the maximum size of a packet frame and of its decompressed data.
+    pub fn set_size_limits(&mut self, max_packet_size: usize, max_decompressed_size: usize) {
+        self.max_packet_size = max_packet_size;
+        self.max_decompressed_size = max_decompressed_size;
     }
 
-    pub async fn read_packet(&mut self) -> Result<RawPacket, ()> {
-        let packet_len = match self.read_varint().await {
-            Ok(v) => v,
-            Err(_) => return Err(()),
-        };
+    pub async fn read_packet(&mut self) -> Result<RawPacket, DecodeError> {
+        let packet_len = self
+            .read_varint()
+            .await
+            .map_err(|e| DecodeError::IoError(e.kind()))?;
 
-        let compressed = self.threshold != -1;
-        let mut take = (self).take(packet_len as u64);
-        if !compressed {
-            // WITHOUT compression
+        if packet_len <= 0 {
+            return Err(DecodeError::Decode("Invalid packet length"));
+        }
+        let packet_len = packet_len as usize;
+        if packet_len > self.max_packet_size {
+            return Err(DecodeError::PacketTooLarge(
+                packet_len,
+                self.max_packet_size,
+            ));
+        }
 
-            let id = take.read_varint().await.unwrap();
+        let mut frame = vec![0; packet_len];
+        self.read_exact(&mut frame)
+            .await
+            .map_err(|e| DecodeError::IoError(e.kind()))?;
 
-            let mut data = Vec::with_capacity(take.limit() as usize);
-            take.read_to_end(&mut data).await.unwrap();
+        let compressed = self.threshold != -1;
+        let data = if !compressed {
+            // WITHOUT compression
 
-            return Ok(RawPacket::new(id, data));
+            frame
         } else {
             // WITH compression
 
-            let data_len = take.read_varint().await.unwrap();
+            let mut payload = &frame[..];
+            let data_len = PacketRead::read_varint(&mut payload)?;
 
-            let mut payload = vec![0; (packet_len - Self::varint_size(data_len)) as usize];
-            take.read_exact(&mut payload).await.unwrap();
+            if data_len == 0 {
+                // size < threshold
 
-            let mut data = Vec::new();
-            if data_len > 0 {
+                payload.to_vec()
+            } else {
                 // size >= threshold
 
-                let mut inflator = ZlibDecoder::new(BufReader::new(&payload[..]));
-                inflator.read_to_end(&mut data).await.unwrap();
-            } else {
-                // size < threshold
+                if data_len < 0 {
+                    return Err(DecodeError::Decode("Invalid data length"));
+                }
+                let data_len = data_len as usize;
+                if data_len > self.max_decompressed_size {
+                    return Err(DecodeError::PacketTooLarge(
+                        data_len,
+                        self.max_decompressed_size,
+                    ));
+                }
 
-                data = payload;
-            }
+                // Never inflate more than the announced length
+                let mut data = Vec::with_capacity(data_len);
+                ZlibDecoder::new(payload)
+                    .take(data_len as u64 + 1)
+                    .read_to_end(&mut data)
+                    .await
+                    .map_err(|e| DecodeError::IoError(e.kind()))?;
 
-            let mut data = &data[..];
+                if data.len() != data_len {
+                    return Err(DecodeError::Decode("Invalid data length"));
+                }
+                data
+            }
+        };
 
-            let id = data.read_varint().await.unwrap();
+        let mut data = &data[..];
+        let id = PacketRead::read_varint(&mut data)?;
 
-            return Ok(RawPacket::new(id, data.to_vec()));
-        }
+        Ok(RawPacket::new(id, data.to_vec()))
     }
 }
 
@@ -152,3 +193,44 @@ where
         return Err(ErrorKind::Other.into());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_packet_too_large() {
+        // Length prefix of 2097152 (one byte more than allowed)
+        let data: &[u8] = &[0x80, 0x80, 0x80, 0x01];
+        let mut reader = StreamReader::new(data);
+
+        assert!(matches!(
+            reader.read_packet().await,
+            Err(DecodeError::PacketTooLarge(2097152, MAX_PACKET_SIZE))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decompressed_too_large() {
+        // Announces 16 MiB of decompressed data
+        let data: &[u8] = &[0x05, 0x80, 0x80, 0x80, 0x08, 0x00];
+        let mut reader = StreamReader::new(data);
+        reader.set_compression(256);
+
+        assert!(matches!(
+            reader.read_packet().await,
+            Err(DecodeError::PacketTooLarge(16777216, MAX_DECOMPRESSED_SIZE))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_packet() {
+        let data: &[u8] = &[0x05, 0x00, 0x01];
+        let mut reader = StreamReader::new(data);
+
+        assert!(matches!(
+            reader.read_packet().await,
+            Err(DecodeError::IoError(ErrorKind::UnexpectedEof))
+        ));
+    }
+}
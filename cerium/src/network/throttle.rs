@@ -0,0 +1,50 @@
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// Limits how often a single IP address may attempt to log in.
+pub(crate) struct LoginThrottle {
+    attempts: Mutex<HashMap<IpAddr, Instant>>,
+}
+
+impl LoginThrottle {
+    pub fn new() -> Self {
+        Self {
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a login attempt and returns `false` if the previous attempt
+    /// from the same address was less than `delay` ago.
+    pub fn try_login(&self, addr: IpAddr, delay: Duration) -> bool {
+        if delay.is_zero() {
+            return true;
+        }
+
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock();
+        attempts.retain(|_, last| now.duration_since(*last) < delay);
+
+        attempts.insert(addr, now).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_login() {
+        let throttle = LoginThrottle::new();
+        let addr = IpAddr::from([127, 0, 0, 1]);
+        let other = IpAddr::from([127, 0, 0, 2]);
+
+        assert!(throttle.try_login(addr, Duration::from_secs(60)));
+        assert!(!throttle.try_login(addr, Duration::from_secs(60)));
+        assert!(throttle.try_login(other, Duration::from_secs(60)));
+        assert!(throttle.try_login(addr, Duration::ZERO));
+    }
+}
@@ -5,9 +5,8 @@ use std::{
 };
 
 use aes::cipher::{BlockEncryptMut as _, BlockSizeUser as _, KeyIvInit as _};
-use async_compression::{Level, tokio::write::ZlibEncoder};
 
-use crate::protocol::encode::EncodeError;
+use crate::protocol::{encode::EncodeError, encode_frame};
 use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 
 use crate::auth::Encryptor;
@@ -44,84 +43,16 @@ where
         self.threshold = threshold;
     }
 
-    async fn write_varint(&mut self, value: i32) -> Result<(), EncodeError> {
-        let mut val = value;
-        for _ in 0..5 {
-            let b: u8 = val as u8 & 0b01111111;
-            val >>= 7;
-            self.write_u8(if val == 0 { b } else { b | 0b10000000 })
-                .await
-                .map_err(|_| EncodeError::Encode("Failed to encode VarInt".to_string()))?;
-            if val == 0 {
-                break;
-            }
-        }
-        Ok(())
-    }
-
-    #[inline]
-    fn varint_size(value: i32) -> i32 {
-        match value {
-            0 => 1,
-            n => ((31 - n.leading_zeros()) / 7 + 1) as i32,
-        }
-    }
-
     pub async fn write_packet(&mut self, packet: &[u8]) -> Result<(), EncodeError> {
-        let compressed = self.threshold != -1;
-
-        let mut data = packet;
-        let data_len = data.len() as i32;
-
-        if !compressed {
-            // WITHOUT compression
-
-            self.write_varint(data_len).await?;
-            self.write_all(&mut data)
-                .await
-                .map_err(|e| EncodeError::IoError(e))?;
-        } else {
-            // WITH compression
-
-            if data_len >= self.threshold {
-                // size >= threshold
-
-                let mut compressed = Vec::new();
-
-                let mut deflator = ZlibEncoder::with_quality(&mut compressed, Level::Default);
-                deflator
-                    .write_all(&data)
-                    .await
-                    .map_err(|e| EncodeError::IoError(e))?;
-                deflator
-                    .flush()
-                    .await
-                    .map_err(|e| EncodeError::IoError(e))?;
-
-                // len of data_len + compressed_len
-                self.write_varint(Self::varint_size(data_len) + compressed.len() as i32)
-                    .await?;
-
-                // data_len
-                self.write_varint(data_len).await?;
-                self.write_all(&compressed)
-                    .await
-                    .map_err(|e| EncodeError::IoError(e))?;
-            } else {
-                // size < threshold
-
-                // len of data_len + data_len (because uncompressed)
-                self.write_varint(1 + data_len).await?;
-
-                // data_len (0 to indicate uncompressed)
-                self.write_varint(0).await?;
-
-                self.write_all(&data)
-                    .await
-                    .map_err(|e| EncodeError::IoError(e))?;
-            }
-        }
+        let frame = encode_frame(packet, self.threshold)?;
+        self.write_frame(&frame).await
+    }
 
+    /// Writes a packet that is already prefixed with its length and compressed.
+    pub async fn write_frame(&mut self, frame: &[u8]) -> Result<(), EncodeError> {
+        self.write_all(frame)
+            .await
+            .map_err(|e| EncodeError::IoError(e))?;
         self.flush().await.map_err(|e| EncodeError::IoError(e))
     }
 }
@@ -0,0 +1,103 @@
+use std::sync::{Arc, LazyLock};
+
+use crate::protocol::{
+    FramedPacket, ProtocolState,
+    packet::{BundleDelimiterPacket, Packet, ServerPacket},
+};
+
+/// The most packets the client accepts in one bundle.
+pub const MAX_BUNDLE_SIZE: usize = 4096;
+
+static DELIMITER: LazyLock<Arc<FramedPacket>> = LazyLock::new(|| {
+    Arc::new(FramedPacket::new(ProtocolState::Play, &BundleDelimiterPacket {}).unwrap())
+});
+
+/// Packets the client applies at once, so it never renders a frame in which only some of them are
+/// applied, e.g. an entity that is spawned but has no metadata yet.
+///
+/// The packets are encoded once when they are added, so a bundle can be sent to many players. Bundles
+/// with more than [`MAX_BUNDLE_SIZE`] packets are split into several bundles.
+#[derive(Debug, Default)]
+pub struct Bundle {
+    packets: Vec<Arc<FramedPacket>>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<P>(&mut self, packet: &P)
+    where
+        P: Packet + ServerPacket + 'static,
+    {
+        match FramedPacket::new(ProtocolState::Play, packet) {
+            Ok(packet) => self.packets.push(Arc::new(packet)),
+            Err(e) => log::error!("Failed to encode bundled packet: {}", e),
+        }
+    }
+
+    pub fn add_framed(&mut self, packet: Arc<FramedPacket>) {
+        self.packets.push(packet);
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Returns the packets wrapped in delimiters, in the order they are sent.
+    pub(crate) fn frames(&self) -> Vec<Arc<FramedPacket>> {
+        let mut frames = Vec::with_capacity(self.packets.len() + 2);
+        for packets in self.packets.chunks(MAX_BUNDLE_SIZE) {
+            // A single packet is applied at once anyway
+            if packets.len() == 1 {
+                frames.push(packets[0].clone());
+                continue;
+            }
+            frames.push(DELIMITER.clone());
+            frames.extend(packets.iter().cloned());
+            frames.push(DELIMITER.clone());
+        }
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packet::SetTimePacket;
+
+    #[test]
+    fn test_bundle_frames() {
+        let packet = SetTimePacket {
+            world_age: 0,
+            time_of_day: 0,
+            time_of_day_increasing: false,
+        };
+        let is_delimiter = |frame: &Arc<FramedPacket>| Arc::ptr_eq(frame, &DELIMITER);
+
+        let mut bundle = Bundle::new();
+        assert!(bundle.frames().is_empty());
+        bundle.add(&packet);
+        assert_eq!(bundle.frames().len(), 1);
+
+        bundle.add(&packet);
+        let frames = bundle.frames();
+        assert_eq!(frames.len(), 4);
+        assert!(is_delimiter(&frames[0]) && is_delimiter(&frames[3]));
+        assert_eq!(frames[0].data().as_ref(), [0x00]);
+
+        // Too many packets are split into several bundles
+        for _ in 0..MAX_BUNDLE_SIZE {
+            bundle.add(&packet);
+        }
+        let frames = bundle.frames();
+        assert_eq!(frames.len(), MAX_BUNDLE_SIZE + 2 + 4);
+        assert!(is_delimiter(&frames[MAX_BUNDLE_SIZE + 1]));
+        assert!(is_delimiter(&frames[MAX_BUNDLE_SIZE + 2]));
+    }
+}
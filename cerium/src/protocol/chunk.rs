@@ -7,6 +7,7 @@ use crate::protocol::{
 };
 use crate::world::{
     BlockEntity, Chunk, ChunkSection,
+    chunk_section::LIGHT_SIZE,
     heightmap::Heightmap,
     palette::{Palette, PaletteFormat},
 };
@@ -17,7 +18,7 @@ impl Decode for BlockEntity {
             packed_xz: r.read_u8()?,
             y: r.read_i16()?,
             r#type: r.read_varint()?,
-            data: Some(r.read_nbt()?),
+            data: r.read_nbt()?.root,
         })
     }
 }
@@ -27,40 +28,24 @@ impl Encode for BlockEntity {
         w.write_u8(this.packed_xz)?;
         w.write_i16(this.y)?;
         w.write_varint(this.r#type)?;
-        if let Some(data) = &this.data {
-            w.write_nbt(data)?;
-        } else {
-            w.write_u8(0)?; // End Tag (temporary solution! todo!)
-        }
+        w.write_nbt_compound(&this.data)?;
         Ok(())
     }
 }
 
-impl Decode for Heightmap {
-    fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
-        Ok(Self {
-            kind: r.read_varint()?,
-            data: r.read_array(|r| r.read_i64())?,
-        })
-    }
-}
-
 impl Encode for Heightmap {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
-        w.write_varint(this.kind)?;
-        w.write_array(&this.data, |w, v| w.write_i64(*v))?;
+        w.write_varint(this.kind().id())?;
+        w.write_array(&this.data(), |w, v| w.write_i64(*v))?;
         Ok(())
     }
 }
 
 impl Encode for Palette {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
-        let (bpe, format, values) = this.compute();
-        w.write_u8(bpe)?;
-        PaletteFormat::encode(w, &format)?;
-        for value in &values {
-            w.write_i64(*value)?;
-        }
+        w.write_u8(this.bpe())?;
+        PaletteFormat::encode(w, this.format())?;
+        w.write_bytes(this.data())?;
         Ok(())
     }
 }
@@ -91,26 +76,61 @@ impl Encode for ChunkSection {
 
 impl Into<ChunkDataAndUpdateLightPacket> for &Chunk {
     fn into(self) -> ChunkDataAndUpdateLightPacket {
+        let sections = self.sections();
         let mut data = BytesMut::new();
-        for section in self.sections() {
-            ChunkSection::encode(&mut data, &section).unwrap();
+        for section in &sections {
+            ChunkSection::encode(&mut data, section).unwrap();
         }
 
-        let chunk_x = self.x();
-        let chunk_z = self.z();
-
         let data = ChunkData {
-            heightmaps: vec![],
+            heightmaps: self.client_heightmaps(),
             data: data.to_vec(),
             block_entities: self.block_entites(),
         };
-        let light = LightData {};
+        let light = light_data(&sections);
 
         ChunkDataAndUpdateLightPacket {
-            chunk_x,
-            chunk_z,
+            chunk: self.pos(),
             data,
             light,
         }
     }
 }
+
+/// Builds the light of the sections from their stored light.
+///
+/// The server does not compute light, so sections without stored sky light, including the sections below and
+/// above the world, are sent fully lit. Sections without stored block light have none.
+fn light_data(sections: &[ChunkSection]) -> LightData {
+    let count = sections.len() + 2;
+    let mut light = LightData {
+        sky_light_mask: vec![0; count.div_ceil(64)],
+        block_light_mask: vec![0; count.div_ceil(64)],
+        empty_sky_light_mask: Vec::new(),
+        empty_block_light_mask: vec![0; count.div_ceil(64)],
+        sky_light: Vec::new(),
+        block_light: Vec::new(),
+    };
+
+    for index in 0..count {
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        let section = index.checked_sub(1).and_then(|index| sections.get(index));
+
+        light.sky_light_mask[word] |= bit;
+        light.sky_light.push(
+            match section.and_then(|section| section.sky_light.as_ref()) {
+                Some(sky_light) => sky_light.to_vec(),
+                None => vec![0xFF; LIGHT_SIZE],
+            },
+        );
+
+        match section.and_then(|section| section.block_light.as_ref()) {
+            Some(block_light) => {
+                light.block_light_mask[word] |= bit;
+                light.block_light.push(block_light.to_vec());
+            }
+            None => light.empty_block_light_mask[word] |= bit,
+        }
+    }
+    light
+}
@@ -7,7 +7,7 @@ use crate::{
     text::TextComponent,
     util::{BlockPosition, Identifier},
 };
-use cerium_nbt::Nbt;
+use cerium_nbt::{Nbt, NbtCompound};
 
 pub trait Decode
 where
@@ -26,6 +26,10 @@ pub enum DecodeError {
     NotEnoughBytes(usize, usize),
     #[error("Unknown Packet: {0}")]
     UnkownPacket(i32),
+    #[error("Packet too large. (Size: {0}, Maximum: {1})")]
+    PacketTooLarge(usize, usize),
+    #[error("std::io::Error - {0}")]
+    IoError(std::io::ErrorKind),
 }
 
 type Result<T> = core::result::Result<T, DecodeError>;
@@ -97,6 +101,20 @@ macro_rules! read_impl {
     };
 }
 
+/// Reads a length prefix and checks that enough bytes are remaining.
+fn read_length<R: Buf>(r: &mut R) -> Result<usize> {
+    let length = r.read_varint()?;
+    if length < 0 {
+        return Err(DecodeError::Decode("Negative length"));
+    }
+
+    let length = length as usize;
+    if length > r.remaining() {
+        return Err(DecodeError::NotEnoughBytes(r.remaining(), length));
+    }
+    Ok(length)
+}
+
 impl<R: Buf> PacketRead for R {
     read_impl!(u8);
     read_impl!(i8);
@@ -121,7 +139,12 @@ impl<R: Buf> PacketRead for R {
     }
 
     fn read_string_limited<const MAX: usize>(&mut self) -> Result<String> {
-        let length = self.read_varint()? as usize;
+        let length = read_length(self)?;
+
+        // A character takes up to 3 bytes
+        if length > MAX.saturating_mul(3) {
+            return Err(DecodeError::Decode("String too long"));
+        }
 
         let mut buf = vec![0u8; length];
         self.copy_to_slice(&mut buf);
@@ -155,7 +178,13 @@ impl<R: Buf> PacketRead for R {
     }
 
     fn read_nbt(&mut self) -> Result<Nbt> {
-        todo!()
+        // Network NBT has no name for the root compound
+        if self.read_u8()? != cerium_nbt::COMPOUND_ID {
+            return Err(DecodeError::Decode("NBT root is not a compound"));
+        }
+        let compound = NbtCompound::deserialize_content(self)
+            .map_err(|_| DecodeError::Decode("Invalid NBT"))?;
+        Ok(Nbt::from(compound))
     }
 
     fn read_position(&mut self) -> Result<BlockPosition> {
@@ -184,8 +213,14 @@ impl<R: Buf> PacketRead for R {
     where
         F: FnMut(&mut Self) -> Result<T>,
     {
-        let length = self.read_varint()? as usize;
-        let mut list = Vec::with_capacity(length);
+        let length = self.read_varint()?;
+        if length < 0 {
+            return Err(DecodeError::Decode("Negative array length"));
+        }
+        let length = length as usize;
+
+        // Every element takes at least one byte
+        let mut list = Vec::with_capacity(length.min(self.remaining()));
         for _ in 0..length {
             list.push(f(self)?);
         }
@@ -204,9 +239,9 @@ impl<R: Buf> PacketRead for R {
     }
 
     fn read_boxed_slice(&mut self) -> Result<Box<[u8]>> {
-        let length = self.read_varint()?;
+        let length = read_length(self)?;
 
-        let mut buf = vec![0u8; length as usize];
+        let mut buf = vec![0u8; length];
         self.copy_to_slice(&mut buf);
         Ok(buf.into())
     }
@@ -7,19 +7,22 @@ use crate::{
     protocol::{
         ProtocolState,
         packet::{
-            AcknowledgeBlockChangePacket, BlockUpdatePacket, ChunkBatchFinishedPacket,
-            ChunkBatchStartPacket, ChunkDataAndUpdateLightPacket, DisconnectPacket,
-            DisplayObjectivePacket, EncryptionRequestPacket, EntityAnimationPacket,
-            EntityPositionPacket, EntityPositionRotationPacket, EntityRotationPacket,
-            FeatureFlagsPacket, FinishConfigPacket, GameEventPacket, LoginDisconnectPacket,
+            AcknowledgeBlockChangePacket, BlockEntityDataPacket, BlockUpdatePacket,
+            BundleDelimiterPacket, ChunkBatchFinishedPacket, ChunkBatchStartPacket,
+            ChunkDataAndUpdateLightPacket, DisconnectPacket, DisplayObjectivePacket,
+            EncryptionRequestPacket, EntityAnimationPacket, EntityPositionPacket,
+            EntityPositionRotationPacket, EntityRotationPacket, FeatureFlagsPacket,
+            FinishConfigPacket, GameEventPacket, InitializeBorderPacket, LoginDisconnectPacket,
             LoginPacket, LoginSuccessPacket, OpenScreenPacket, Packet, PlayerInfoRemovePacket,
             PlayerInfoUpdatePacket, PluginMessagePacket, PongResponsePacket, RegistryDataPacket,
-            RemoveEntitiesPacket, ServerPacket, SetBlockDestroyStagePacket, SetCenterChunkPacket,
+            RemoveEntitiesPacket, RespawnPacket, ServerPacket, SetBlockDestroyStagePacket,
+            SetBorderCenterPacket, SetBorderLerpSizePacket, SetBorderSizePacket,
+            SetBorderWarningDelayPacket, SetBorderWarningDistancePacket, SetCenterChunkPacket,
             SetCompressionPacket, SetContainerContentPacket, SetContainerSlotPacket,
-            SetEntityMetadataPacket, SetHeadRotationPacket, SetTablistHeaderFooterPacket,
-            SpawnEntityPacket, StatusResponsePacket, SyncPlayerPositionPacket,
-            SystemChatMessagePacket, UnloadChunkPacket, UpdateObjectivesPacket, UpdateScorePacket,
-            WorldEventPacket,
+            SetEntityMetadataPacket, SetEquipmentPacket, SetHeadRotationPacket,
+            SetTablistHeaderFooterPacket, SetTimePacket, SpawnEntityPacket, StatusResponsePacket,
+            SyncPlayerPositionPacket, SystemChatMessagePacket, UnloadChunkPacket,
+            UpdateObjectivesPacket, UpdateScorePacket, WorldEventPacket,
             server::{
                 CloseContainerPacket, KeepAlivePacket, KnownPacksPacket, PlayerAbilitiesPacket,
                 SetHeldItemPacket,
@@ -29,7 +32,7 @@ use crate::{
     text::TextComponent,
     util::{BlockPosition, Identifier},
 };
-use cerium_nbt::{Nbt, NbtTag};
+use cerium_nbt::{COMPOUND_ID, Nbt, NbtCompound, NbtTag};
 
 #[derive(Error, Debug)]
 pub enum EncodeError {
@@ -37,6 +40,8 @@ pub enum EncodeError {
     Encode(String),
     #[error("std::io::Error - {0}")]
     IoError(std::io::Error),
+    #[error("NBT error - {0}")]
+    NbtError(cerium_nbt::Error),
 }
 
 pub trait Encode
@@ -77,6 +82,8 @@ pub trait PacketWrite {
 
     fn write_varint(&mut self, value: i32) -> Result<()>;
 
+    fn write_varlong(&mut self, value: i64) -> Result<()>;
+
     fn write_string(&mut self, value: &String) -> Result<()>;
 
     fn write_identifier(&mut self, value: &Identifier) -> Result<()>;
@@ -87,6 +94,8 @@ pub trait PacketWrite {
 
     fn write_nbt_tag(&mut self, value: &NbtTag) -> Result<()>;
 
+    fn write_nbt_compound(&mut self, value: &NbtCompound) -> Result<()>;
+
     fn write_component(&mut self, value: &TextComponent) -> Result<()>;
 
     fn write_position(&mut self, value: &BlockPosition) -> Result<()>;
@@ -101,6 +110,8 @@ pub trait PacketWrite {
 
     fn write_boxed_slice(&mut self, value: &Box<[u8]>) -> Result<()>;
 
+    fn write_bytes(&mut self, value: &[u8]) -> Result<()>;
+
     fn write_unprefixed_array<T, F>(&mut self, value: &Vec<T>, f: F) -> Result<()>
     where
         F: FnMut(&mut Self, &T) -> Result<()>;
@@ -159,6 +170,17 @@ impl<B: BufMut> PacketWrite for B {
         Ok(())
     }
 
+    fn write_varlong(&mut self, value: i64) -> Result<()> {
+        let mut value = value as u64;
+        loop {
+            if value & !0x7F == 0 {
+                return self.write_u8(value as u8);
+            }
+            self.write_u8((value & 0x7F) as u8 | 0x80)?;
+            value >>= 7;
+        }
+    }
+
     fn write_string(&mut self, value: &String) -> Result<()> {
         self.write_varint(value.len() as i32)?;
         self.put(value.as_bytes());
@@ -175,21 +197,32 @@ impl<B: BufMut> PacketWrite for B {
 
     fn write_nbt(&mut self, value: &Nbt) -> Result<()> {
         let mut data: Vec<u8> = Vec::new();
-        value.write_unnamed(&mut data).unwrap();
+        value
+            .write_unnamed(&mut data)
+            .map_err(EncodeError::NbtError)?;
         self.put(&*data);
         Ok(())
     }
 
     fn write_nbt_tag(&mut self, value: &NbtTag) -> Result<()> {
         let mut data: Vec<u8> = Vec::new();
-        value.write(&mut data).unwrap();
+        value.write(&mut data).map_err(EncodeError::NbtError)?;
+        self.put(&*data);
+        Ok(())
+    }
+
+    fn write_nbt_compound(&mut self, value: &NbtCompound) -> Result<()> {
+        let mut data: Vec<u8> = vec![COMPOUND_ID];
+        value
+            .serialize_content(&mut data)
+            .map_err(EncodeError::NbtError)?;
         self.put(&*data);
         Ok(())
     }
 
     fn write_component(&mut self, value: &TextComponent) -> Result<()> {
         let mut data: Vec<u8> = Vec::new();
-        cerium_nbt::to_bytes_unnamed(&value, &mut data).unwrap();
+        cerium_nbt::to_bytes_unnamed(&value, &mut data).map_err(EncodeError::NbtError)?;
         self.put(&*data);
         Ok(())
     }
@@ -232,6 +265,11 @@ impl<B: BufMut> PacketWrite for B {
         Ok(())
     }
 
+    fn write_bytes(&mut self, value: &[u8]) -> Result<()> {
+        self.put_slice(value);
+        Ok(())
+    }
+
     fn write_unprefixed_array<T, F>(&mut self, value: &Vec<T>, mut f: F) -> Result<()>
     where
         F: FnMut(&mut Self, &T) -> Result<()>,
@@ -316,13 +354,13 @@ where
     P: Packet + ServerPacket,
 {
     Some(match () {
-        // _ if type_id == TypeId::of::<BundleDelimiterPacket>() => 0x00,
+        _ if type_id == TypeId::of::<BundleDelimiterPacket>() => 0x00,
         _ if type_id == TypeId::of::<SpawnEntityPacket>() => 0x01,
         _ if type_id == TypeId::of::<EntityAnimationPacket>() => 0x02,
         // _ if type_id == TypeId::of::<AwardStatsPacket>() => 0x03,
         _ if type_id == TypeId::of::<AcknowledgeBlockChangePacket>() => 0x04,
         _ if type_id == TypeId::of::<SetBlockDestroyStagePacket>() => 0x05,
-        // _ if type_id == TypeId::of::<BlockEntityDataPacket>() => 0x06,
+        _ if type_id == TypeId::of::<BlockEntityDataPacket>() => 0x06,
         // _ if type_id == TypeId::of::<BlockEventPacket>() => 0x07,
         _ if type_id == TypeId::of::<BlockUpdatePacket>() => 0x08,
         // _ if type_id == TypeId::of::<BossEventPacket>() => 0x09,
@@ -358,7 +396,7 @@ where
         // _ if type_id == TypeId::of::<GameTestHighlightPosPacket>() => 0x27,
         // _ if type_id == TypeId::of::<HorseScreenOpenPacket>() => 0x28,
         // _ if type_id == TypeId::of::<HurtAnimationPacket>() => 0x29,
-        // _ if type_id == TypeId::of::<InitializeBorderPacket>() => 0x2A,
+        _ if type_id == TypeId::of::<InitializeBorderPacket>() => 0x2A,
         _ if type_id == TypeId::of::<KeepAlivePacket>() => 0x2B,
         _ if type_id == TypeId::of::<ChunkDataAndUpdateLightPacket>() => 0x2C,
         _ if type_id == TypeId::of::<WorldEventPacket>() => 0x2D,
@@ -396,17 +434,17 @@ where
         // _ if type_id == TypeId::of::<ResetScorePacket>() => 0x4D,
         // _ if type_id == TypeId::of::<ResourcePackPopPacket>() => 0x4E,
         // _ if type_id == TypeId::of::<ResourcePackPushPacket>() => 0x4F,
-        // _ if type_id == TypeId::of::<RespawnPacket>() => 0x50,
+        _ if type_id == TypeId::of::<RespawnPacket>() => 0x50,
         _ if type_id == TypeId::of::<SetHeadRotationPacket>() => 0x51,
         // _ if type_id == TypeId::of::<SectionBlocksUpdatePacket>() => 0x52,
         // _ if type_id == TypeId::of::<SelectAdvancementsTabPacket>() => 0x53,
         // _ if type_id == TypeId::of::<ServerDataPacket>() => 0x54,
         // _ if type_id == TypeId::of::<SetActionBarTextPacket>() => 0x55,
-        // _ if type_id == TypeId::of::<SetBorderCenterPacket>() => 0x56,
-        // _ if type_id == TypeId::of::<SetBorderLerpSizePacket>() => 0x57,
-        // _ if type_id == TypeId::of::<SetBorderSizePacket>() => 0x58,
-        // _ if type_id == TypeId::of::<SetBorderWarningDelayPacket>() => 0x59,
-        // _ if type_id == TypeId::of::<SetBorderWarningDistancePacket>() => 0x5A,
+        _ if type_id == TypeId::of::<SetBorderCenterPacket>() => 0x56,
+        _ if type_id == TypeId::of::<SetBorderLerpSizePacket>() => 0x57,
+        _ if type_id == TypeId::of::<SetBorderSizePacket>() => 0x58,
+        _ if type_id == TypeId::of::<SetBorderWarningDelayPacket>() => 0x59,
+        _ if type_id == TypeId::of::<SetBorderWarningDistancePacket>() => 0x5A,
         // _ if type_id == TypeId::of::<SetCameraPacket>() => 0x5B,
         _ if type_id == TypeId::of::<SetCenterChunkPacket>() => 0x5C,
         // _ if type_id == TypeId::of::<SetChunkCacheRadiusPacket>() => 0x5D,
@@ -416,9 +454,8 @@ where
         _ if type_id == TypeId::of::<SetEntityMetadataPacket>() => 0x61,
         // _ if type_id == TypeId::of::<SetEntityLinkPacket>() => 0x62,
         // _ if type_id == TypeId::of::<SetEntityMotionPacket>() => 0x63,
-        // _ if type_id == TypeId::of::<SetEquipmentPacket>() => 0x64,
+        _ if type_id == TypeId::of::<SetEquipmentPacket>() => 0x64,
         // _ if type_id == TypeId::of::<SetExperiencePacket>() => 0x65,
-        // _ if type_id == TypeId::of::<SetHealthPacket>() => 0x66,
         _ if type_id == TypeId::of::<SetHeldItemPacket>() => 0x67,
         _ if type_id == TypeId::of::<UpdateObjectivesPacket>() => 0x68,
         // _ if type_id == TypeId::of::<SetPassengersPacket>() => 0x69,
@@ -427,7 +464,7 @@ where
         _ if type_id == TypeId::of::<UpdateScorePacket>() => 0x6C,
         // _ if type_id == TypeId::of::<SetSimulationDistancePacket>() => 0x6D,
         // _ if type_id == TypeId::of::<SetSubtitleTextPacket>() => 0x6E,
-        // _ if type_id == TypeId::of::<SetTimePacket>() => 0x6F,
+        _ if type_id == TypeId::of::<SetTimePacket>() => 0x6F,
         // _ if type_id == TypeId::of::<SetTitleTextPacket>() => 0x70,
         // _ if type_id == TypeId::of::<SetTitlesAnimationPacket>() => 0x71,
         // _ if type_id == TypeId::of::<SoundEntityPacket>() => 0x72,
@@ -0,0 +1,170 @@
+use std::{io::Write as _, sync::Arc};
+
+use bytes::{BufMut as _, Bytes, BytesMut};
+use flate2::{Compression, write::ZlibEncoder};
+use parking_lot::Mutex;
+
+use crate::protocol::{
+    ProtocolState,
+    encode::{EncodeError, PacketWrite as _, packet_id},
+    packet::{Packet, ServerPacket},
+};
+
+/// A packet waiting in the queue of a connection.
+#[derive(Debug)]
+pub enum SendablePacket {
+    /// A packet encoded for the connection alone.
+    Encoded(BytesMut),
+    /// A packet encoded once and shared with other connections.
+    Framed(Arc<FramedPacket>),
+}
+
+impl From<FramedPacket> for SendablePacket {
+    fn from(packet: FramedPacket) -> Self {
+        SendablePacket::Framed(Arc::new(packet))
+    }
+}
+
+impl From<Arc<FramedPacket>> for SendablePacket {
+    fn from(packet: Arc<FramedPacket>) -> Self {
+        SendablePacket::Framed(packet)
+    }
+}
+
+/// A packet that is encoded once, to send it to many connections or to send it again later.
+///
+/// The frames written to the connections are kept as well, so the packet is compressed once for each
+/// compression threshold and connections only encrypt it.
+#[derive(Debug)]
+pub struct FramedPacket {
+    data: Bytes,
+    frames: Mutex<Vec<(i32, Bytes)>>,
+}
+
+impl FramedPacket {
+    /// Encodes the packet with its id in the protocol state, which has to be the state of the connections
+    /// it is sent to.
+    pub fn new<P>(state: ProtocolState, packet: &P) -> Result<Self, EncodeError>
+    where
+        P: Packet + ServerPacket + 'static,
+    {
+        let Some(packet_id) = packet_id::<P>(&state) else {
+            return Err(EncodeError::Encode(format!(
+                "No id for packet {} in state {:?}",
+                std::any::type_name::<P>(),
+                state
+            )));
+        };
+
+        let mut data = BytesMut::new();
+        data.write_varint(packet_id)?;
+        P::encode(&mut data, packet)?;
+        Ok(Self {
+            data: data.freeze(),
+            frames: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the packet id followed by the encoded packet.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Returns the frame of the packet for a connection with the compression threshold, or `-1` if the
+    /// connection is not compressed.
+    pub fn frame(&self, threshold: i32) -> Result<Bytes, EncodeError> {
+        let mut frames = self.frames.lock();
+        if let Some((_, frame)) = frames.iter().find(|(t, _)| *t == threshold) {
+            return Ok(frame.clone());
+        }
+
+        let frame = encode_frame(&self.data, threshold)?.freeze();
+        frames.push((threshold, frame.clone()));
+        Ok(frame)
+    }
+}
+
+/// Prefixes a packet with its length and compresses it if it reaches the threshold.
+pub(crate) fn encode_frame(data: &[u8], threshold: i32) -> Result<BytesMut, EncodeError> {
+    let data_len = data.len() as i32;
+    let mut frame = BytesMut::with_capacity(data.len() + 10);
+
+    if threshold == -1 {
+        // WITHOUT compression
+        frame.write_varint(data_len)?;
+        frame.put_slice(data);
+    } else if data_len >= threshold {
+        // WITH compression, size >= threshold
+        let mut deflator = ZlibEncoder::new(Vec::new(), Compression::default());
+        deflator.write_all(data).map_err(EncodeError::IoError)?;
+        let compressed = deflator.finish().map_err(EncodeError::IoError)?;
+
+        // len of data_len + compressed_len
+        frame.write_varint(varint_size(data_len) + compressed.len() as i32)?;
+        frame.write_varint(data_len)?;
+        frame.put_slice(&compressed);
+    } else {
+        // WITH compression, size < threshold
+
+        // len of data_len + data_len (because uncompressed), data_len 0 to indicate uncompressed
+        frame.write_varint(1 + data_len)?;
+        frame.write_varint(0)?;
+        frame.put_slice(data);
+    }
+
+    Ok(frame)
+}
+
+#[inline]
+fn varint_size(value: i32) -> i32 {
+    match value {
+        0 => 1,
+        n => ((31 - n.leading_zeros()) / 7 + 1) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use bytes::Buf as _;
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+    use crate::protocol::{decode::PacketRead as _, packet::SetTimePacket};
+
+    #[test]
+    fn test_frame() {
+        let packet = SetTimePacket {
+            world_age: 1,
+            time_of_day: 2,
+            time_of_day_increasing: true,
+        };
+        let framed = FramedPacket::new(ProtocolState::Play, &packet).unwrap();
+
+        let mut frame = framed.frame(-1).unwrap();
+        assert_eq!(frame.read_varint().unwrap() as usize, framed.data().len());
+        assert_eq!(frame, framed.data());
+
+        let mut frame = framed.frame(256).unwrap();
+        assert_eq!(
+            frame.read_varint().unwrap() as usize,
+            framed.data().len() + 1
+        );
+        assert_eq!(frame.read_varint().unwrap(), 0);
+        assert_eq!(frame, framed.data());
+
+        let mut frame = framed.frame(0).unwrap();
+        let _ = frame.read_varint().unwrap();
+        assert_eq!(frame.read_varint().unwrap() as usize, framed.data().len());
+        let mut data = Vec::new();
+        ZlibDecoder::new(frame.reader())
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, framed.data().as_ref());
+
+        // The frame is only compressed once per threshold
+        assert!(framed.frame(0).unwrap().as_ptr() == framed.frame(0).unwrap().as_ptr());
+        assert!(framed.frame(-1).unwrap().as_ptr() == framed.frame(-1).unwrap().as_ptr());
+    }
+}
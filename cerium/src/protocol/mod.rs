@@ -5,8 +5,13 @@ use crate::protocol::{
 
 pub mod packet;
 
+mod bundle;
 pub mod decode;
 pub mod encode;
+pub use bundle::{Bundle, MAX_BUNDLE_SIZE};
+mod frame;
+pub(crate) use frame::encode_frame;
+pub use frame::{FramedPacket, SendablePacket};
 pub mod types;
 
 mod chunk;
@@ -22,15 +27,15 @@ pub enum ProtocolState {
 }
 
 impl ProtocolState {
-    pub fn from_id(id: i32) -> Self {
+    pub fn from_id(id: i32) -> Option<Self> {
         match id {
-            0 => Self::Handshake,
-            1 => Self::Status,
-            2 => Self::Login,
+            0 => Some(Self::Handshake),
+            1 => Some(Self::Status),
+            2 => Some(Self::Login),
             // 3 => Self::Transfer,
-            4 => Self::Config,
-            5 => Self::Play,
-            _ => panic!("protocol with id {} does not exist!", id),
+            4 => Some(Self::Config),
+            5 => Some(Self::Play),
+            _ => None,
         }
     }
 }
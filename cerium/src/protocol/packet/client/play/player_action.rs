@@ -22,9 +22,9 @@ impl Decode for PlayerActionPacket {
     #[rustfmt::skip]
     fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
         Ok(Self {
-            status:   PlayerDiggingState::try_from(r.read_varint()?).unwrap(),
+            status:   PlayerDiggingState::try_from(r.read_varint()?).map_err(|_| DecodeError::Decode("Invalid PlayerDiggingState"))?,
             position: r.read_position()?,
-            face:     BlockFace::try_from(r.read_u8()? as i32).unwrap(),
+            face:     BlockFace::try_from(r.read_u8()? as i32).map_err(|_| DecodeError::Decode("Invalid BlockFace"))?,
             sequence: r.read_varint()?,
         })
     }
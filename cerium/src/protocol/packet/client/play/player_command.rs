@@ -17,7 +17,8 @@ impl Decode for PlayerCommandPacket {
     fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
         Ok(Self {
             entity_id: r.read_varint()?,
-            action_id: PlayerCommand::try_from(r.read_varint()?).unwrap(),
+            action_id: PlayerCommand::try_from(r.read_varint()?)
+                .map_err(|_| DecodeError::Decode("Invalid PlayerCommand"))?,
             jump_boost: r.read_varint()?,
         })
     }
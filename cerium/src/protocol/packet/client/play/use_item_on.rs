@@ -30,7 +30,7 @@ impl Decode for UseItemOnPacket {
         Ok(Self {
             hand:             Hand::decode(r)?,
             position:         r.read_position()?,
-            face:             BlockFace::try_from(r.read_varint()?).unwrap(),
+            face:             BlockFace::try_from(r.read_varint()?).map_err(|_| DecodeError::Decode("Invalid BlockFace"))?,
             cursor_x:         r.read_f32()?,
             cursor_y:         r.read_f32()?,
             cursor_z:         r.read_f32()?,
@@ -170,12 +170,23 @@ pub mod server {
         mod update_teams;
         mod set_tablist_header_footer;
         mod set_block_destroy_stage;
+        mod block_entity_data;
         mod block_update;
         mod world_event;
         mod acknowledge_block_change;
         mod set_held_item;
-
-        pub use login::LoginPacket;
+        mod respawn;
+        mod set_time;
+        mod initialize_border;
+        mod set_border_center;
+        mod set_border_lerp_size;
+        mod set_border_size;
+        mod set_border_warning_delay;
+        mod set_border_warning_distance;
+        mod bundle_delimiter;
+        mod set_equipment;
+
+        pub use login::{DeathLocation, LoginPacket};
         pub use sync_player_position::SyncPlayerPositionPacket;
         pub use game_event::GameEventPacket;
         pub use set_center_chunk::SetCenterChunkPacket;
@@ -207,10 +218,21 @@ pub mod server {
         pub use update_teams::*;
         pub use set_tablist_header_footer::SetTablistHeaderFooterPacket;
         pub use set_block_destroy_stage::SetBlockDestroyStagePacket;
+        pub use block_entity_data::BlockEntityDataPacket;
         pub use block_update::BlockUpdatePacket;
         pub use world_event::WorldEventPacket;
         pub use acknowledge_block_change::AcknowledgeBlockChangePacket;
         pub use set_held_item::SetHeldItemPacket;
+        pub use respawn::RespawnPacket;
+        pub use set_time::SetTimePacket;
+        pub use initialize_border::InitializeBorderPacket;
+        pub use set_border_center::SetBorderCenterPacket;
+        pub use set_border_lerp_size::SetBorderLerpSizePacket;
+        pub use set_border_size::SetBorderSizePacket;
+        pub use set_border_warning_delay::SetBorderWarningDelayPacket;
+        pub use set_border_warning_distance::SetBorderWarningDistancePacket;
+        pub use bundle_delimiter::BundleDelimiterPacket;
+        pub use set_equipment::SetEquipmentPacket;
     }
 
     pub use status::*;
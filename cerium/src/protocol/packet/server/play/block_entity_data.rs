@@ -0,0 +1,28 @@
+use cerium_nbt::NbtCompound;
+
+use crate::{
+    protocol::{
+        encode::{Encode, EncodeError, PacketWrite},
+        packet::{Packet, ServerPacket},
+    },
+    util::BlockPosition,
+};
+
+#[derive(Debug, Clone)]
+pub struct BlockEntityDataPacket {
+    pub position: BlockPosition,
+    pub r#type: i32,
+    pub data: NbtCompound,
+}
+
+impl Packet for BlockEntityDataPacket {}
+impl ServerPacket for BlockEntityDataPacket {}
+
+impl Encode for BlockEntityDataPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_position(&this.position)?;
+        w.write_varint(this.r#type)?;
+        w.write_nbt_compound(&this.data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+/// Starts or ends a bundle of packets, which the client handles within the same tick.
+#[derive(Debug, Clone)]
+pub struct BundleDelimiterPacket {
+    // Empty
+}
+
+impl Packet for BundleDelimiterPacket {}
+impl ServerPacket for BundleDelimiterPacket {}
+
+impl Encode for BundleDelimiterPacket {
+    fn encode<W: PacketWrite>(_w: &mut W, _this: &Self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
@@ -3,13 +3,13 @@ use crate::{
         encode::{Encode, EncodeError, PacketWrite},
         packet::{Packet, ServerPacket},
     },
+    util::ChunkPos,
     world::{BlockEntity, heightmap::Heightmap},
 };
 
 #[derive(Debug, Clone)]
 pub struct ChunkDataAndUpdateLightPacket {
-    pub chunk_x: i32,
-    pub chunk_z: i32,
+    pub chunk: ChunkPos,
     pub data: ChunkData,
     pub light: LightData,
 }
@@ -19,8 +19,8 @@ impl ServerPacket for ChunkDataAndUpdateLightPacket {}
 
 impl Encode for ChunkDataAndUpdateLightPacket {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
-        w.write_i32(this.chunk_x)?;
-        w.write_i32(this.chunk_z)?;
+        w.write_i32(this.chunk.x())?;
+        w.write_i32(this.chunk.z())?;
         ChunkData::encode(w, &this.data)?;
         LightData::encode(w, &this.light)?;
         Ok(())
@@ -43,29 +43,32 @@ impl Encode for ChunkData {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct LightData {}
+/// The light of the sections of a chunk, including the sections below and above the world.
+///
+/// Bit `i` of a mask stands for section `i - 1`. The arrays are sent for the sections set in the sky and block
+/// light masks, in order. Sections set in an empty mask have no light.
+#[derive(Debug, Clone, Default)]
+pub struct LightData {
+    pub sky_light_mask: Vec<u64>,
+    pub block_light_mask: Vec<u64>,
+    pub empty_sky_light_mask: Vec<u64>,
+    pub empty_block_light_mask: Vec<u64>,
+    pub sky_light: Vec<Vec<u8>>,
+    pub block_light: Vec<Vec<u8>>,
+}
 
 impl Encode for LightData {
-    fn encode<W: PacketWrite>(w: &mut W, _this: &Self) -> Result<(), EncodeError> {
-        let num_sections = 26;
-
-        w.write_varint(1)?;
-        w.write_u64(0x3FFFFFF_u64)?;
-
-        w.write_varint(0)?;
-
-        w.write_varint(0)?;
-        w.write_varint(1)?;
-        w.write_u64(0x3FFFFFF_u64)?;
-
-        let light_array = vec![0xFF; 2048];
-        w.write_varint(num_sections as i32)?;
-        for _ in 0..num_sections {
-            w.write_array(&light_array.clone(), |w, v| w.write_u8(*v))?;
-        }
-        w.write_varint(0)?;
-
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_array(&this.sky_light_mask, |w, v| w.write_u64(*v))?;
+        w.write_array(&this.block_light_mask, |w, v| w.write_u64(*v))?;
+        w.write_array(&this.empty_sky_light_mask, |w, v| w.write_u64(*v))?;
+        w.write_array(&this.empty_block_light_mask, |w, v| w.write_u64(*v))?;
+        w.write_array(&this.sky_light, |w, light| {
+            w.write_array(light, |w, v| w.write_u8(*v))
+        })?;
+        w.write_array(&this.block_light, |w, light| {
+            w.write_array(light, |w, v| w.write_u8(*v))
+        })?;
         Ok(())
     }
 }
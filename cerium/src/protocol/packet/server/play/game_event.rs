@@ -10,10 +10,36 @@ pub struct GameEventPacket {
 }
 
 impl GameEventPacket {
+    pub const BEGIN_RAINING: GameEventPacket = GameEventPacket {
+        event: 1,
+        value: 0.,
+    };
+
+    pub const END_RAINING: GameEventPacket = GameEventPacket {
+        event: 2,
+        value: 0.,
+    };
+
     pub const START_WAITING_FOR_CHUNKS: GameEventPacket = GameEventPacket {
         event: 13,
         value: 0.,
     };
+
+    /// Changes the rain level, from `0.0` to `1.0`.
+    pub fn rain_level(level: f32) -> Self {
+        Self {
+            event: 7,
+            value: level,
+        }
+    }
+
+    /// Changes the thunder level, from `0.0` to `1.0`.
+    pub fn thunder_level(level: f32) -> Self {
+        Self {
+            event: 8,
+            value: level,
+        }
+    }
 }
 
 impl Packet for GameEventPacket {}
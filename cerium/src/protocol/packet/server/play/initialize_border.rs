@@ -0,0 +1,34 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct InitializeBorderPacket {
+    pub x: f64,
+    pub z: f64,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// The time in milliseconds until the new diameter is reached.
+    pub speed: i64,
+    pub portal_teleport_boundary: i32,
+    pub warning_blocks: i32,
+    pub warning_time: i32,
+}
+
+impl Packet for InitializeBorderPacket {}
+impl ServerPacket for InitializeBorderPacket {}
+
+impl Encode for InitializeBorderPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_f64(this.x)?;
+        w.write_f64(this.z)?;
+        w.write_f64(this.old_diameter)?;
+        w.write_f64(this.new_diameter)?;
+        w.write_varlong(this.speed)?;
+        w.write_varint(this.portal_teleport_boundary)?;
+        w.write_varint(this.warning_blocks)?;
+        w.write_varint(this.warning_time)?;
+        Ok(())
+    }
+}
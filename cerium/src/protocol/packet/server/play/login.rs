@@ -58,8 +58,8 @@ impl Encode for LoginPacket {
 
 #[derive(Debug, Clone)]
 pub struct DeathLocation {
-    dimension_name: String,
-    location: BlockPosition,
+    pub dimension_name: String,
+    pub location: BlockPosition,
 }
 
 impl Encode for DeathLocation {
@@ -58,6 +58,7 @@ impl Encode for PlayerEntry {
             }
             PlayerAction::UpdateListed { listed } => buffer.write_bool(*listed),
             PlayerAction::UpdateGameMode { game_mode } => buffer.write_varint(*game_mode as i32),
+            PlayerAction::UpdateLatency { ping } => buffer.write_varint(*ping),
             _ => todo!(),
         })?;
         Ok(())
@@ -0,0 +1,47 @@
+use crate::{
+    protocol::{
+        encode::{Encode, EncodeError, PacketWrite},
+        packet::{DeathLocation, Packet, ServerPacket},
+    },
+    util::Identifier,
+};
+
+#[derive(Debug, Clone)]
+pub struct RespawnPacket {
+    pub dimension_type: i32,
+    pub dimension_name: Identifier,
+    pub hashed_seed: i64,
+    pub game_mode: u8,
+    pub previous_game_mode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    pub portal_cooldown: i32,
+    pub sea_level: i32,
+    pub data_kept: u8,
+}
+
+impl RespawnPacket {
+    pub const KEEP_ATTRIBUTES: u8 = 0x01;
+    pub const KEEP_METADATA: u8 = 0x02;
+}
+
+impl Packet for RespawnPacket {}
+impl ServerPacket for RespawnPacket {}
+
+impl Encode for RespawnPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_varint(this.dimension_type)?;
+        w.write_identifier(&this.dimension_name)?;
+        w.write_i64(this.hashed_seed)?;
+        w.write_u8(this.game_mode)?;
+        w.write_u8(this.previous_game_mode as u8)?;
+        w.write_bool(this.is_debug)?;
+        w.write_bool(this.is_flat)?;
+        w.write_option(&this.death_location, DeathLocation::encode)?;
+        w.write_varint(this.portal_cooldown)?;
+        w.write_varint(this.sea_level)?;
+        w.write_u8(this.data_kept)?;
+        Ok(())
+    }
+}
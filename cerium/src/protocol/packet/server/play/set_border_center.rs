@@ -0,0 +1,21 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetBorderCenterPacket {
+    pub x: f64,
+    pub z: f64,
+}
+
+impl Packet for SetBorderCenterPacket {}
+impl ServerPacket for SetBorderCenterPacket {}
+
+impl Encode for SetBorderCenterPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_f64(this.x)?;
+        w.write_f64(this.z)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetBorderLerpSizePacket {
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    /// The time in milliseconds until the new diameter is reached.
+    pub speed: i64,
+}
+
+impl Packet for SetBorderLerpSizePacket {}
+impl ServerPacket for SetBorderLerpSizePacket {}
+
+impl Encode for SetBorderLerpSizePacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_f64(this.old_diameter)?;
+        w.write_f64(this.new_diameter)?;
+        w.write_varlong(this.speed)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetBorderSizePacket {
+    pub diameter: f64,
+}
+
+impl Packet for SetBorderSizePacket {}
+impl ServerPacket for SetBorderSizePacket {}
+
+impl Encode for SetBorderSizePacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_f64(this.diameter)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetBorderWarningDelayPacket {
+    /// The warning time in seconds.
+    pub warning_time: i32,
+}
+
+impl Packet for SetBorderWarningDelayPacket {}
+impl ServerPacket for SetBorderWarningDelayPacket {}
+
+impl Encode for SetBorderWarningDelayPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_varint(this.warning_time)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetBorderWarningDistancePacket {
+    pub warning_blocks: i32,
+}
+
+impl Packet for SetBorderWarningDistancePacket {}
+impl ServerPacket for SetBorderWarningDistancePacket {}
+
+impl Encode for SetBorderWarningDistancePacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_varint(this.warning_blocks)?;
+        Ok(())
+    }
+}
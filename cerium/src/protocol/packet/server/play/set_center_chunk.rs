@@ -1,12 +1,14 @@
-use crate::protocol::{
-    encode::{Encode, EncodeError, PacketWrite},
-    packet::{Packet, ServerPacket},
+use crate::{
+    protocol::{
+        encode::{Encode, EncodeError, PacketWrite},
+        packet::{Packet, ServerPacket},
+    },
+    util::ChunkPos,
 };
 
 #[derive(Debug, Clone)]
 pub struct SetCenterChunkPacket {
-    pub chunk_x: i32,
-    pub chunk_z: i32,
+    pub chunk: ChunkPos,
 }
 
 impl Packet for SetCenterChunkPacket {}
@@ -14,8 +16,8 @@ impl ServerPacket for SetCenterChunkPacket {}
 
 impl Encode for SetCenterChunkPacket {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
-        w.write_varint(this.chunk_x)?;
-        w.write_varint(this.chunk_z)?;
+        w.write_varint(this.chunk.x())?;
+        w.write_varint(this.chunk.z())?;
         Ok(())
     }
 }
@@ -0,0 +1,30 @@
+use crate::{
+    entity::EquipmentSlot,
+    inventory::Slot,
+    protocol::{
+        encode::{Encode, EncodeError, PacketWrite},
+        packet::{Packet, ServerPacket},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct SetEquipmentPacket {
+    pub entity_id: i32,
+    pub equipment: Vec<(EquipmentSlot, Slot)>,
+}
+
+impl Packet for SetEquipmentPacket {}
+impl ServerPacket for SetEquipmentPacket {}
+
+impl Encode for SetEquipmentPacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_varint(this.entity_id)?;
+        for (index, (slot, item)) in this.equipment.iter().enumerate() {
+            // The top bit tells whether another entry follows
+            let more = index + 1 < this.equipment.len();
+            w.write_u8(slot.slot_id() as u8 | if more { 0x80 } else { 0 })?;
+            Slot::encode(w, item)?;
+        }
+        Ok(())
+    }
+}
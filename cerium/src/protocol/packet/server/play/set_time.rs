@@ -0,0 +1,24 @@
+use crate::protocol::{
+    encode::{Encode, EncodeError, PacketWrite},
+    packet::{Packet, ServerPacket},
+};
+
+#[derive(Debug, Clone)]
+pub struct SetTimePacket {
+    pub world_age: i64,
+    pub time_of_day: i64,
+    /// Whether the client advances the time of day by itself between updates.
+    pub time_of_day_increasing: bool,
+}
+
+impl Packet for SetTimePacket {}
+impl ServerPacket for SetTimePacket {}
+
+impl Encode for SetTimePacket {
+    fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
+        w.write_i64(this.world_age)?;
+        w.write_i64(this.time_of_day)?;
+        w.write_bool(this.time_of_day_increasing)?;
+        Ok(())
+    }
+}
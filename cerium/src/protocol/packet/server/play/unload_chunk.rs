@@ -1,6 +1,9 @@
-use crate::protocol::{
-    encode::{Encode, EncodeError, PacketWrite},
-    packet::{Packet, ServerPacket},
+use crate::{
+    protocol::{
+        encode::{Encode, EncodeError, PacketWrite},
+        packet::{Packet, ServerPacket},
+    },
+    util::ChunkPos,
 };
 
 // https://minecraft.wiki/w/Java_Edition_protocol/Packets#Unload_Chunk
@@ -8,8 +11,7 @@ use crate::protocol::{
 //       It is legal to send this packet even if the given chunk is not currently loaded.
 #[derive(Debug, Clone)]
 pub struct UnloadChunkPacket {
-    pub chunk_x: i32,
-    pub chunk_z: i32,
+    pub chunk: ChunkPos,
 }
 
 impl Packet for UnloadChunkPacket {}
@@ -17,8 +19,8 @@ impl ServerPacket for UnloadChunkPacket {}
 
 impl Encode for UnloadChunkPacket {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
-        w.write_i32(this.chunk_z)?;
-        w.write_i32(this.chunk_x)?;
+        w.write_i32(this.chunk.z())?;
+        w.write_i32(this.chunk.x())?;
         Ok(())
     }
 }
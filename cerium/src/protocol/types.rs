@@ -1,6 +1,6 @@
 use crate::auth::Property;
 use crate::inventory::Slot;
-use crate::item::{AnyDataComponent, ComponentMap, DataComponent};
+use crate::item::{AnyDataComponent, ComponentMap, DataComponent, Material};
 use crate::protocol::encode::PacketWrite;
 use crate::protocol::{
     decode::{Decode, DecodeError, PacketRead},
@@ -28,7 +28,9 @@ impl Encode for Property {
 
 impl Decode for AnyDataComponent {
     fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
-        Ok(*DataComponent::from_id(r.read_varint()?).unwrap())
+        DataComponent::from_id(r.read_varint()?)
+            .copied()
+            .ok_or(DecodeError::Decode("Unknown data component"))
     }
 }
 
@@ -43,7 +45,11 @@ impl Decode for Slot {
     fn decode<R: PacketRead>(r: &mut R) -> Result<Self, DecodeError> {
         let item_count = r.read_varint()?;
         let item_id = if item_count > 0 {
-            Some(r.read_varint()?)
+            let item_id = r.read_varint()?;
+            if Material::from_id(item_id).is_none() {
+                return Err(DecodeError::Decode("Unknown item"));
+            }
+            Some(item_id)
         } else {
             None
         };
@@ -80,13 +86,21 @@ impl Encode for Slot {
     fn encode<W: PacketWrite>(w: &mut W, this: &Self) -> Result<(), EncodeError> {
         w.write_varint(this.item_count)?;
         if this.item_count > 0 {
-            w.write_varint(this.item_id.unwrap())?;
+            let Some(item_id) = this.item_id else {
+                return Err(EncodeError::Encode("Slot without an item id".to_string()));
+            };
+            w.write_varint(item_id)?;
 
             w.write_varint(this.to_add.len() as i32)?;
             w.write_varint(this.to_remove.len() as i32)?;
 
             for (id, data) in &this.to_add {
-                let component = DataComponent::from_id(*id).unwrap();
+                let Some(component) = DataComponent::from_id(*id) else {
+                    return Err(EncodeError::Encode(format!(
+                        "Unknown data component {}",
+                        id
+                    )));
+                };
                 w.write_varint(*id)?;
                 component.encode_value(w, data)?;
             }
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// An enchantment of the vanilla data pack. The registry is only used to look up enchantment ids and is not sent
+/// to clients, which know the vanilla enchantments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enchantment {
+    pub max_level: i32,
+}
@@ -16,6 +16,7 @@ mod chicken_variant;
 mod cow_variant;
 mod damage_type;
 mod dimension_type;
+mod enchantment;
 mod frog_variant;
 mod generated;
 mod painting_variant;
@@ -30,6 +31,7 @@ pub use cow_variant::*;
 pub use damage_type::*;
 pub use dimension_type::*;
 pub use dimension_type::*;
+pub use enchantment::*;
 pub use frog_variant::*;
 pub use generated::*;
 pub use painting_variant::*;
@@ -101,6 +103,7 @@ pub struct Registries {
     pub cow_variant: DynamicRegistry<CowVariant>,
     pub damage_type: DynamicRegistry<DamageType>,
     pub dimension_type: DynamicRegistry<DimensionType>,
+    pub enchantment: DynamicRegistry<Enchantment>,
     pub frog_variant: DynamicRegistry<FrogVariant>,
     pub painting_variant: DynamicRegistry<PaintingVariant>,
     pub pig_variant: DynamicRegistry<PigVariant>,
@@ -137,6 +140,10 @@ impl Registries {
                 "minecraft:dimension_type".into(),
                 include_str!("../../data/dimension_type.json").to_owned(),
             ),
+            enchantment: DynamicRegistry::create(
+                "minecraft:enchantment".into(),
+                include_str!("../../data/enchantment.json").to_owned(),
+            ),
             frog_variant: DynamicRegistry::create(
                 "minecraft:frog_variant".into(),
                 include_str!("../../data/frog_variant.json").to_owned(),
@@ -1,59 +1,99 @@
-use parking_lot::Mutex;
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
-};
+use indexmap::IndexMap;
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
 use tokio::{
     net::{TcpListener, ToSocketAddrs},
     runtime::{Handle, Runtime},
+    sync::watch,
+    task::JoinSet,
 };
 
 use crate::{
-    auth::KeyStore, entity::Player, event::Events, network::client::Connection, tickable::Ticker,
+    ServerConfig,
+    auth::{AuthError, KeyStore},
+    entity::Player,
+    event::{Events, ServerShutdownEvent},
+    network::{
+        LoginThrottle,
+        client::{Connection, wait_closed},
+    },
+    protocol::{
+        FramedPacket,
+        packet::{Packet, ServerPacket},
+    },
+    registry::{DimensionType, RegistryKey},
+    text::TextComponent,
+    tickable::Ticker,
+    util::{Identifier, broadcast_packet},
+    world::World,
 };
 
 #[derive(thiserror::Error, Debug)]
 pub enum ServerError {
     #[error("std::io::Error: {0}")]
     IoError(std::io::Error),
+    #[error("Failed to create the key store: {0}")]
+    KeyStoreError(AuthError),
 }
 
 pub struct Server {
     runtime: tokio::runtime::Runtime,
     handle: tokio::runtime::Handle,
-    closed: AtomicBool,
+    closed: watch::Sender<bool>,
+    shutdown_reason: Mutex<Option<TextComponent>>,
     pub(crate) players: Arc<Mutex<Vec<Player>>>,
+    pub(crate) connections: Mutex<Vec<Arc<Connection>>>,
+    pub(crate) login_throttle: LoginThrottle,
+    worlds: RwLock<IndexMap<Identifier, World>>,
     key_store: Arc<KeyStore>,
     events: Events,
+    config: ServerConfig,
 }
 
 impl Server {
-    pub fn new() -> Self {
+    pub fn new(config: ServerConfig) -> Result<Self, ServerError> {
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .build()
-            .expect("todo");
+            .map_err(ServerError::IoError)?;
 
         let handle = runtime.handle().clone();
+        let key_store =
+            KeyStore::new(config.network.rsa_key_bits).map_err(ServerError::KeyStoreError)?;
 
-        Self {
+        Ok(Self {
             runtime: runtime,
             handle,
-            closed: AtomicBool::new(false),
+            closed: watch::Sender::new(false),
+            shutdown_reason: Mutex::new(None),
             players: Arc::new(Mutex::new(Vec::new())),
-            key_store: Arc::new(KeyStore::new()),
+            connections: Mutex::new(Vec::new()),
+            login_throttle: LoginThrottle::new(),
+            worlds: RwLock::new(IndexMap::new()),
+            key_store: Arc::new(key_store),
             events: Events::new(),
-        }
+            config,
+        })
+    }
+
+    /// Binds the server to the address and port of its config, see [`Server::bind`].
+    pub fn run(self) -> Result<(), ServerError> {
+        let addr = self.config.network.socket_addr();
+        self.bind(addr)
     }
 
     /// Binds the server to the specified address.
     ///
     /// The address type can be any implementor of the [ToSocketAddrs] trait.
     ///
+    /// This blocks until the server is shut down, either by [`Server::shutdown`] or by SIGINT/SIGTERM.
+    ///
     /// # Example
-    /// ```
+    /// ```no_run
+    /// use cerium::{Server, ServerConfig};
+    ///
     /// fn main() {
-    ///     let server = Server::new();
+    ///     let server = Server::new(ServerConfig::default()).unwrap();
     ///     server.bind("127.0.0.1:25565").unwrap();
     /// }
     /// ```
@@ -62,10 +102,13 @@ impl Server {
         A: ToSocketAddrs,
     {
         let handle = self.handle.clone();
-        handle.block_on(self.bind0(addr))
+
+        // The server owns the runtime, so it must be dropped outside of it
+        let this = Arc::new(self);
+        handle.block_on(this.clone().bind0(addr))
     }
 
-    async fn bind0<A>(self, addr: A) -> Result<(), ServerError>
+    async fn bind0<A>(self: Arc<Self>, addr: A) -> Result<(), ServerError>
     where
         A: ToSocketAddrs,
     {
@@ -81,10 +124,10 @@ impl Server {
             listener.local_addr().map_err(ServerError::IoError)?
         );
 
-        let this = Arc::new(self);
+        let this = self;
 
         // Tick Task
-        this.handle.spawn({
+        let mut tick_task = this.handle.spawn({
             let this = this.clone();
             let mut ticker = Ticker::new(this.clone());
 
@@ -95,18 +138,79 @@ impl Server {
             }
         });
 
-        while !this.closed() {
-            let (stream, addr) = listener.accept().await.map_err(ServerError::IoError)?;
+        // Signal Task
+        let signal_task = this.handle.spawn({
+            let this = this.clone();
+            async move {
+                wait_for_signal().await;
+                log::info!("Received shutdown signal");
+                this.close();
+            }
+        });
+
+        let mut connections = JoinSet::new();
+        let mut closed = this.closed.subscribe();
+        let result = loop {
+            tokio::select! {
+                result = listener.accept() => {
+                    let (stream, addr) = match result {
+                        Ok(v) => v,
+                        Err(e) => break Err(ServerError::IoError(e)),
+                    };
 
-            this.handle.spawn({
-                let this = this.clone();
-                async move {
-                    Connection::accept(addr, stream, this.clone()).await;
+                    connections.spawn_on(
+                        Connection::accept(addr, stream, this.clone()),
+                        &this.handle,
+                    );
                 }
-            });
+                // Reap finished connections
+                Some(_) = connections.join_next() => {}
+                _ = wait_closed(&mut closed) => break Ok(()),
+            }
+        };
+
+        // Stop accepting new connections
+        drop(listener);
+        signal_task.abort();
+        let _ = signal_task.await;
+        this.close();
+
+        let reason = this
+            .shutdown_reason
+            .lock()
+            .take()
+            .unwrap_or_else(|| TextComponent::from("Server closed"));
+
+        let online_connections = this.connections.lock().clone();
+        for connection in online_connections {
+            connection.kick(reason.clone());
         }
 
-        Ok(())
+        this.events.fire(&mut ServerShutdownEvent::new(reason));
+
+        let timeout = this.config.network.shutdown_timeout;
+        let drained = tokio::time::timeout(timeout, async {
+            while connections.join_next().await.is_some() {}
+            let _ = (&mut tick_task).await;
+        })
+        .await;
+
+        if drained.is_err() {
+            log::warn!("Connections did not close within {:?}", timeout);
+            // Aborting the connections also stops their read and write loops, so no task keeps the
+            // server alive once this returns
+            connections.abort_all();
+            while connections.join_next().await.is_some() {}
+            tick_task.abort();
+            let _ = tick_task.await;
+
+            let remaining = this.connections.lock().clone();
+            for connection in remaining {
+                connection.remove_from_server();
+            }
+        }
+
+        result
     }
 
     pub fn runtime(&self) -> &Runtime {
@@ -118,11 +222,22 @@ impl Server {
     }
 
     pub fn closed(&self) -> bool {
-        self.closed.load(Ordering::Relaxed)
+        *self.closed.borrow()
     }
 
+    /// Shuts the server down with the default reason.
     pub fn close(&self) {
-        self.closed.store(true, Ordering::Release);
+        self.closed.send_replace(true);
+    }
+
+    /// Shuts the server down.
+    ///
+    /// The server stops accepting connections, kicks all players with the given reason and
+    /// fires a [`ServerShutdownEvent`]. [`Server::bind`] returns once all connections are
+    /// closed or the configured shutdown timeout has passed.
+    pub fn shutdown(&self, reason: impl Into<TextComponent>) {
+        self.shutdown_reason.lock().get_or_insert(reason.into());
+        self.close();
     }
 
     pub fn key_store(&self) -> Arc<KeyStore> {
@@ -132,4 +247,174 @@ impl Server {
     pub fn events(&self) -> &Events {
         &self.events
     }
+
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Sends a packet to all players on the server, encoding it only once.
+    pub fn broadcast_packet<P>(&self, packet: &P)
+    where
+        P: Packet + ServerPacket + 'static,
+    {
+        broadcast_packet(self.players.lock().iter(), packet);
+    }
+
+    pub fn broadcast_framed_packet(&self, packet: &Arc<FramedPacket>) {
+        for player in self.players.lock().iter() {
+            player.send_framed_packet(packet);
+        }
+    }
+
+    // ===== Worlds ======
+
+    /// Creates a new world and registers it under the given name.
+    pub fn create_world(
+        &self,
+        name: impl Into<Identifier>,
+        dimension: &RegistryKey<DimensionType>,
+    ) -> World {
+        let world = World::with_name(name.into(), dimension);
+        self.register_world(world.clone());
+        world
+    }
+
+    /// Registers a world under its name, replacing and returning a world with the same name.
+    ///
+    /// Registered worlds are ticked even if no player is in them.
+    pub fn register_world(&self, world: World) -> Option<World> {
+        self.worlds.write().insert(world.name().clone(), world)
+    }
+
+    /// Unregisters the world with the given name.
+    pub fn unregister_world(&self, name: &Identifier) -> Option<World> {
+        self.worlds.write().shift_remove(name)
+    }
+
+    /// Returns the registered world with the given name.
+    pub fn world(&self, name: &Identifier) -> Option<World> {
+        self.worlds.read().get(name).cloned()
+    }
+
+    /// Returns all registered worlds.
+    pub fn worlds(&self) -> Vec<World> {
+        self.worlds.read().values().cloned().collect()
+    }
+}
+
+/// Waits for SIGINT or SIGTERM.
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                return;
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::protocol::SendablePacket;
+
+    #[test]
+    fn test_worlds() {
+        let server = Server::new(ServerConfig::default()).unwrap();
+        let dimension = DimensionType::OVERWORLD;
+
+        let lobby = server.create_world("lobby", &dimension);
+        let arena = server.create_world(Identifier::new("game", "arena"), &dimension);
+
+        assert_eq!(lobby.name(), &Identifier::vanilla("lobby"));
+        assert!(server.world(&Identifier::of("game:arena")) == Some(arena.clone()));
+        assert_eq!(server.worlds().len(), 2);
+
+        let replaced = server.register_world(World::with_name(Identifier::of("lobby"), &dimension));
+        assert!(replaced == Some(lobby));
+        assert!(server.unregister_world(arena.name()) == Some(arena.clone()));
+        assert_eq!(server.worlds().len(), 1);
+
+        // Forks get their own name, so they are registered next to the world they were forked from
+        server.register_world(arena.clone());
+        let fork = arena.fork(Identifier::new("game", "arena_1"));
+        assert!(server.register_world(fork.clone()).is_none());
+        assert!(server.world(arena.name()) == Some(arena));
+        assert!(server.world(&Identifier::of("game:arena_1")) == Some(fork));
+        assert_eq!(server.worlds().len(), 3);
+    }
+
+    #[test]
+    fn test_shutdown_timeout() {
+        let config = ServerConfig::builder()
+            .shutdown_timeout(Duration::from_millis(100))
+            .build();
+        let server = Arc::new(Server::new(config).unwrap());
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        // A client that never reads, so the connection is stuck writing to it
+        let client = std::thread::spawn({
+            let server = server.clone();
+            move || {
+                let stream = loop {
+                    if let Ok(stream) = std::net::TcpStream::connect(addr) {
+                        break stream;
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                };
+                let connection = loop {
+                    if let Some(connection) = server.connections.lock().first() {
+                        break connection.clone();
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                };
+                for _ in 0..64 {
+                    let data = BytesMut::zeroed(1 << 20);
+                    connection.send_framed_packet(SendablePacket::Encoded(data));
+                }
+                drop(connection);
+                std::thread::sleep(Duration::from_millis(100));
+                server.shutdown("Closed");
+                stream
+            }
+        });
+
+        let handle = server.handle().clone();
+        handle.block_on(server.clone().bind0(addr)).unwrap();
+        let _stream = client.join().unwrap();
+
+        // No task of the connection holds on to the server anymore
+        assert!(server.connections.lock().is_empty());
+        assert_eq!(Arc::strong_count(&server), 1);
+    }
+
+    #[test]
+    fn test_invalid_key_size() {
+        let config = ServerConfig::builder().rsa_key_bits(16).build();
+        assert!(matches!(
+            Server::new(config),
+            Err(ServerError::KeyStoreError(AuthError::InvalidKeySize(16)))
+        ));
+    }
 }
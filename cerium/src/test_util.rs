@@ -0,0 +1,96 @@
+//! Helpers for tests that need a server or connected clients.
+
+use std::{io::Cursor, sync::Arc};
+
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc::Receiver,
+};
+use uuid::Uuid;
+
+use crate::{
+    Server, ServerConfig,
+    auth::GameProfile,
+    entity::Player,
+    network::client::Connection,
+    protocol::{ProtocolState, SendablePacket, decode::PacketRead as _},
+    registry::DimensionType,
+    util::Position,
+    world::World,
+};
+
+/// Runs the test on the runtime of a new server with the default config.
+pub(crate) fn run_with_server(test: impl AsyncFnOnce(Arc<Server>)) {
+    let server = Arc::new(Server::new(ServerConfig::default()).unwrap());
+    let handle = server.handle().clone();
+    // The runtime is dropped with the server, which must not happen inside of it
+    handle.block_on(test(server.clone()));
+}
+
+/// Creates a connection in the given state together with the packets it sends and the client side of the socket.
+///
+/// Connections in the play state get a player at (0.5, 64, 0.5) in a new overworld world.
+pub(crate) async fn connect(
+    server: &Arc<Server>,
+    state: ProtocolState,
+) -> (Arc<Connection>, Receiver<SendablePacket>, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (stream, addr) = listener.accept().await.unwrap();
+
+    let (conn, rx) = Connection::new(addr, stream, server.clone());
+
+    conn.set_state(state);
+    if state == ProtocolState::Play {
+        *conn.game_profile.lock() = Some(GameProfile {
+            uuid: Uuid::new_v4(),
+            name: "Test".to_string(),
+            properties: vec![],
+        });
+        let player = Player::new(conn.clone(), server.clone());
+        let dimension = DimensionType::OVERWORLD;
+        player.0.update_world(World::new(&dimension));
+        player
+            .0
+            .set_position(Position::new(0.5, 64.0, 0.5, 0.0, 0.0));
+        *conn.player.lock() = Some(player);
+    }
+    (conn, rx, client)
+}
+
+/// A player connected in the play state, see [`connect`].
+pub(crate) struct TestPlayer {
+    pub player: Player,
+    pub conn: Arc<Connection>,
+    /// The packets sent to the player.
+    pub rx: Receiver<SendablePacket>,
+    /// The client side of the socket, which has to stay open while the player is used.
+    _client: TcpStream,
+}
+
+pub(crate) async fn connect_player(server: &Arc<Server>) -> TestPlayer {
+    let (conn, rx, client) = connect(server, ProtocolState::Play).await;
+    let player = conn.player.lock().clone().unwrap();
+    TestPlayer {
+        player,
+        conn,
+        rx,
+        _client: client,
+    }
+}
+
+/// Returns the data of the next packet with the id the connection sent, skipping any other packets.
+pub(crate) fn next_packet(rx: &mut Receiver<SendablePacket>, id: i32) -> Vec<u8> {
+    loop {
+        let Ok(SendablePacket::Encoded(data)) = rx.try_recv() else {
+            panic!("Expected a packet with the id {id}");
+        };
+        let mut data = Cursor::new(&data[..]);
+        if data.read_varint().unwrap() == id {
+            let position = data.position() as usize;
+            return data.into_inner()[position..].to_vec();
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::{sync::Arc, time::Duration};
 use tokio::time::{Interval, interval};
 
-use crate::Server;
+use crate::{Server, entity::EntityLike as _, protocol::ProtocolState};
 
 pub trait Tickable {
     fn tick(&self);
@@ -25,8 +25,26 @@ impl Ticker {
 
         let server = Arc::clone(&self.server);
 
+        // Players tick their keep alives themselves, but clients still in the configuration have no player
+        let connections = server.connections.lock().clone();
+        for connection in connections {
+            if connection.state() == ProtocolState::Config {
+                connection.tick_keep_alive();
+            }
+        }
+
+        let mut worlds = server.worlds();
         for player in &*server.players.lock() {
             player.tick();
+
+            let world = player.world();
+            if !worlds.contains(&world) {
+                worlds.push(world);
+            }
+        }
+
+        for world in worlds {
+            world.tick();
         }
     }
 }
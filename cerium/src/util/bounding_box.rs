@@ -0,0 +1,361 @@
+use crate::{util::Position, world::BlockFace};
+
+/// An axis-aligned box, like the hitbox of an entity or a part of the shape of a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    min_x: f64,
+    min_y: f64,
+    min_z: f64,
+    max_x: f64,
+    max_y: f64,
+    max_z: f64,
+}
+
+impl BoundingBox {
+    /// The box filling a whole block.
+    pub const BLOCK: BoundingBox = BoundingBox::new(0., 0., 0., 1., 1., 1.);
+
+    pub const fn new(
+        min_x: f64,
+        min_y: f64,
+        min_z: f64,
+        max_x: f64,
+        max_y: f64,
+        max_z: f64,
+    ) -> Self {
+        Self {
+            min_x,
+            min_y,
+            min_z,
+            max_x,
+            max_y,
+            max_z,
+        }
+    }
+
+    /// Creates a box of the width and height centered above the feet of an entity.
+    pub fn at_feet(position: Position, width: f64, height: f64) -> Self {
+        let half = width / 2.;
+        Self::new(
+            position.x() - half,
+            position.y(),
+            position.z() - half,
+            position.x() + half,
+            position.y() + height,
+            position.z() + half,
+        )
+    }
+
+    pub fn min_x(&self) -> f64 {
+        self.min_x
+    }
+
+    pub fn min_y(&self) -> f64 {
+        self.min_y
+    }
+
+    pub fn min_z(&self) -> f64 {
+        self.min_z
+    }
+
+    pub fn max_x(&self) -> f64 {
+        self.max_x
+    }
+
+    pub fn max_y(&self) -> f64 {
+        self.max_y
+    }
+
+    pub fn max_z(&self) -> f64 {
+        self.max_z
+    }
+
+    pub fn offset(&self, x: f64, y: f64, z: f64) -> Self {
+        Self::new(
+            self.min_x + x,
+            self.min_y + y,
+            self.min_z + z,
+            self.max_x + x,
+            self.max_y + y,
+            self.max_z + z,
+        )
+    }
+
+    /// Returns the box grown by the amount on every side, or shrunk if it is negative.
+    pub fn grow(&self, x: f64, y: f64, z: f64) -> Self {
+        Self::new(
+            self.min_x - x,
+            self.min_y - y,
+            self.min_z - z,
+            self.max_x + x,
+            self.max_y + y,
+            self.max_z + z,
+        )
+    }
+
+    /// Returns the box stretched to cover all of the space it passes while moving by the motion.
+    pub fn expand_towards(&self, x: f64, y: f64, z: f64) -> Self {
+        Self::new(
+            self.min_x + x.min(0.),
+            self.min_y + y.min(0.),
+            self.min_z + z.min(0.),
+            self.max_x + x.max(0.),
+            self.max_y + y.max(0.),
+            self.max_z + z.max(0.),
+        )
+    }
+
+    /// Returns whether the boxes overlap. Boxes only touching each other do not.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x < other.max_x
+            && self.max_x > other.min_x
+            && self.min_y < other.max_y
+            && self.max_y > other.min_y
+            && self.min_z < other.max_z
+            && self.max_z > other.min_z
+    }
+
+    pub fn contains(&self, x: f64, y: f64, z: f64) -> bool {
+        x >= self.min_x
+            && x < self.max_x
+            && y >= self.min_y
+            && y < self.max_y
+            && z >= self.min_z
+            && z < self.max_z
+    }
+
+    /// Returns the distance along a ray at which it enters the box, and the face it enters through.
+    ///
+    /// The direction must be a unit vector. A ray starting inside of the box hits it right away, on the face
+    /// facing against the direction the ray is mostly going in.
+    pub fn intersect_ray(
+        &self,
+        origin: (f64, f64, f64),
+        direction: (f64, f64, f64),
+    ) -> Option<(f64, BlockFace)> {
+        let axes = [
+            (
+                origin.0,
+                direction.0,
+                self.min_x,
+                self.max_x,
+                BlockFace::West,
+                BlockFace::East,
+            ),
+            (
+                origin.1,
+                direction.1,
+                self.min_y,
+                self.max_y,
+                BlockFace::Bottom,
+                BlockFace::Top,
+            ),
+            (
+                origin.2,
+                direction.2,
+                self.min_z,
+                self.max_z,
+                BlockFace::North,
+                BlockFace::South,
+            ),
+        ];
+
+        let (mut near, mut far) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut face = None;
+        // Going along the axis, the ray enters through the face at the minimum
+        for (origin, direction, min, max, min_face, max_face) in axes {
+            if direction == 0. {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let (t1, t2) = ((min - origin) / direction, (max - origin) / direction);
+            let entered = if direction > 0. { min_face } else { max_face };
+            if t1.min(t2) > near {
+                near = t1.min(t2);
+                face = Some(entered);
+            }
+            far = far.min(t1.max(t2));
+        }
+
+        if near > far || far < 0. {
+            return None;
+        }
+        if near < 0. {
+            return Some((0., facing_against(direction)));
+        }
+        face.map(|face| (near, face))
+    }
+
+    /// Returns how far along the motion the box can move until it runs into the obstacle, as a fraction from
+    /// 0 to 1, and the face of the obstacle it runs into.
+    ///
+    /// An obstacle the box already overlaps does not stop it, so entities stuck inside of a block can move
+    /// out of it.
+    pub fn sweep(
+        &self,
+        motion: (f64, f64, f64),
+        obstacle: &BoundingBox,
+    ) -> Option<(f64, BlockFace)> {
+        if self.intersects(obstacle) {
+            return None;
+        }
+
+        let axes = [
+            (
+                motion.0,
+                (self.min_x, self.max_x),
+                (obstacle.min_x, obstacle.max_x),
+                BlockFace::West,
+                BlockFace::East,
+            ),
+            (
+                motion.1,
+                (self.min_y, self.max_y),
+                (obstacle.min_y, obstacle.max_y),
+                BlockFace::Bottom,
+                BlockFace::Top,
+            ),
+            (
+                motion.2,
+                (self.min_z, self.max_z),
+                (obstacle.min_z, obstacle.max_z),
+                BlockFace::North,
+                BlockFace::South,
+            ),
+        ];
+
+        let (mut enter, mut exit) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut face = None;
+        for (motion, (min, max), (other_min, other_max), min_face, max_face) in axes {
+            if motion == 0. {
+                if max <= other_min || min >= other_max {
+                    return None;
+                }
+                continue;
+            }
+
+            let (t_enter, t_exit, entered) = if motion > 0. {
+                (
+                    (other_min - max) / motion,
+                    (other_max - min) / motion,
+                    min_face,
+                )
+            } else {
+                (
+                    (other_max - min) / motion,
+                    (other_min - max) / motion,
+                    max_face,
+                )
+            };
+            if t_enter > enter {
+                enter = t_enter;
+                face = Some(entered);
+            }
+            exit = exit.min(t_exit);
+        }
+
+        if enter >= exit || !(0. ..=1.).contains(&enter) {
+            return None;
+        }
+        face.map(|face| (enter, face))
+    }
+}
+
+/// Returns the face a ray going in the direction hits first.
+fn facing_against(direction: (f64, f64, f64)) -> BlockFace {
+    let (x, y, z) = (direction.0.abs(), direction.1.abs(), direction.2.abs());
+    if x >= y && x >= z {
+        if direction.0 > 0. {
+            BlockFace::West
+        } else {
+            BlockFace::East
+        }
+    } else if y >= z {
+        if direction.1 > 0. {
+            BlockFace::Bottom
+        } else {
+            BlockFace::Top
+        }
+    } else if direction.2 > 0. {
+        BlockFace::North
+    } else {
+        BlockFace::South
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersects() {
+        let block = BoundingBox::BLOCK.offset(1., 0., 0.);
+        assert!(block.intersects(&BoundingBox::new(0.5, 0.5, 0.5, 1.5, 1.5, 1.5)));
+        // Touching is not overlapping
+        assert!(!block.intersects(&BoundingBox::BLOCK));
+        assert!(!block.intersects(&BoundingBox::BLOCK.offset(1., 1., 0.)));
+
+        let moved = BoundingBox::BLOCK.expand_towards(-2., 0., 0.5);
+        assert_eq!(moved, BoundingBox::new(-2., 0., 0., 1., 1., 1.5));
+        assert_eq!(
+            BoundingBox::BLOCK.grow(0.5, 0., 0.),
+            BoundingBox::new(-0.5, 0., 0., 1.5, 1., 1.)
+        );
+    }
+
+    #[test]
+    fn test_sweep() {
+        let player = BoundingBox::new(-0.3, 1., -0.3, 0.3, 2.8, 0.3);
+        let wall = BoundingBox::BLOCK.offset(2., 1., -0.5);
+        let floor = BoundingBox::BLOCK.offset(0., 0., -0.5);
+
+        let (fraction, face) = player.sweep((4., 0., 0.), &wall).unwrap();
+        assert_eq!(face, BlockFace::West);
+        assert!((fraction - 1.7 / 4.).abs() < 1e-9);
+
+        // Too short, moving away or sliding along the floor the player stands on
+        assert_eq!(player.sweep((1., 0., 0.), &wall), None);
+        assert_eq!(player.sweep((-4., 0., 0.), &wall), None);
+        assert_eq!(player.sweep((4., 0., 0.), &floor), None);
+
+        assert_eq!(
+            player.sweep((0., -1., 0.), &floor),
+            Some((0., BlockFace::Top))
+        );
+        assert_eq!(
+            player.offset(0., 2., 0.).sweep((0., -4., 0.), &floor),
+            Some((0.5, BlockFace::Top))
+        );
+
+        // Already inside of the obstacle
+        assert_eq!(player.sweep((1., 0., 0.), &player), None);
+    }
+
+    #[test]
+    fn test_intersect_ray() {
+        let block = BoundingBox::BLOCK.offset(2., 0., 0.);
+
+        let hit = block.intersect_ray((0., 0.5, 0.5), (1., 0., 0.));
+        assert_eq!(hit, Some((2., BlockFace::West)));
+
+        let hit = block.intersect_ray((2.5, 3., 0.5), (0., -1., 0.));
+        assert_eq!(hit, Some((2., BlockFace::Top)));
+
+        // Behind the ray or next to it
+        assert_eq!(block.intersect_ray((4., 0.5, 0.5), (1., 0., 0.)), None);
+        assert_eq!(block.intersect_ray((0., 1.5, 0.5), (1., 0., 0.)), None);
+
+        let hit = block.intersect_ray((2.5, 0.5, 0.5), (0., 0., -1.));
+        assert_eq!(hit, Some((0., BlockFace::South)));
+
+        let diagonal = std::f64::consts::FRAC_1_SQRT_2;
+        let (distance, face) = block
+            .intersect_ray((1., 0.5, -0.5), (diagonal, 0., diagonal))
+            .unwrap();
+        assert_eq!(face, BlockFace::West);
+        assert!((distance - std::f64::consts::SQRT_2).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::util::{BlockPosition, Position};
+
+/// The position of a chunk column.
+///
+/// Block coordinates are converted with floor division, so block `-1` lies in chunk `-1` and not in chunk `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ChunkPos {
+    x: i32,
+    z: i32,
+}
+
+impl ChunkPos {
+    pub const ZERO: ChunkPos = ChunkPos::new(0, 0);
+
+    pub const fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+
+    /// Returns the chunk containing the given block coordinates.
+    pub const fn from_block(x: i32, z: i32) -> Self {
+        Self::new(x >> 4, z >> 4)
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Returns the lowest block x coordinate inside the chunk.
+    pub fn min_block_x(&self) -> i32 {
+        self.x << 4
+    }
+
+    /// Returns the lowest block z coordinate inside the chunk.
+    pub fn min_block_z(&self) -> i32 {
+        self.z << 4
+    }
+
+    /// Returns the chessboard distance to another chunk, as used for view distances.
+    pub fn distance(&self, other: ChunkPos) -> i32 {
+        (self.x - other.x).abs().max((self.z - other.z).abs())
+    }
+}
+
+impl From<BlockPosition> for ChunkPos {
+    fn from(position: BlockPosition) -> Self {
+        Self::new((position.x() >> 4) as i32, (position.z() >> 4) as i32)
+    }
+}
+
+impl From<Position> for ChunkPos {
+    fn from(position: Position) -> Self {
+        BlockPosition::from(position).into()
+    }
+}
+
+impl From<(i32, i32)> for ChunkPos {
+    fn from((x, z): (i32, i32)) -> Self {
+        Self::new(x, z)
+    }
+}
+
+/// The position of a 16x16x16 chunk section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SectionPos {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl SectionPos {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the section containing the given block coordinates.
+    pub const fn from_block(x: i32, y: i32, z: i32) -> Self {
+        Self::new(x >> 4, y >> 4, z >> 4)
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Returns the chunk column the section belongs to.
+    pub fn chunk(&self) -> ChunkPos {
+        ChunkPos::new(self.x, self.z)
+    }
+
+    /// Returns the index of the section inside its chunk column, or `None` if it lies below `min_y`.
+    pub fn index(&self, min_y: i32) -> Option<usize> {
+        usize::try_from(self.y - (min_y >> 4)).ok()
+    }
+}
+
+impl From<BlockPosition> for SectionPos {
+    fn from(position: BlockPosition) -> Self {
+        Self::new(
+            (position.x() >> 4) as i32,
+            (position.y() >> 4) as i32,
+            (position.z() >> 4) as i32,
+        )
+    }
+}
+
+/// A block position relative to its chunk section, with every coordinate in `0..16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LocalPos {
+    x: u8,
+    y: u8,
+    z: u8,
+}
+
+impl LocalPos {
+    /// Returns the position of the given block coordinates inside their section.
+    pub const fn from_block(x: i32, y: i32, z: i32) -> Self {
+        Self {
+            x: (x & 0xF) as u8,
+            y: (y & 0xF) as u8,
+            z: (z & 0xF) as u8,
+        }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x as usize
+    }
+
+    pub fn y(&self) -> usize {
+        self.y as usize
+    }
+
+    pub fn z(&self) -> usize {
+        self.z as usize
+    }
+}
+
+impl From<BlockPosition> for LocalPos {
+    fn from(position: BlockPosition) -> Self {
+        Self::from_block(
+            position.x() as i32,
+            position.y() as i32,
+            position.z() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_negative_coordinates() {
+        assert_eq!(ChunkPos::from_block(-1, -1), ChunkPos::new(-1, -1));
+        assert_eq!(ChunkPos::from_block(-16, 15), ChunkPos::new(-1, 0));
+        assert_eq!(ChunkPos::from_block(-17, 16), ChunkPos::new(-2, 1));
+        assert_eq!(
+            ChunkPos::from(Position::new(-0.5, 64., -15.9, 0., 0.)),
+            ChunkPos::new(-1, -1)
+        );
+        assert_eq!(
+            LocalPos::from_block(-1, -64, -16),
+            LocalPos::from_block(15, 0, 0)
+        );
+        assert_eq!(SectionPos::from_block(0, -64, 0).index(-64), Some(0));
+        assert_eq!(SectionPos::from_block(0, -65, 0).index(-64), None);
+    }
+
+    proptest! {
+        #[test]
+        fn chunk_and_local_round_trip(x in -30_000_000..30_000_000i32, y in -2048..2048i32, z in -30_000_000..30_000_000i32) {
+            let chunk = ChunkPos::from_block(x, z);
+            let section = SectionPos::from_block(x, y, z);
+            let local = LocalPos::from_block(x, y, z);
+
+            prop_assert_eq!(chunk, section.chunk());
+            prop_assert_eq!(chunk.min_block_x() + local.x() as i32, x);
+            prop_assert_eq!(chunk.min_block_z() + local.z() as i32, z);
+            prop_assert_eq!((section.y() << 4) + local.y() as i32, y);
+        }
+
+        #[test]
+        fn position_matches_block(x in -1e7..1e7f64, y in -2048.0..2048.0f64, z in -1e7..1e7f64) {
+            let block = BlockPosition::from(Position::new(x, y, z, 0., 0.));
+
+            prop_assert_eq!(block.x(), x.floor() as i64);
+            prop_assert_eq!(block.y(), y.floor() as i64);
+            prop_assert_eq!(block.z(), z.floor() as i64);
+            prop_assert_eq!(ChunkPos::from(block), ChunkPos::from_block(block.x() as i32, block.z() as i32));
+        }
+    }
+}
@@ -26,6 +26,30 @@ pub enum DyeColor {
     Black,
 }
 
+impl DyeColor {
+    /// Returns the name of the color, e.g. `light_blue`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DyeColor::White => "white",
+            DyeColor::Orange => "orange",
+            DyeColor::Magenta => "magenta",
+            DyeColor::LightBlue => "light_blue",
+            DyeColor::Yellow => "yellow",
+            DyeColor::Lime => "lime",
+            DyeColor::Pink => "pink",
+            DyeColor::Gray => "gray",
+            DyeColor::LightGray => "light_gray",
+            DyeColor::Cyan => "cyan",
+            DyeColor::Purple => "purple",
+            DyeColor::Blue => "blue",
+            DyeColor::Brown => "brown",
+            DyeColor::Green => "green",
+            DyeColor::Red => "red",
+            DyeColor::Black => "black",
+        }
+    }
+}
+
 impl TryFrom<i32> for DyeColor {
     type Error = ();
 
@@ -1,13 +1,21 @@
+use std::sync::Arc;
+
 use parking_lot::Mutex;
 
 use crate::{
     entity::Player,
-    protocol::packet::{Packet, ServerPacket},
+    protocol::{
+        Bundle, FramedPacket, ProtocolState,
+        packet::{Packet, ServerPacket},
+    },
 };
 
 mod position;
 pub use position::*;
 
+mod chunk_pos;
+pub use chunk_pos::*;
+
 mod identifier;
 pub use identifier::*;
 
@@ -20,6 +28,9 @@ pub use direction::*;
 mod pose;
 pub use pose::*;
 
+mod bounding_box;
+pub use bounding_box::*;
+
 pub trait Viewable {
     fn viewers(&self) -> &Viewers;
 
@@ -31,13 +42,48 @@ pub trait Viewable {
         self.viewers().remove_viewer(player);
     }
 
+    /// Sends a packet to all viewers, encoding it only once.
     fn send_packet_to_viewers<P>(&self, packet: &P)
     where
         P: Packet + ServerPacket + 'static,
     {
-        for viewer in self.viewers() {
-            viewer.send_packet(packet);
+        self.viewers().send_packet(packet);
+    }
+
+    fn send_framed_packet_to_viewers(&self, packet: &Arc<FramedPacket>) {
+        self.viewers().send_framed_packet(packet);
+    }
+
+    /// Sends the packets added to the bundle to all viewers, which apply them at once.
+    fn send_bundle_to_viewers<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Bundle),
+    {
+        let mut bundle = Bundle::new();
+        f(&mut bundle);
+        self.viewers().send_bundle(&bundle);
+    }
+}
+
+/// Sends a packet to many players, encoding and compressing it only once.
+pub(crate) fn broadcast_packet<'a, P>(players: impl IntoIterator<Item = &'a Player>, packet: &P)
+where
+    P: Packet + ServerPacket + 'static,
+{
+    let mut players = players.into_iter().peekable();
+    if players.peek().is_none() {
+        return;
+    }
+
+    let packet = match FramedPacket::new(ProtocolState::Play, packet) {
+        Ok(packet) => Arc::new(packet),
+        Err(e) => {
+            log::error!("Failed to encode broadcast packet: {}", e);
+            return;
         }
+    };
+    for player in players {
+        player.send_framed_packet(&packet);
     }
 }
 
@@ -59,6 +105,34 @@ impl Viewers {
     pub fn remove_viewer(&self, player: Player) {
         self.viewers.lock().retain(|other| *other != player);
     }
+
+    /// Sends a packet to all viewers, encoding it only once.
+    pub fn send_packet<P>(&self, packet: &P)
+    where
+        P: Packet + ServerPacket + 'static,
+    {
+        broadcast_packet(self.viewers.lock().iter(), packet);
+    }
+
+    pub fn send_framed_packet(&self, packet: &Arc<FramedPacket>) {
+        for viewer in self.viewers.lock().iter() {
+            viewer.send_framed_packet(packet);
+        }
+    }
+
+    pub fn send_bundle(&self, bundle: &Bundle) {
+        let frames = bundle.frames();
+        for viewer in self.viewers.lock().iter() {
+            for frame in &frames {
+                viewer.send_framed_packet(frame);
+            }
+        }
+    }
+
+    pub fn contains(&self, player: &Player) -> bool {
+        self.viewers.lock().contains(player)
+    }
+
     pub fn iter(&self) -> Vec<Player> {
         self.viewers.lock().clone()
     }
@@ -113,6 +113,19 @@ impl Position {
         }
     }
 
+    /// Returns the unit vector pointing where the yaw and pitch look.
+    pub fn direction(&self) -> (f64, f64, f64) {
+        let (yaw, pitch) = (
+            (self.yaw as f64).to_radians(),
+            (self.pitch as f64).to_radians(),
+        );
+        (
+            -yaw.sin() * pitch.cos(),
+            -pitch.sin(),
+            yaw.cos() * pitch.cos(),
+        )
+    }
+
     const fn fix_yaw(yaw: f32) -> f32 {
         let mut yaw = yaw % 360.0;
         if yaw < -180.0 {
@@ -173,7 +186,7 @@ bitflags! {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BlockPosition {
     x: i64,
     y: i64,
@@ -208,12 +221,12 @@ impl BlockPosition {
     }
 }
 
-impl Into<BlockPosition> for Position {
-    fn into(self) -> BlockPosition {
+impl From<Position> for BlockPosition {
+    fn from(position: Position) -> Self {
         BlockPosition::new(
-            (self.x() / 16.0) as i64,
-            (self.y() / 16.0) as i64,
-            (self.z() / 16.0) as i64,
+            position.x().floor() as i64,
+            position.y().floor() as i64,
+            position.z().floor() as i64,
         )
     }
 }
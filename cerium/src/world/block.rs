@@ -1,6 +1,12 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, ops::Deref, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    sync::{Arc, LazyLock},
+};
+
+use crate::{item::Material, util::BoundingBox};
 
 include!("../registry/generated/blocks.rs");
 
@@ -14,34 +20,64 @@ impl Deref for Block {
     }
 }
 
-pub static REGISTRY: LazyLock<(HashMap<String, i32>, HashMap<i32, BlockState>, Vec<i32>)> = LazyLock::new(|| {
+pub static REGISTRY: LazyLock<(HashMap<String, i32>, HashMap<i32, BlockState>, Vec<i32>, HashMap<i32, Vec<i32>>)> = LazyLock::new(|| {
     let entries: IndexMap<String, serde_json::Value> =
         serde_json::from_str(include_str!("../../data/block.json")).unwrap();
 
     let mut by_key= HashMap::new();
     let mut states = HashMap::new();
     let mut to_state = vec![];
+    let mut block_states = HashMap::new();
+    // Most states share a handful of shapes, so every shape is only stored once
+    let mut shapes: HashMap<String, Arc<[BoundingBox]>> = HashMap::new();
+    let mut shape = |value: &serde_json::Value| -> Arc<[BoundingBox]> {
+        let value = value.as_str().unwrap_or("[]");
+        shapes.entry(value.to_string()).or_insert_with(|| parse_shape(value).into()).clone()
+    };
 
     for (key, block) in entries {
         let id = block["id"].as_i64().unwrap() as i32;
         let block_entity: Option<BlockEntityInfo> =
             serde_json::from_value(block["blockEntity"].clone()).ok();
 
-        to_state.push(block["defaultStateId"].as_i64().unwrap() as i32);
+        let default_state = block["defaultStateId"].as_i64().unwrap() as i32;
+        to_state.push(default_state);
 
-        for (_state_key, state) in block["states"].as_object().unwrap() {
+        let mut ids = vec![];
+        for (state_key, state) in block["states"].as_object().unwrap() {
             let state_id = state["stateId"].as_i64().unwrap() as usize;
+            let properties = state_key
+                .trim_matches(['[', ']'])
+                .split(',')
+                .filter_map(|property| property.split_once('='))
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
             let state = BlockState {
                 id,
                 state_id: state_id as i32,
+                name: key.clone(),
+                properties,
                 block_entity: block_entity.clone(),
+                air: block["air"].as_bool().unwrap_or(false),
+                liquid: state["liquid"].as_bool().unwrap_or(false),
+                waterlogged: state_key.contains("waterlogged=true")
+                    || ALWAYS_WATERLOGGED.contains(&key.as_str()),
+                blocks_motion: state["blocksMotion"].as_bool().unwrap_or(false),
+                collision_shape: shape(&state["collisionShape"]),
+                outline_shape: shape(&state["interactionShape"]),
+                replaceable: state["replaceable"].as_bool().unwrap_or(false),
+                hardness: block["hardness"].as_f64().unwrap_or(0.) as f32,
+                requires_tool: block["requiresTool"].as_bool().unwrap_or(false),
+                item: block["correspondingItem"].as_str().and_then(Material::from_key),
             };
             states.insert(state_id as i32, state);
+            ids.push(state_id as i32);
         }
-        by_key.insert(key, (states.len() - 1) as i32);
+        block_states.insert(id, ids);
+        by_key.insert(key, default_state);
     }
 
-    (by_key, states, to_state)
+    (by_key, states, to_state, block_states)
 });
 
 impl AsRef<BlockState> for BlockState {
@@ -56,11 +92,51 @@ impl AsRef<BlockState> for Block {
     }
 }
 
+/// Parses a shape from the data, e.g. `[AABB[0.0, 0.0, 0.0] -> [1.0, 0.5, 1.0]]`.
+fn parse_shape(value: &str) -> Vec<BoundingBox> {
+    value
+        .split("AABB")
+        .skip(1)
+        .filter_map(|aabb| {
+            let coordinates: Vec<f64> = aabb
+                .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == 'E'))
+                .filter(|value| !value.is_empty() && *value != "-")
+                .map(|value| value.parse().ok())
+                .collect::<Option<_>>()?;
+            let [min_x, min_y, min_z, max_x, max_y, max_z] = coordinates[..] else {
+                return None;
+            };
+            Some(BoundingBox::new(min_x, min_y, min_z, max_x, max_y, max_z))
+        })
+        .collect()
+}
+
+/// Blocks that always contain water without having a `waterlogged` property.
+const ALWAYS_WATERLOGGED: [&str; 5] = [
+    "minecraft:kelp",
+    "minecraft:kelp_plant",
+    "minecraft:seagrass",
+    "minecraft:tall_seagrass",
+    "minecraft:bubble_column",
+];
+
 #[derive(Debug, Clone)]
 pub struct BlockState {
     id: i32,
     state_id: i32,
+    name: String,
+    properties: Vec<(String, String)>,
     block_entity: Option<BlockEntityInfo>,
+    air: bool,
+    liquid: bool,
+    waterlogged: bool,
+    blocks_motion: bool,
+    collision_shape: Arc<[BoundingBox]>,
+    outline_shape: Arc<[BoundingBox]>,
+    replaceable: bool,
+    hardness: f32,
+    requires_tool: bool,
+    item: Option<Material>,
 }
 
 impl BlockState {
@@ -72,14 +148,127 @@ impl BlockState {
         self.state_id
     }
 
+    /// Returns the key of the block, e.g. `minecraft:stone`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the properties of the state, e.g. `("facing", "north")`.
+    pub fn properties(&self) -> &[(String, String)] {
+        &self.properties
+    }
+
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the state of the same block with a property changed, or `None` if the block has no such value.
+    pub fn with_property(&self, name: &str, value: &str) -> Option<&'static BlockState> {
+        self.property(name)?;
+        REGISTRY.3.get(&self.id)?
+            .iter()
+            .filter_map(|id| REGISTRY.1.get(id))
+            .find(|state| {
+                state.properties.iter().zip(&self.properties).all(|((key, new), (_, old))| {
+                    if key == name { new == value } else { new == old }
+                })
+            })
+    }
+
+    /// Returns the default state of the block.
+    pub fn default_state(&self) -> &'static BlockState {
+        BlockState::from_key(self.name.clone()).unwrap()
+    }
+
     pub fn block_entity(&self) -> Option<&BlockEntityInfo> {
         self.block_entity.as_ref()
     }
 
+    /// Returns whether the state is air, cave air or void air.
+    pub fn is_air(&self) -> bool {
+        self.air
+    }
+
+    /// Returns whether the state is water or lava, or contains water.
+    pub fn has_fluid(&self) -> bool {
+        self.liquid || self.waterlogged
+    }
+
+    /// Returns whether entities collide with the state.
+    pub fn blocks_motion(&self) -> bool {
+        self.blocks_motion
+    }
+
+    /// Returns the boxes entities collide with, relative to the block. Empty for blocks like air or grass.
+    pub fn collision_shape(&self) -> &[BoundingBox] {
+        &self.collision_shape
+    }
+
+    /// Returns the boxes players target and see outlined when looking at the block, relative to the block.
+    pub fn outline_shape(&self) -> &[BoundingBox] {
+        &self.outline_shape
+    }
+
+    /// Returns whether placing a block replaces the state, like it does for air, water or grass.
+    pub fn is_replaceable(&self) -> bool {
+        self.replaceable
+    }
+
+    /// Returns how long the block takes to break, or a negative value if it cannot be broken like bedrock.
+    pub fn hardness(&self) -> f32 {
+        self.hardness
+    }
+
+    /// Returns whether the block only drops items when broken with the correct tool.
+    pub fn requires_tool(&self) -> bool {
+        self.requires_tool
+    }
+
+    /// Returns the item of the block, if there is one.
+    pub fn item(&self) -> Option<Material> {
+        self.item
+    }
+
+    pub fn is_leaves(&self) -> bool {
+        self.name.ends_with("_leaves")
+    }
+
     pub fn from_id(id: i32) -> Option<&'static BlockState> {
         REGISTRY.1.get(&id)
     }
 
+    /// Returns the state described by a key with properties, e.g. `minecraft:oak_log[axis=x]`.
+    ///
+    /// Missing properties keep their default value and unknown properties are ignored.
+    pub fn from_state_key(key: &str) -> Option<&'static BlockState> {
+        let (name, properties) = match key.split_once('[') {
+            Some((name, properties)) => (name, properties.trim_end_matches(']')),
+            None => (key, ""),
+        };
+
+        let mut state = BlockState::from_key(name.to_string())?;
+        for (property, value) in properties.split(',').filter_map(|property| property.split_once('=')) {
+            state = state.with_property(property.trim(), value.trim()).unwrap_or(state);
+        }
+        Some(state)
+    }
+
+    /// Returns the key of the state with its properties, e.g. `minecraft:oak_log[axis=x]`.
+    pub fn state_key(&self) -> String {
+        if self.properties.is_empty() {
+            return self.name.clone();
+        }
+
+        let properties: Vec<String> = self.properties
+            .iter()
+            .map(|(property, value)| format!("{property}={value}"))
+            .collect();
+        format!("{}[{}]", self.name, properties.join(","))
+    }
+
     pub fn from_key(key: String) -> Option<&'static BlockState> {
         let i = REGISTRY.0.get(&key);
         i.map(|i| REGISTRY.1.get(i)).flatten()
@@ -102,6 +291,63 @@ pub enum BlockFace {
     East,
 }
 
+impl BlockFace {
+    pub const ALL: [BlockFace; 6] = [
+        BlockFace::Bottom,
+        BlockFace::Top,
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::West,
+        BlockFace::East,
+    ];
+
+    pub const HORIZONTAL: [BlockFace; 4] = [
+        BlockFace::North,
+        BlockFace::East,
+        BlockFace::South,
+        BlockFace::West,
+    ];
+
+    /// Returns the offset to the neighbouring block in the direction of the face.
+    pub fn offset(&self) -> (i64, i64, i64) {
+        match self {
+            BlockFace::Bottom => (0, -1, 0),
+            BlockFace::Top => (0, 1, 0),
+            BlockFace::North => (0, 0, -1),
+            BlockFace::South => (0, 0, 1),
+            BlockFace::West => (-1, 0, 0),
+            BlockFace::East => (1, 0, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> BlockFace {
+        match self {
+            BlockFace::Bottom => BlockFace::Top,
+            BlockFace::Top => BlockFace::Bottom,
+            BlockFace::North => BlockFace::South,
+            BlockFace::South => BlockFace::North,
+            BlockFace::West => BlockFace::East,
+            BlockFace::East => BlockFace::West,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        !matches!(self, BlockFace::Bottom | BlockFace::Top)
+    }
+
+    /// Returns the name of the direction as used by block state properties, e.g. `north`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockFace::Bottom => "down",
+            BlockFace::Top => "up",
+            BlockFace::North => "north",
+            BlockFace::South => "south",
+            BlockFace::West => "west",
+            BlockFace::East => "east",
+        }
+    }
+}
+
 impl TryFrom<i32> for BlockFace {
     type Error = ();
 
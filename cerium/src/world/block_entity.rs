@@ -1,9 +1,228 @@
-use cerium_nbt::Nbt;
+use cerium_nbt::{NbtCompound, NbtTag};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::auth::GameProfile;
+use crate::protocol::encode::EncodeError;
+use crate::text::TextComponent;
+use crate::util::DyeColor;
 
 #[derive(Debug, Clone)]
 pub struct BlockEntity {
     pub packed_xz: u8,
     pub y: i16,
     pub r#type: i32,
-    pub data: Option<Nbt>,
+    pub data: NbtCompound,
+}
+
+/// The text of one side of a sign.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignText {
+    lines: [TextComponent; 4],
+    color: DyeColor,
+    glowing: bool,
+}
+
+impl SignText {
+    pub fn new(lines: [TextComponent; 4]) -> Self {
+        Self {
+            lines,
+            color: DyeColor::Black,
+            glowing: false,
+        }
+    }
+
+    pub fn lines(&self) -> &[TextComponent; 4] {
+        &self.lines
+    }
+
+    pub fn with_color(mut self, color: DyeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_glowing(mut self, glowing: bool) -> Self {
+        self.glowing = glowing;
+        self
+    }
+
+    fn to_nbt(&self) -> Result<NbtCompound, EncodeError> {
+        let messages = self
+            .lines
+            .iter()
+            .map(|line| line.serialize(cerium_nbt::Serializer))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(EncodeError::NbtError)?;
+
+        let mut compound = NbtCompound::new();
+        compound.insert("messages", NbtTag::List(messages));
+        compound.insert("color", self.color.name());
+        compound.insert("has_glowing_text", self.glowing);
+        Ok(compound)
+    }
+}
+
+impl Default for SignText {
+    fn default() -> Self {
+        Self::new([const { TextComponent::EMPTY }; 4])
+    }
+}
+
+/// The block entity data of a sign or hanging sign.
+///
+/// # Example
+/// ```no_run
+/// # use cerium::world::{SignData, SignText, World};
+/// # use cerium::text::TextComponent;
+/// # use cerium::util::BlockPosition;
+/// # use cerium_nbt::NbtCompound;
+/// # fn place(world: &World, position: BlockPosition) -> Result<(), Box<dyn std::error::Error>> {
+/// let front = SignText::new([
+///     TextComponent::text("Welcome"),
+///     TextComponent::text("to the"),
+///     TextComponent::text("server!"),
+///     TextComponent::EMPTY,
+/// ]);
+/// // Fails if the text can not be written as NBT
+/// let data: NbtCompound = SignData::new(front).waxed().try_into()?;
+/// world.set_block_entity_data(position, data);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignData {
+    pub front: SignText,
+    pub back: SignText,
+    pub waxed: bool,
+}
+
+impl SignData {
+    pub fn new(front: SignText) -> Self {
+        Self {
+            front,
+            ..Default::default()
+        }
+    }
+
+    /// Prevents players from editing the sign.
+    pub fn waxed(mut self) -> Self {
+        self.waxed = true;
+        self
+    }
+}
+
+impl TryFrom<SignData> for NbtCompound {
+    type Error = EncodeError;
+
+    fn try_from(sign: SignData) -> Result<Self, Self::Error> {
+        let mut compound = NbtCompound::new();
+        compound.insert("front_text", sign.front.to_nbt()?);
+        compound.insert("back_text", sign.back.to_nbt()?);
+        compound.insert("is_waxed", sign.waxed);
+        Ok(compound)
+    }
+}
+
+/// The block entity data of a player head showing the skin of a profile.
+#[derive(Debug, Clone)]
+pub struct SkullData {
+    pub profile: GameProfile,
+}
+
+impl SkullData {
+    pub fn new(profile: GameProfile) -> Self {
+        Self { profile }
+    }
+}
+
+impl From<SkullData> for NbtCompound {
+    fn from(skull: SkullData) -> Self {
+        let properties = skull
+            .profile
+            .properties
+            .iter()
+            .map(|property| {
+                let mut compound = NbtCompound::new();
+                compound.insert("name", property.name.as_str());
+                compound.insert("value", property.value.as_str());
+                if let Some(signature) = &property.signature {
+                    compound.insert("signature", signature.as_str());
+                }
+                NbtTag::Compound(compound)
+            })
+            .collect::<Vec<_>>();
+
+        let mut profile = NbtCompound::new();
+        profile.insert("id", uuid_to_int_array(skull.profile.uuid));
+        profile.insert("name", skull.profile.name);
+        profile.insert("properties", NbtTag::List(properties));
+
+        let mut compound = NbtCompound::new();
+        compound.insert("profile", profile);
+        compound
+    }
+}
+
+fn uuid_to_int_array(uuid: Uuid) -> Vec<i32> {
+    let value = uuid.as_u128();
+    (0..4)
+        .map(|i| (value >> (96 - i * 32)) as u32 as i32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Property;
+
+    #[test]
+    fn test_sign_data() {
+        let front = SignText::new([
+            TextComponent::text("a"),
+            TextComponent::text("b"),
+            TextComponent::EMPTY,
+            TextComponent::EMPTY,
+        ])
+        .with_color(DyeColor::Red)
+        .with_glowing(true);
+        let compound: NbtCompound = SignData::new(front).waxed().try_into().unwrap();
+
+        let keys = compound
+            .children
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["front_text", "back_text", "is_waxed"]);
+
+        let Some((_, NbtTag::Compound(front))) = compound.children.first() else {
+            panic!("front_text is not a compound");
+        };
+        let Some((_, NbtTag::List(messages))) = front.children.first() else {
+            panic!("messages is not a list");
+        };
+        assert_eq!(messages.len(), 4);
+        assert_eq!(front.children[1].1, NbtTag::String("red".to_string()));
+        assert_eq!(front.children[2].1, NbtTag::Byte(1));
+    }
+
+    #[test]
+    fn test_skull_data() {
+        let uuid = Uuid::from_u128(0x00000001_00000002_00000003_fffffffe);
+        let compound: NbtCompound = SkullData::new(GameProfile {
+            uuid,
+            name: "Notch".to_string(),
+            properties: vec![Property {
+                name: "textures".to_string(),
+                value: "abc".to_string(),
+                signature: None,
+            }],
+        })
+        .into();
+
+        let Some((_, NbtTag::Compound(profile))) = compound.children.first() else {
+            panic!("profile is not a compound");
+        };
+        assert_eq!(profile.children[0].1, NbtTag::IntArray(vec![1, 2, 3, -2]));
+        assert_eq!(profile.children[1].1, NbtTag::String("Notch".to_string()));
+    }
 }
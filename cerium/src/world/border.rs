@@ -0,0 +1,321 @@
+use std::time::{Duration, Instant};
+
+use crate::protocol::packet::{
+    InitializeBorderPacket, SetBorderCenterPacket, SetBorderLerpSizePacket, SetBorderSizePacket,
+    SetBorderWarningDelayPacket, SetBorderWarningDistancePacket,
+};
+use crate::util::Position;
+
+/// The distance from the world center at which portals stop working, the same as in vanilla.
+const PORTAL_TELEPORT_BOUNDARY: i32 = 29_999_984;
+
+/// How players outside of a [`WorldBorder`] are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderMode {
+    /// A [`PlayerBorderDamageEvent`](crate::event::player::PlayerBorderDamageEvent) is fired for players
+    /// outside of the safe zone, with the damage depending on how far outside they are. The server does not
+    /// track health, so a listener has to apply the damage.
+    #[default]
+    DamageEvent,
+    /// Players cannot move further away from the border.
+    Solid,
+}
+
+/// The world border of a [`World`](crate::world::World).
+///
+/// A border is a value that is changed with its `with_*` methods and applied with
+/// [`World::set_border`](crate::world::World::set_border).
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// # use cerium::world::World;
+/// # fn shrink(world: &World) {
+/// let border = world.border().lerp_to(50.0, Duration::from_secs(300));
+/// world.set_border(border);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    center_x: f64,
+    center_z: f64,
+    old_diameter: f64,
+    new_diameter: f64,
+    lerp_start: Instant,
+    lerp_duration: Duration,
+    warning_distance: i32,
+    warning_time: i32,
+    damage_per_block: f64,
+    damage_safe_zone: f64,
+    mode: BorderMode,
+}
+
+impl WorldBorder {
+    pub const DEFAULT_DIAMETER: f64 = 59_999_968.;
+
+    pub fn new() -> Self {
+        Self {
+            center_x: 0.,
+            center_z: 0.,
+            old_diameter: Self::DEFAULT_DIAMETER,
+            new_diameter: Self::DEFAULT_DIAMETER,
+            lerp_start: Instant::now(),
+            lerp_duration: Duration::ZERO,
+            warning_distance: 5,
+            warning_time: 15,
+            damage_per_block: 0.2,
+            damage_safe_zone: 5.,
+            mode: BorderMode::DamageEvent,
+        }
+    }
+
+    pub fn center_x(&self) -> f64 {
+        self.center_x
+    }
+
+    pub fn center_z(&self) -> f64 {
+        self.center_z
+    }
+
+    /// Returns the current diameter, which changes over time while the border is moving.
+    pub fn diameter(&self) -> f64 {
+        let elapsed = self.lerp_start.elapsed();
+        if elapsed >= self.lerp_duration {
+            return self.new_diameter;
+        }
+
+        let progress = elapsed.as_secs_f64() / self.lerp_duration.as_secs_f64();
+        self.old_diameter + (self.new_diameter - self.old_diameter) * progress
+    }
+
+    /// Returns the diameter the border is moving to.
+    pub fn target_diameter(&self) -> f64 {
+        self.new_diameter
+    }
+
+    /// Returns the remaining time until the border reached its target diameter.
+    pub fn remaining_lerp_time(&self) -> Duration {
+        self.lerp_duration.saturating_sub(self.lerp_start.elapsed())
+    }
+
+    pub fn warning_distance(&self) -> i32 {
+        self.warning_distance
+    }
+
+    /// Returns the warning time in seconds.
+    pub fn warning_time(&self) -> i32 {
+        self.warning_time
+    }
+
+    pub fn damage_per_block(&self) -> f64 {
+        self.damage_per_block
+    }
+
+    pub fn damage_safe_zone(&self) -> f64 {
+        self.damage_safe_zone
+    }
+
+    pub fn mode(&self) -> BorderMode {
+        self.mode
+    }
+
+    pub fn with_center(mut self, x: f64, z: f64) -> Self {
+        self.center_x = x;
+        self.center_z = z;
+        self
+    }
+
+    /// Changes the diameter immediately.
+    pub fn with_diameter(mut self, diameter: f64) -> Self {
+        self.old_diameter = diameter;
+        self.new_diameter = diameter;
+        self.lerp_start = Instant::now();
+        self.lerp_duration = Duration::ZERO;
+        self
+    }
+
+    /// Moves the border from its current diameter to the given diameter over time.
+    pub fn lerp_to(mut self, diameter: f64, duration: Duration) -> Self {
+        self.old_diameter = self.diameter();
+        self.new_diameter = diameter;
+        self.lerp_start = Instant::now();
+        self.lerp_duration = duration;
+        self
+    }
+
+    pub fn with_warning_distance(mut self, blocks: i32) -> Self {
+        self.warning_distance = blocks;
+        self
+    }
+
+    /// Sets the warning time in seconds.
+    pub fn with_warning_time(mut self, seconds: i32) -> Self {
+        self.warning_time = seconds;
+        self
+    }
+
+    /// Sets how much damage is dealt per block outside of the safe zone.
+    pub fn with_damage(mut self, per_block: f64, safe_zone: f64) -> Self {
+        self.damage_per_block = per_block;
+        self.damage_safe_zone = safe_zone;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: BorderMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the distance of a position to the closest border edge.
+    ///
+    /// The distance is negative if the position is outside of the border.
+    pub fn distance(&self, position: Position) -> f64 {
+        let radius = self.diameter() / 2.;
+        let dx = radius - (position.x() - self.center_x).abs();
+        let dz = radius - (position.z() - self.center_z).abs();
+        dx.min(dz)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.distance(position) >= 0.
+    }
+
+    /// Returns the damage a player at the position takes, or `None` if the position is in the safe zone.
+    pub fn damage_at(&self, position: Position) -> Option<f32> {
+        let outside = -(self.distance(position) + self.damage_safe_zone);
+        if outside <= 0. || self.damage_per_block <= 0. {
+            return None;
+        }
+        Some((outside * self.damage_per_block).floor().max(1.) as f32)
+    }
+
+    pub(crate) fn initialize_packet(&self) -> InitializeBorderPacket {
+        InitializeBorderPacket {
+            x: self.center_x,
+            z: self.center_z,
+            old_diameter: self.diameter(),
+            new_diameter: self.new_diameter,
+            speed: self.remaining_lerp_time().as_millis() as i64,
+            portal_teleport_boundary: PORTAL_TELEPORT_BOUNDARY,
+            warning_blocks: self.warning_distance,
+            warning_time: self.warning_time,
+        }
+    }
+
+    /// Returns the packets that change the border of clients from `old` to `self`.
+    pub(crate) fn update_packets(&self, old: &WorldBorder) -> BorderPackets {
+        let mut packets = BorderPackets::default();
+
+        if self.center_x != old.center_x || self.center_z != old.center_z {
+            packets.center = Some(SetBorderCenterPacket {
+                x: self.center_x,
+                z: self.center_z,
+            });
+        }
+
+        if self.lerp_start != old.lerp_start || self.new_diameter != old.new_diameter {
+            let remaining = self.remaining_lerp_time();
+            if remaining.is_zero() {
+                packets.size = Some(SetBorderSizePacket {
+                    diameter: self.new_diameter,
+                });
+            } else {
+                packets.lerp_size = Some(SetBorderLerpSizePacket {
+                    old_diameter: self.diameter(),
+                    new_diameter: self.new_diameter,
+                    speed: remaining.as_millis() as i64,
+                });
+            }
+        }
+
+        if self.warning_distance != old.warning_distance {
+            packets.warning_distance = Some(SetBorderWarningDistancePacket {
+                warning_blocks: self.warning_distance,
+            });
+        }
+        if self.warning_time != old.warning_time {
+            packets.warning_delay = Some(SetBorderWarningDelayPacket {
+                warning_time: self.warning_time,
+            });
+        }
+
+        packets
+    }
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The packets needed to apply a change of a [`WorldBorder`].
+#[derive(Default)]
+pub(crate) struct BorderPackets {
+    pub center: Option<SetBorderCenterPacket>,
+    pub size: Option<SetBorderSizePacket>,
+    pub lerp_size: Option<SetBorderLerpSizePacket>,
+    pub warning_distance: Option<SetBorderWarningDistancePacket>,
+    pub warning_delay: Option<SetBorderWarningDelayPacket>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_and_damage() {
+        let border = WorldBorder::new()
+            .with_center(10., -10.)
+            .with_diameter(20.)
+            .with_damage(0.5, 2.);
+
+        let inside = Position::new(15., 64., -12., 0., 0.);
+        assert_eq!(border.distance(inside), 5.);
+        assert!(border.contains(inside));
+        assert_eq!(border.damage_at(inside), None);
+
+        // 1 block outside is still in the safe zone
+        let outside = Position::new(21., 64., -10., 0., 0.);
+        assert!(!border.contains(outside));
+        assert_eq!(border.damage_at(outside), None);
+
+        assert_eq!(
+            border.damage_at(Position::new(-10., 64., -10., 0., 0.)),
+            Some(4.)
+        );
+        assert_eq!(
+            border.damage_at(Position::new(24., 64., -10., 0., 0.)),
+            Some(1.)
+        );
+
+        // A border without damage never hurts, no matter how far outside
+        let border = border.with_damage(0., 2.);
+        assert_eq!(
+            border.damage_at(Position::new(-100., 64., -10., 0., 0.)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_update_packets() {
+        let border = WorldBorder::new();
+        let packets = border.update_packets(&border);
+        assert!(packets.center.is_none() && packets.size.is_none() && packets.lerp_size.is_none());
+
+        let moved = border
+            .with_center(5., 5.)
+            .lerp_to(100., Duration::from_secs(60));
+        let packets = moved.update_packets(&border);
+        assert!(packets.center.is_some());
+        assert!(packets.size.is_none());
+        assert_eq!(packets.lerp_size.unwrap().new_diameter, 100.);
+
+        let resized = moved.with_diameter(10.).with_warning_time(30);
+        let packets = resized.update_packets(&moved);
+        assert!(packets.center.is_none());
+        assert_eq!(packets.size.unwrap().diameter, 10.);
+        assert!(packets.warning_delay.is_some());
+        assert!(packets.warning_distance.is_none());
+    }
+}
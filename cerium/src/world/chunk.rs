@@ -1,34 +1,108 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
-use parking_lot::RwLock;
+use cerium_nbt::NbtCompound;
+use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
 
-use crate::util::BlockPosition;
+use crate::entity::Player;
+use crate::protocol::{
+    FramedPacket, ProtocolState, encode::EncodeError, packet::ChunkDataAndUpdateLightPacket,
+};
+use crate::util::{BlockPosition, ChunkPos, LocalPos, SectionPos, Viewable, Viewers};
 
+use crate::world::heightmap::{Heightmap, HeightmapKind};
 use crate::world::{BlockEntity, BlockState, ChunkSection};
 
 #[derive(Clone)]
-pub struct Chunk(Arc<RwLock<Inner>>);
+pub struct Chunk(Arc<Inner>);
 
 impl Chunk {
-    pub(crate) fn new(chunk_x: i32, chunk_z: i32, min_y: i32) -> Self {
-        Self(Arc::new(RwLock::new(Inner::new(chunk_x, chunk_z, min_y))))
+    /// Creates an empty chunk for a world starting at `min_y` that is `height` blocks high.
+    pub(crate) fn new(pos: ChunkPos, min_y: i32, height: i32) -> Self {
+        Self(Arc::new(Inner {
+            pos,
+            data: RwLock::new(Data::new(min_y, height)),
+            packet: Mutex::new(None),
+            viewers: Viewers::new(),
+            unviewed_since: Mutex::new(Some(Instant::now())),
+        }))
+    }
+
+    /// Creates a copy of the chunk at the same position that shares the sections with this chunk.
+    ///
+    /// A section is only copied once either chunk changes it. Viewers are not copied.
+    pub(crate) fn fork(&self) -> Self {
+        Self(Arc::new(Inner {
+            pos: self.0.pos,
+            data: RwLock::new(self.0.data.read().clone()),
+            packet: Mutex::new(self.0.packet.lock().clone()),
+            viewers: Viewers::new(),
+            unviewed_since: Mutex::new(Some(Instant::now())),
+        }))
+    }
+
+    pub fn pos(&self) -> ChunkPos {
+        self.0.pos
     }
 
     pub fn x(&self) -> i32 {
-        self.0.read().x()
+        self.0.pos.x()
     }
 
     pub fn z(&self) -> i32 {
-        self.0.read().z()
+        self.0.pos.z()
+    }
+
+    /// Returns since when no player views the chunk, or `None` if it is currently viewed.
+    pub fn unviewed_since(&self) -> Option<Instant> {
+        *self.0.unviewed_since.lock()
     }
 
     pub fn sections(&self) -> Vec<ChunkSection> {
-        self.0.read().sections().clone()
+        self.0
+            .data
+            .read()
+            .sections()
+            .iter()
+            .map(|section| ChunkSection::clone(section))
+            .collect()
+    }
+
+    pub(crate) fn section_count(&self) -> usize {
+        self.0.data.read().sections.len()
+    }
+
+    /// Returns the packet sending the chunk to a client.
+    ///
+    /// The packet is encoded once and shared by every player loading the chunk until the chunk changes. Fails if
+    /// the chunk cannot be encoded, e.g. because of invalid block entity data.
+    pub fn packet(&self) -> Result<Arc<FramedPacket>, EncodeError> {
+        let mut cache = self.0.packet.lock();
+        let version = self.0.data.read().version;
+        if let Some((cached, packet)) = &*cache
+            && *cached == version
+        {
+            return Ok(packet.clone());
+        }
+
+        let packet: ChunkDataAndUpdateLightPacket = self.into();
+        let packet = Arc::new(FramedPacket::new(ProtocolState::Play, &packet)?);
+        // A change while encoding makes the packet outdated, as it is stored with the version from before
+        *cache = Some((version, packet.clone()));
+        Ok(packet)
+    }
+
+    /// Locks the data to change it, which makes the cached packet outdated.
+    fn write(&self) -> RwLockWriteGuard<'_, Data> {
+        let mut data = self.0.data.write();
+        data.version += 1;
+        data
     }
 
     pub fn block_entites(&self) -> Vec<BlockEntity> {
         self.0
+            .data
             .read()
             .block_entities
             .values()
@@ -36,41 +110,120 @@ impl Chunk {
             .collect::<Vec<_>>()
     }
 
+    /// Returns the block entity at the block coordinates, or `None` if there is none.
+    pub fn get_block_entity(&self, x: i32, y: i32, z: i32) -> Option<BlockEntity> {
+        self.0
+            .data
+            .read()
+            .block_entities
+            .get(&Data::block_entity_key(x, y, z))
+            .cloned()
+    }
+
+    /// Replaces the data of the block entity at the block coordinates and returns the updated block entity,
+    /// or `None` if there is no block entity.
+    pub fn set_block_entity_data(
+        &self,
+        x: i32,
+        y: i32,
+        z: i32,
+        data: NbtCompound,
+    ) -> Option<BlockEntity> {
+        let mut inner = self.write();
+        let block_entity = inner
+            .block_entities
+            .get_mut(&Data::block_entity_key(x, y, z))?;
+        block_entity.data = data;
+        Some(block_entity.clone())
+    }
+
+    pub fn heightmap(&self, kind: HeightmapKind) -> Heightmap {
+        self.0.data.read().heightmap(kind).clone()
+    }
+
+    /// Returns the heightmaps the client needs.
+    pub fn client_heightmaps(&self) -> Vec<Heightmap> {
+        self.0
+            .data
+            .read()
+            .heightmaps
+            .iter()
+            .filter(|heightmap| heightmap.kind().is_sent_to_client())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the y coordinate of the highest block of a kind in the column, or `None` if there is none.
+    pub fn highest_block_y(&self, x: i32, z: i32, kind: HeightmapKind) -> Option<i32> {
+        self.0.data.read().heightmap(kind).highest_block_y(x, z)
+    }
+
     pub fn get_block(&self, x: i32, y: i32, z: i32) -> u16 {
-        self.0.read().get_block(x, y, z)
+        self.0.data.read().get_block(x, y, z)
     }
 
     pub fn set_block(&self, x: i32, y: i32, z: i32, block: &BlockState) {
-        self.0.write().set_block(x, y, z, block)
+        self.write().set_block(x, y, z, block)
     }
 
     pub fn get_biome(&self, x: i32, y: i32, z: i32) -> u16 {
-        self.0.read().get_biome(x, y, z)
+        self.0.data.read().get_biome(x, y, z)
+    }
+
+    /// Replaces the section at the index, counted from the bottom of the world, and creates the block
+    /// entities of its blocks.
+    ///
+    /// Sections have to be replaced from the bottom up to keep the heightmaps correct.
+    pub(crate) fn set_section(&self, index: usize, section: ChunkSection) {
+        self.write().set_section(index, section)
     }
 
     pub fn set_biome(&self, x: i32, y: i32, z: i32, biome: i32) {
-        self.0.write().set_biome(x, y, z, biome)
+        self.write().set_biome(x, y, z, biome)
     }
 
-    pub fn to_chunk_pos(position: impl Into<BlockPosition>) -> (i32, i32) {
-        let position = position.into();
-        let chunk_x = (position.x() / 16) as i32;
-        let chunk_z = (position.z() / 16) as i32;
-        (chunk_x, chunk_z)
+    pub fn get_sky_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.0
+            .data
+            .read()
+            .get_light(x, y, z, ChunkSection::get_sky_light)
+    }
+
+    /// Sets the sky light of the block, which is saved with the world and sent to clients. Light is not computed by
+    /// the server.
+    pub fn set_sky_light(&self, x: i32, y: i32, z: i32, level: u8) {
+        self.write().set_light(x, y, z, |section, x, y, z| {
+            section.set_sky_light(x, y, z, level)
+        })
+    }
+
+    pub fn get_block_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.0
+            .data
+            .read()
+            .get_light(x, y, z, ChunkSection::get_block_light)
+    }
+
+    /// Sets the block light of the block, which is saved with the world and sent to clients. Light is not computed
+    /// by the server.
+    pub fn set_block_light(&self, x: i32, y: i32, z: i32, level: u8) {
+        self.write().set_light(x, y, z, |section, x, y, z| {
+            section.set_block_light(x, y, z, level)
+        })
     }
 
     // This implementation comes from [Minestom](https://github.com/Minestom/Minestom/blob/7620f3320988e766cb8e34dd640b5a23911fa7e8/src/main/java/net/minestom/server/coordinate/ChunkRange.java#L48),
     // which comes from [Krypton](https://github.com/KryptonMC/Krypton/blob/a9eff5463328f34072cdaf37aae3e77b14fcac93/server/src/main/kotlin/org/kryptonmc/krypton/util/math/Maths.kt#L62),
     // which comes from a kotlin port [Esophose](https://github.com/Esophose),
     // which originally comes from a [StackOverflow answer](https://stackoverflow.com/questions/398299/looping-in-a-spiral).
-    pub fn chunks_in_range(chunk: (i32, i32), range: i32) -> Vec<(i32, i32)> {
-        let (cx, cz) = chunk;
+    pub fn chunks_in_range(chunk: ChunkPos, range: i32) -> Vec<ChunkPos> {
+        let (cx, cz) = (chunk.x(), chunk.z());
 
         // Send in spiral around the center chunk
         // Note: its not really required to start at the center anymore since the chunk queue is sorted by distance,
         //       however we still should send a circle so this method is still fine, and good for any other case a
         //       spiral might be needed.
-        let mut chunks = vec![(cx, cz)];
+        let mut chunks = vec![chunk];
 
         for id in 1..(range * 2 + 1) * (range * 2 + 1) {
             let index = id - 1;
@@ -86,10 +239,10 @@ impl Chunk {
 
             match a / (radius * 2) {
                 // find the face (0 = top, 1 = right, 2 = bottom, 3 = left)
-                0 => chunks.push((a - radius + cx, -radius + cz)),
-                1 => chunks.push((radius + cx, a % en - radius + cz)),
-                2 => chunks.push((radius - a % en + cx, radius + cz)),
-                3 => chunks.push((-radius + cx, radius - a % en + cz)),
+                0 => chunks.push(ChunkPos::new(a - radius + cx, -radius + cz)),
+                1 => chunks.push(ChunkPos::new(radius + cx, a % en - radius + cz)),
+                2 => chunks.push(ChunkPos::new(radius - a % en + cx, radius + cz)),
+                3 => chunks.push(ChunkPos::new(-radius + cx, radius - a % en + cz)),
                 _ => {}
             }
         }
@@ -98,59 +251,101 @@ impl Chunk {
     }
 
     /// Calulates difference between chunks
-    pub fn difference<F>(lhs: (i32, i32), rhs: (i32, i32), range: i32, callback: F)
+    pub fn difference<F>(lhs: ChunkPos, rhs: ChunkPos, range: i32, callback: F)
     where
-        F: Fn(i32, i32),
+        F: Fn(ChunkPos),
     {
-        let start_x = lhs.0 - range;
-        let end_x = lhs.0 + range;
-        let start_z = lhs.1 - range;
-        let end_z = lhs.1 + range;
-
-        for x in start_x..=end_x {
-            for z in start_z..=end_z {
-                if (x - rhs.0).abs() > range || (z - rhs.1).abs() > range {
-                    callback(x, z);
+        for x in lhs.x() - range..=lhs.x() + range {
+            for z in lhs.z() - range..=lhs.z() + range {
+                let chunk = ChunkPos::new(x, z);
+                if chunk.distance(rhs) > range {
+                    callback(chunk);
                 }
             }
         }
     }
 }
 
+impl Viewable for Chunk {
+    fn viewers(&self) -> &Viewers {
+        &self.0.viewers
+    }
+
+    fn add_viewer(&self, player: Player) {
+        if !self.0.viewers.contains(&player) {
+            self.0.viewers.add_viewer(player);
+        }
+        *self.0.unviewed_since.lock() = None;
+    }
+
+    fn remove_viewer(&self, player: Player) {
+        self.0.viewers.remove_viewer(player);
+        if self.0.viewers.is_empty() {
+            self.0
+                .unviewed_since
+                .lock()
+                .get_or_insert_with(Instant::now);
+        }
+    }
+}
+
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 struct Inner {
-    chunk_x: i32,
-    chunk_z: i32,
+    pos: ChunkPos,
+    data: RwLock<Data>,
+    /// The encoded chunk packet and the version of the data it was encoded from.
+    packet: Mutex<Option<(u64, Arc<FramedPacket>)>>,
+    viewers: Viewers,
+    /// When the last viewer stopped viewing the chunk.
+    unviewed_since: Mutex<Option<Instant>>,
+}
+
+#[derive(Clone)]
+struct Data {
     min_y: i32,
-    sections: Vec<ChunkSection>,
-    block_entities: HashMap<u8, BlockEntity>,
+    /// The sections from the bottom up, shared with forks of the chunk until they are changed.
+    sections: Vec<Arc<ChunkSection>>,
+    block_entities: HashMap<BlockPosition, BlockEntity>,
+    heightmaps: Vec<Heightmap>,
+    /// Counts the changes, to tell whether the cached packet is outdated.
+    version: u64,
 }
 
-impl Inner {
-    fn new(chunk_x: i32, chunk_z: i32, min_y: i32) -> Self {
+impl Data {
+    fn new(min_y: i32, height: i32) -> Self {
         let mut sections = vec![];
-        for _ in 0..24 {
-            sections.push(ChunkSection::new());
+        for _ in 0..height / 16 {
+            sections.push(Arc::new(ChunkSection::new()));
         }
 
+        let heightmaps = HeightmapKind::ALL
+            .iter()
+            .map(|kind| Heightmap::new(*kind, min_y, height as u32))
+            .collect();
+
         Self {
-            chunk_x,
-            chunk_z,
             min_y,
             sections,
             block_entities: HashMap::new(),
+            heightmaps,
+            version: 0,
         }
     }
 
-    fn x(&self) -> i32 {
-        self.chunk_x
-    }
-
-    fn z(&self) -> i32 {
-        self.chunk_z
+    fn sections(&self) -> &Vec<Arc<ChunkSection>> {
+        &self.sections
     }
 
-    fn sections(&self) -> &Vec<ChunkSection> {
-        &self.sections
+    fn heightmap(&self, kind: HeightmapKind) -> &Heightmap {
+        self.heightmaps
+            .iter()
+            .find(|heightmap| heightmap.kind() == kind)
+            .expect("Chunk is missing a heightmap")
     }
 
     // pub fn block_entites(&self) -> Vec<&BlockEntity> {
@@ -162,36 +357,89 @@ impl Inner {
             panic!("Chunk section out of bounds for y: {}", y);
         };
 
-        section.get_block(
-            Self::to_relative(x),
-            Self::to_relative(y),
-            Self::to_relative(z),
-        )
+        let local = LocalPos::from_block(x, y, z);
+        section.get_block(local.x(), local.y(), local.z())
     }
 
     fn set_block(&mut self, x: i32, y: i32, z: i32, block: &BlockState) {
-        if let Some(info) = block.block_entity() {
-            let packed_xz = Self::pack_xz(x, z);
-            let block_entity = BlockEntity {
-                packed_xz,
-                y: y as i16,
-                r#type: info.id,
-                data: None,
-            };
-
-            self.block_entities.insert(packed_xz, block_entity);
+        let key = Self::block_entity_key(x, y, z);
+        match block.block_entity() {
+            // Changing the state of a block, e.g. rotating a chest, keeps its block entity
+            Some(info)
+                if self
+                    .block_entities
+                    .get(&key)
+                    .is_some_and(|b| b.r#type == info.id) => {}
+            Some(info) => {
+                let block_entity = BlockEntity {
+                    packed_xz: Self::pack_xz(x, z),
+                    y: y as i16,
+                    r#type: info.id,
+                    data: NbtCompound::new(),
+                };
+                self.block_entities.insert(key, block_entity);
+            }
+            None => {
+                self.block_entities.remove(&key);
+            }
         }
 
         let Some(section) = self.section_at_mut(y) else {
             panic!("Chunk section out of bounds for y: {}", y);
         };
 
-        section.set_block(
-            Self::to_relative(x),
-            Self::to_relative(y),
-            Self::to_relative(z),
-            block.state_id(),
-        );
+        let local = LocalPos::from_block(x, y, z);
+        section.set_block(local.x(), local.y(), local.z(), block.state_id());
+
+        let (sections, min_y) = (&self.sections, self.min_y);
+        let get_block = |y: i32| {
+            let index = SectionPos::from_block(x, y, z).index(min_y).unwrap();
+            let state = sections[index].get_block(local.x(), (y & 0xF) as usize, local.z());
+            BlockState::from_id(state as i32).unwrap()
+        };
+        for heightmap in &mut self.heightmaps {
+            heightmap.update(x, y, z, block, get_block);
+        }
+    }
+
+    fn set_section(&mut self, index: usize, section: ChunkSection) {
+        self.sections[index] = Arc::new(section);
+
+        let (sections, min_y) = (&self.sections, self.min_y);
+        let section_y = min_y + index as i32 * 16;
+        for local_y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let state = sections[index].get_block(x, local_y, z);
+                    if state == 0 {
+                        continue;
+                    }
+                    let block = BlockState::from_id(state as i32).unwrap();
+                    let (x, y, z) = (x as i32, section_y + local_y as i32, z as i32);
+
+                    if let Some(info) = block.block_entity() {
+                        let block_entity = BlockEntity {
+                            packed_xz: Self::pack_xz(x, z),
+                            y: y as i16,
+                            r#type: info.id,
+                            data: NbtCompound::new(),
+                        };
+                        self.block_entities
+                            .insert(Self::block_entity_key(x, y, z), block_entity);
+                    }
+
+                    let get_block = |y: i32| {
+                        let index = SectionPos::from_block(x, y, z).index(min_y).unwrap();
+                        let state =
+                            sections[index].get_block(x as usize, (y & 0xF) as usize, z as usize);
+                        BlockState::from_id(state as i32).unwrap()
+                    };
+                    for heightmap in &mut self.heightmaps {
+                        heightmap.update(x, y, z, block, get_block);
+                    }
+                }
+            }
+        }
     }
 
     fn get_biome(&self, x: i32, y: i32, z: i32) -> u16 {
@@ -199,11 +447,8 @@ impl Inner {
             panic!("Chunk section out of bounds for y: {}", y);
         };
 
-        section.get_biome(
-            Self::to_relative(x) / 4,
-            Self::to_relative(y) / 4,
-            Self::to_relative(z) / 4,
-        )
+        let local = LocalPos::from_block(x, y, z);
+        section.get_biome(local.x() / 4, local.y() / 4, local.z() / 4)
     }
 
     fn set_biome(&mut self, x: i32, y: i32, z: i32, biome: i32) {
@@ -211,27 +456,54 @@ impl Inner {
             panic!("Chunk section out of bounds for y: {}", y);
         };
 
-        section.set_biome(
-            Self::to_relative(x) / 4,
-            Self::to_relative(y) / 4,
-            Self::to_relative(z) / 4,
-            biome,
-        );
+        let local = LocalPos::from_block(x, y, z);
+        section.set_biome(local.x() / 4, local.y() / 4, local.z() / 4, biome);
     }
 
-    #[inline]
-    fn to_relative(value: i32) -> usize {
-        (value & 0x0F) as usize
+    fn get_light(
+        &self,
+        x: i32,
+        y: i32,
+        z: i32,
+        get: impl Fn(&ChunkSection, usize, usize, usize) -> u8,
+    ) -> u8 {
+        let Some(section) = self.section_at(y) else {
+            panic!("Chunk section out of bounds for y: {}", y);
+        };
+
+        let local = LocalPos::from_block(x, y, z);
+        get(section, local.x(), local.y(), local.z())
+    }
+
+    fn set_light(
+        &mut self,
+        x: i32,
+        y: i32,
+        z: i32,
+        set: impl FnOnce(&mut ChunkSection, usize, usize, usize),
+    ) {
+        let Some(section) = self.section_at_mut(y) else {
+            panic!("Chunk section out of bounds for y: {}", y);
+        };
+
+        let local = LocalPos::from_block(x, y, z);
+        set(section, local.x(), local.y(), local.z());
     }
 
     #[inline]
     fn section_at(&self, y: i32) -> Option<&ChunkSection> {
-        self.sections.get(((y - self.min_y) / 16) as usize)
+        let index = SectionPos::from_block(0, y, 0).index(self.min_y)?;
+        self.sections.get(index).map(Arc::as_ref)
     }
 
     #[inline]
     fn section_at_mut(&mut self, y: i32) -> Option<&mut ChunkSection> {
-        self.sections.get_mut(((y - self.min_y) / 16) as usize)
+        let index = SectionPos::from_block(0, y, 0).index(self.min_y)?;
+        self.sections.get_mut(index).map(Arc::make_mut)
+    }
+
+    fn block_entity_key(x: i32, y: i32, z: i32) -> BlockPosition {
+        BlockPosition::new((x & 0xF) as i64, y as i64, (z & 0xF) as i64)
     }
 
     fn pack_xz(world_x: i32, world_z: i32) -> u8 {
@@ -240,3 +512,86 @@ impl Inner {
         ((block_x << 4) | block_z) as u8
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Block;
+
+    #[test]
+    fn test_fork_copies_changed_sections() {
+        let chunk = Chunk::new(ChunkPos::ZERO, -64, 384);
+        chunk.set_block(0, 0, 0, &Block::Stone);
+        let fork = chunk.fork();
+        fork.set_block(0, 0, 0, &Block::Dirt);
+
+        assert_eq!(chunk.get_block(0, 0, 0), Block::Stone.state_id() as u16);
+        assert_eq!(fork.get_block(0, 0, 0), Block::Dirt.state_id() as u16);
+
+        let (data, fork_data) = (chunk.0.data.read(), fork.0.data.read());
+        let shared = |index: usize| Arc::ptr_eq(&data.sections[index], &fork_data.sections[index]);
+        assert!(!shared(4));
+        assert!(shared(0) && shared(5));
+        // The biomes of the changed section are still shared
+        assert!(Arc::ptr_eq(
+            &data.sections[4].biomes,
+            &fork_data.sections[4].biomes
+        ));
+    }
+
+    #[test]
+    fn test_packet_cache() {
+        let chunk = Chunk::new(ChunkPos::ZERO, -64, 384);
+        let packet = chunk.packet().unwrap();
+        assert!(Arc::ptr_eq(&packet, &chunk.packet().unwrap()));
+
+        chunk.set_block(0, 0, 0, &Block::Stone);
+        let changed = chunk.packet().unwrap();
+        assert!(!Arc::ptr_eq(&packet, &changed));
+        assert_ne!(packet.data(), changed.data());
+
+        // Forks share the packet until they change
+        let fork = chunk.fork();
+        assert!(Arc::ptr_eq(&changed, &fork.packet().unwrap()));
+        fork.set_biome(0, 0, 0, 1);
+        assert!(!Arc::ptr_eq(&changed, &fork.packet().unwrap()));
+        assert!(Arc::ptr_eq(&changed, &chunk.packet().unwrap()));
+    }
+
+    #[test]
+    fn test_packet_invalid_block_entity() {
+        let chunk = Chunk::new(ChunkPos::ZERO, -64, 384);
+        chunk.set_block(0, 0, 0, &Block::Chest);
+        // NBT strings are at most 65535 bytes long
+        let mut data = NbtCompound::new();
+        data.insert("CustomName", "a".repeat(1 << 16));
+        chunk.set_block_entity_data(0, 0, 0, data);
+        assert!(chunk.packet().is_err());
+
+        chunk.set_block(0, 0, 0, &Block::Stone);
+        assert!(chunk.packet().is_ok());
+    }
+
+    #[test]
+    fn test_light_data() {
+        let chunk = Chunk::new(ChunkPos::ZERO, -64, 384);
+        chunk.set_sky_light(0, -64, 0, 7);
+        chunk.set_block_light(0, 0, 0, 12);
+
+        let packet: ChunkDataAndUpdateLightPacket = (&chunk).into();
+        let light = packet.light;
+        // 24 sections and the sections below and above the world
+        assert_eq!(light.sky_light_mask, vec![(1 << 26) - 1]);
+        assert_eq!(light.sky_light.len(), 26);
+        assert_eq!(light.sky_light[1][0], 7);
+        assert_eq!(light.sky_light[2][0], 0xFF);
+
+        // The section at y 0 is the fifth section of the world
+        assert_eq!(light.block_light_mask, vec![1 << 5]);
+        assert_eq!(
+            light.empty_block_light_mask,
+            vec![((1 << 26) - 1) & !(1 << 5)]
+        );
+        assert_eq!(light.block_light[0][0], 12);
+    }
+}
@@ -1,17 +1,30 @@
+use std::sync::Arc;
+
 use crate::world::palette::Palette;
 
+/// The bytes of the sky or block light of a section, half a byte for every block.
+pub(crate) const LIGHT_SIZE: usize = 2048;
 
+/// A 16x16x16 part of a chunk.
+///
+/// The palettes and light are shared between clones and copied once a clone changes them.
 #[derive(Debug, Clone)]
 pub struct ChunkSection {
-    pub block_states: Palette,
-    pub biomes: Palette,
+    pub block_states: Arc<Palette>,
+    pub biomes: Arc<Palette>,
+    /// The sky light of every block, or `None` if no level was set.
+    pub sky_light: Option<Arc<[u8; LIGHT_SIZE]>>,
+    /// The block light of every block, or `None` if no level was set.
+    pub block_light: Option<Arc<[u8; LIGHT_SIZE]>>,
 }
 
 impl ChunkSection {
     pub fn new() -> Self {
         Self {
-            block_states: Palette::blocks(),
-            biomes: Palette::biomes(),
+            block_states: Arc::new(Palette::blocks()),
+            biomes: Arc::new(Palette::biomes()),
+            sky_light: None,
+            block_light: None,
         }
     }
 
@@ -24,18 +37,56 @@ impl ChunkSection {
 
     #[inline]
     pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: i32) {
-        self.block_states.set(x, y, z, block as u16);
+        Arc::make_mut(&mut self.block_states).set(x, y, z, block as u16);
     }
 
     // Biome
 
     #[inline]
     pub fn set_biome(&mut self, x: usize, y: usize, z: usize, biome: i32) {
-        self.biomes.set(x, y, z, biome as u16);
+        Arc::make_mut(&mut self.biomes).set(x, y, z, biome as u16);
     }
 
     #[inline]
     pub fn get_biome(&self, x: usize, y: usize, z: usize) -> u16 {
         self.biomes.get(x, y, z)
     }
+
+    // Light
+
+    #[inline]
+    pub fn get_sky_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        get_light(&self.sky_light, x, y, z)
+    }
+
+    /// Sets the sky light of the block, from 0 to 15.
+    #[inline]
+    pub fn set_sky_light(&mut self, x: usize, y: usize, z: usize, level: u8) {
+        set_light(&mut self.sky_light, x, y, z, level);
+    }
+
+    #[inline]
+    pub fn get_block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        get_light(&self.block_light, x, y, z)
+    }
+
+    /// Sets the block light of the block, from 0 to 15.
+    #[inline]
+    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, level: u8) {
+        set_light(&mut self.block_light, x, y, z, level);
+    }
+}
+
+fn get_light(light: &Option<Arc<[u8; LIGHT_SIZE]>>, x: usize, y: usize, z: usize) -> u8 {
+    let index = y << 8 | z << 4 | x;
+    light
+        .as_ref()
+        .map_or(0, |light| light[index / 2] >> (index % 2 * 4) & 0xF)
+}
+
+fn set_light(light: &mut Option<Arc<[u8; LIGHT_SIZE]>>, x: usize, y: usize, z: usize, level: u8) {
+    let index = y << 8 | z << 4 | x;
+    let light = Arc::make_mut(light.get_or_insert_with(|| Arc::new([0; LIGHT_SIZE])));
+    let shift = index % 2 * 4;
+    light[index / 2] = light[index / 2] & !(0xF << shift) | (level & 0xF) << shift;
 }
@@ -0,0 +1,256 @@
+use crate::item::{DataComponent, Enchantments, ItemStack};
+use crate::util::BlockPosition;
+use crate::world::BlockState;
+
+/// The share of the break time a player must have dug, the same tolerance against latency vanilla uses.
+const BREAK_TOLERANCE: f32 = 0.7;
+
+/// The conditions a player digs a block in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DigConditions {
+    pub on_ground: bool,
+    /// Whether the eyes of the player are in water without aqua affinity on the helmet.
+    pub underwater: bool,
+}
+
+/// Returns how much of the block is broken every tick, where the block breaks at `1.0`.
+///
+/// Blocks that cannot be broken, like bedrock, make no progress.
+pub fn break_progress(
+    block: &BlockState,
+    item: Option<&ItemStack>,
+    conditions: DigConditions,
+) -> f32 {
+    let hardness = block.hardness();
+    if hardness < 0. {
+        return 0.;
+    }
+
+    let mut speed = item
+        .and_then(ItemStack::tool)
+        .map_or(1., |tool| tool.mining_speed(block));
+    if speed > 1. {
+        let efficiency = item
+            .and_then(|item| item.get(DataComponent::ENCHANTMENTS))
+            .zip(Enchantments::id("minecraft:efficiency"))
+            .map_or(0, |(enchantments, id)| enchantments.level(id));
+        if efficiency > 0 {
+            speed += (efficiency * efficiency + 1) as f32;
+        }
+    }
+    if conditions.underwater {
+        speed /= 5.;
+    }
+    if !conditions.on_ground {
+        speed /= 5.;
+    }
+
+    let divisor = if can_harvest(block, item) { 30. } else { 100. };
+    speed / hardness / divisor
+}
+
+/// Returns whether the block drops items when broken with the item.
+pub fn can_harvest(block: &BlockState, item: Option<&ItemStack>) -> bool {
+    !block.requires_tool()
+        || item
+            .and_then(ItemStack::tool)
+            .is_some_and(|tool| tool.is_correct_for_drops(block))
+}
+
+/// Returns the items a block drops when it is broken with the item and no drops are set for it.
+///
+/// Blocks drop their own item, or nothing if they need a tool that was not used. Either half of a door or a tall
+/// plant drops the item, as its placement rule removes the other half without drops.
+pub fn default_drops(block: &BlockState, item: Option<&ItemStack>) -> Vec<ItemStack> {
+    if !can_harvest(block, item) {
+        return Vec::new();
+    }
+    let Some(material) = block.item() else {
+        return Vec::new();
+    };
+
+    let amount = match block.property("type") {
+        Some("double") => 2,
+        _ => 1,
+    };
+    vec![ItemStack::new(material, amount)]
+}
+
+/// A block a player is digging.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Digging {
+    pub position: BlockPosition,
+    /// The age of the world when the player started digging.
+    pub started: i64,
+    /// The progress made every tick, see [`break_progress`].
+    pub progress: f32,
+    /// The last destroy stage sent to viewers.
+    pub stage: i8,
+}
+
+impl Digging {
+    pub fn new(position: BlockPosition, started: i64, progress: f32) -> Self {
+        Self {
+            position,
+            started,
+            progress,
+            stage: -1,
+        }
+    }
+
+    /// Returns the destroy stage at the age of the world, from `0` to `9`.
+    pub fn stage_at(&self, age: i64) -> i8 {
+        let progress = (age - self.started) as f32 * self.progress;
+        ((progress * 10.) as i8).clamp(0, 9)
+    }
+
+    /// Returns whether the block may break at the age of the world.
+    pub fn is_done_at(&self, age: i64) -> bool {
+        (age - self.started + 1) as f32 * self.progress >= BREAK_TOLERANCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityLike as _;
+    use crate::item::Material;
+    use crate::test_util::{connect_player, run_with_server};
+    use crate::world::{Block, BlockFace};
+
+    const ON_GROUND: DigConditions = DigConditions {
+        on_ground: true,
+        underwater: false,
+    };
+
+    #[test]
+    fn test_break_progress() {
+        // Stone needs a pickaxe and takes 7.5 seconds by hand
+        assert_eq!(
+            break_progress(&Block::Stone, None, ON_GROUND),
+            1. / 1.5 / 100.
+        );
+
+        let pickaxe = ItemStack::of(Material::DiamondPickaxe);
+        assert_eq!(
+            break_progress(&Block::Stone, Some(&pickaxe), ON_GROUND),
+            8. / 1.5 / 30.
+        );
+
+        let pickaxe = pickaxe.with(
+            DataComponent::ENCHANTMENTS,
+            Enchantments::new().with(Enchantments::id("minecraft:efficiency").unwrap(), 5),
+        );
+        assert_eq!(
+            break_progress(&Block::Stone, Some(&pickaxe), ON_GROUND),
+            34. / 1.5 / 30.
+        );
+
+        let in_air = DigConditions {
+            on_ground: false,
+            underwater: false,
+        };
+        assert_eq!(break_progress(&Block::Dirt, None, in_air), 0.2 / 0.5 / 30.);
+        assert_eq!(break_progress(&Block::Bedrock, None, ON_GROUND), 0.);
+    }
+
+    #[test]
+    fn test_digging() {
+        let position = BlockPosition::new(0, 0, 0);
+        let digging = Digging::new(position, 100, 0.1);
+        assert_eq!(digging.stage_at(100), 0);
+        assert_eq!(digging.stage_at(105), 5);
+        assert_eq!(digging.stage_at(200), 9);
+
+        assert!(!digging.is_done_at(105));
+        assert!(digging.is_done_at(106));
+    }
+
+    #[test]
+    fn test_default_drops() {
+        assert!(default_drops(&Block::Stone, None).is_empty());
+        let pickaxe = ItemStack::of(Material::WoodenPickaxe);
+        let drops = default_drops(&Block::Stone, Some(&pickaxe));
+        assert_eq!(drops[0].material(), Material::Stone);
+
+        let slab = Block::OakSlab.with_property("type", "double").unwrap();
+        assert_eq!(default_drops(slab, None)[0].amount(), 2);
+
+        let upper = Block::OakDoor.with_property("half", "upper").unwrap();
+        assert_eq!(default_drops(upper, None)[0].material(), Material::OakDoor);
+    }
+
+    #[test]
+    fn test_break_door() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let player = client.player.clone();
+            let world = player.world();
+            world.load_chunk((0, 0));
+            world.set_block(2, 63, 0, Block::Stone);
+            world.set_block(4, 63, 0, Block::Stone);
+
+            // Breaking either half removes the other one without dropping a second item
+            for (x, break_upper) in [(2, false), (4, true)] {
+                let lower = BlockPosition::new(x, 64, 0);
+                let upper = lower.add(0, 1, 0);
+                let (broken, other) = if break_upper {
+                    (upper, lower)
+                } else {
+                    (lower, upper)
+                };
+                let below = lower.add(0, -1, 0);
+                let cursor = (0.5, 1., 0.5);
+                assert!(world.place_block(
+                    player.clone(),
+                    below,
+                    BlockFace::Top,
+                    cursor,
+                    &Block::OakDoor
+                ));
+                assert_eq!(world.get_block_at(other).unwrap().id(), Block::OakDoor.id());
+
+                let drops = world.entities().len();
+                world.break_block(player.clone(), broken, BlockFace::Top);
+                assert!(world.get_block_at(other).unwrap().is_air());
+                assert_eq!(world.entities().len(), drops + 1);
+            }
+        });
+    }
+
+    #[test]
+    fn test_break_sunflower() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let player = client.player.clone();
+            let world = player.world();
+            world.load_chunk((0, 0));
+            world.set_block(6, 63, 0, Block::GrassBlock);
+
+            let lower = BlockPosition::new(6, 64, 0);
+            let upper = lower.add(0, 1, 0);
+            let cursor = (0.5, 1., 0.5);
+            let below = lower.add(0, -1, 0);
+            assert!(world.place_block(
+                player.clone(),
+                below,
+                BlockFace::Top,
+                cursor,
+                &Block::Sunflower
+            ));
+
+            // Breaking the top of the plant drops the sunflower once and removes the whole plant. The bundled
+            // block data has no halves for tall plants, in which case the sunflower is a single block.
+            let top = if world.get_block_at(upper).unwrap().id() == Block::Sunflower.id() {
+                upper
+            } else {
+                lower
+            };
+            let drops = world.entities().len();
+            world.break_block(player.clone(), top, BlockFace::Top);
+            assert!(world.get_block_at(lower).unwrap().is_air());
+            assert!(world.get_block_at(upper).unwrap().is_air());
+            assert_eq!(world.entities().len(), drops + 1);
+        });
+    }
+}
@@ -0,0 +1,581 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use bytes::{Buf, Bytes};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+
+use crate::protocol::{
+    decode::{DecodeError, PacketRead},
+    encode::{EncodeError, PacketWrite},
+};
+use crate::registry::{REGISTRIES, RegistryKey};
+use crate::util::ChunkPos;
+use crate::world::chunk_section::LIGHT_SIZE;
+use crate::world::schematic::{DATA_VERSION, read_decompressed};
+use crate::world::{Block, BlockState, Chunk, ChunkSection, World};
+
+/// The first bytes of every world file.
+const MAGIC: &[u8; 4] = b"CRWF";
+
+/// The version of the format [`World::save_to`] writes.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// The bit set in the light mask of a section when its sky light is stored.
+const SKY_LIGHT: u8 = 1;
+
+/// The bit set in the light mask of a section when its block light is stored.
+const BLOCK_LIGHT: u8 = 2;
+
+#[derive(thiserror::Error, Debug)]
+pub enum WorldFormatError {
+    #[error("The data is not a world file")]
+    InvalidMagic,
+    #[error("Unsupported world format version: {0}")]
+    UnsupportedVersion(u16),
+    #[error("std::io::Error: {0}")]
+    IoError(std::io::Error),
+    #[error("Failed to decode world: {0}")]
+    DecodeError(DecodeError),
+    #[error("Failed to encode world: {0}")]
+    EncodeError(EncodeError),
+    #[error("Unknown dimension type: {0}")]
+    UnknownDimension(String),
+    #[error("Invalid world data: {0}")]
+    InvalidData(&'static str),
+    #[error("The world exceeds the size limit")]
+    TooLarge,
+}
+
+/// The parts of a world file an older version saved, passed to the migration hook of
+/// [`World::load_from_with_migration`] before they are loaded.
+#[derive(Debug)]
+pub struct Migration<'a> {
+    /// The format version the world was saved with.
+    pub format_version: u16,
+    /// The data version of the game version the world was saved with.
+    pub data_version: i32,
+    /// The key of every block state used by the world, e.g. `minecraft:oak_log[axis=x]`.
+    pub blocks: &'a mut Vec<String>,
+    /// The key of every biome used by the world.
+    pub biomes: &'a mut Vec<String>,
+    pub user_data: &'a mut Vec<u8>,
+    /// The decompressed chunks as they were saved, starting with their count. The hook can rewrite them into
+    /// the layout of [`FORMAT_VERSION`] if the format changed since.
+    pub chunks: &'a mut Vec<u8>,
+}
+
+/// Writes the world in the cerium world format.
+///
+/// The format starts with the magic bytes, the format version and the data version, followed by the
+/// zlib compressed world. Blocks and biomes are stored by their keys, so a world stays loadable when the
+/// ids change between game versions, and only sections that are not empty are stored.
+pub(crate) fn save(world: &World) -> Result<Vec<u8>, WorldFormatError> {
+    let mut body = Vec::new();
+    body.write_identifier(world.name())
+        .map_err(WorldFormatError::EncodeError)?;
+    body.write_identifier(world.dimension().as_key())
+        .map_err(WorldFormatError::EncodeError)?;
+    body.write_i64(world.time())
+        .map_err(WorldFormatError::EncodeError)?;
+
+    let mut blocks = Keys::default();
+    let mut biomes = Keys::default();
+    let mut chunks = Vec::new();
+    for chunk in world.loaded_chunks() {
+        chunks.push(write_chunk(&chunk, &mut blocks, &mut biomes)?);
+    }
+
+    body.write_array(&blocks.keys, |w, key| w.write_string(key))
+        .map_err(WorldFormatError::EncodeError)?;
+    body.write_array(&biomes.keys, |w, key| w.write_string(key))
+        .map_err(WorldFormatError::EncodeError)?;
+    let user_data = world.user_data();
+    body.write_varint(user_data.len() as i32)
+        .map_err(WorldFormatError::EncodeError)?;
+    body.extend_from_slice(&user_data);
+    body.write_varint(chunks.len() as i32)
+        .map_err(WorldFormatError::EncodeError)?;
+    for chunk in chunks {
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+    bytes.extend_from_slice(&DATA_VERSION.to_be_bytes());
+    let mut encoder = ZlibEncoder::new(bytes, Compression::default());
+    encoder
+        .write_all(&body)
+        .map_err(WorldFormatError::IoError)?;
+    encoder.finish().map_err(WorldFormatError::IoError)
+}
+
+/// Reads a world written by [`save`], calling the migration hook if an older version wrote it.
+pub(crate) fn load<F>(bytes: &[u8], migrate: F) -> Result<World, WorldFormatError>
+where
+    F: FnOnce(Migration),
+{
+    let Some(header) = bytes.strip_prefix(MAGIC) else {
+        return Err(WorldFormatError::InvalidMagic);
+    };
+    let mut header = header;
+    let format_version = header.read_u16().map_err(WorldFormatError::DecodeError)?;
+    let data_version = header.read_i32().map_err(WorldFormatError::DecodeError)?;
+    if format_version > FORMAT_VERSION {
+        return Err(WorldFormatError::UnsupportedVersion(format_version));
+    }
+
+    let body = read_decompressed(ZlibDecoder::new(header))
+        .map_err(WorldFormatError::IoError)?
+        .ok_or(WorldFormatError::TooLarge)?;
+    let r = &mut Bytes::from(body);
+
+    let name = r.read_identifier().map_err(WorldFormatError::DecodeError)?;
+    let dimension = r.read_identifier().map_err(WorldFormatError::DecodeError)?;
+    let dimension = RegistryKey::of(dimension.to_string());
+    if REGISTRIES.dimension_type.get(&dimension).is_none() {
+        return Err(WorldFormatError::UnknownDimension(
+            dimension.as_key().to_string(),
+        ));
+    }
+    let time = r.read_i64().map_err(WorldFormatError::DecodeError)?;
+
+    let mut blocks = r
+        .read_array(|r| r.read_string())
+        .map_err(WorldFormatError::DecodeError)?;
+    let mut biomes = r
+        .read_array(|r| r.read_string())
+        .map_err(WorldFormatError::DecodeError)?;
+    let length = r.read_varint().map_err(WorldFormatError::DecodeError)?;
+    if length < 0 || length as usize > r.remaining() {
+        return Err(WorldFormatError::InvalidData("user data"));
+    }
+    let mut user_data = r.split_to(length as usize).to_vec();
+    let mut chunks = r.to_vec();
+
+    if format_version < FORMAT_VERSION || data_version < DATA_VERSION {
+        migrate(Migration {
+            format_version,
+            data_version,
+            blocks: &mut blocks,
+            biomes: &mut biomes,
+            user_data: &mut user_data,
+            chunks: &mut chunks,
+        });
+    }
+
+    let blocks: Vec<&'static BlockState> = blocks
+        .iter()
+        .map(|key| {
+            BlockState::from_state_key(key).unwrap_or_else(|| {
+                log::warn!("Unknown block {key} in world, replacing it with air");
+                Block::Air.default_state()
+            })
+        })
+        .collect();
+    let biomes: Vec<i32> = biomes
+        .iter()
+        .map(|key| {
+            let id = REGISTRIES.biome.get_id(&RegistryKey::of(key.as_str()));
+            id.map_or_else(
+                || {
+                    log::warn!("Unknown biome {key} in world, replacing it with the default biome");
+                    0
+                },
+                |id| id as i32,
+            )
+        })
+        .collect();
+
+    let world = World::with_name(name, &dimension);
+    world.set_time(time);
+    world.set_user_data(user_data);
+
+    let r = &mut Bytes::from(chunks);
+    let chunks = r.read_varint().map_err(WorldFormatError::DecodeError)?;
+    for _ in 0..chunks {
+        let chunk = read_chunk(r, &world, &blocks, &biomes)?;
+        world.0.chunks.write().insert(chunk.pos(), chunk);
+    }
+    Ok(world)
+}
+
+/// The keys of the blocks or biomes of a world, each stored once and referred to by its index.
+#[derive(Default)]
+struct Keys {
+    keys: Vec<String>,
+    indices: HashMap<u16, i32>,
+}
+
+impl Keys {
+    fn index(&mut self, id: u16, key: impl FnOnce() -> String) -> i32 {
+        *self.indices.entry(id).or_insert_with(|| {
+            self.keys.push(key());
+            self.keys.len() as i32 - 1
+        })
+    }
+}
+
+fn write_chunk(
+    chunk: &Chunk,
+    blocks: &mut Keys,
+    biomes: &mut Keys,
+) -> Result<Vec<u8>, WorldFormatError> {
+    let block_key = |id: u16| BlockState::from_id(id as i32).unwrap().state_key();
+    let biome_key = |id: u16| {
+        REGISTRIES
+            .biome
+            .entries()
+            .get_index(id as usize)
+            .map_or("minecraft:plains".to_string(), |(key, _)| {
+                key.as_key().to_string()
+            })
+    };
+
+    let mut bytes = Vec::new();
+    bytes
+        .write_i32(chunk.x())
+        .map_err(WorldFormatError::EncodeError)?;
+    bytes
+        .write_i32(chunk.z())
+        .map_err(WorldFormatError::EncodeError)?;
+
+    let sections = chunk.sections();
+    let stored = sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| !is_empty(section))
+        .collect::<Vec<_>>();
+    bytes
+        .write_varint(stored.len() as i32)
+        .map_err(WorldFormatError::EncodeError)?;
+    for (index, section) in stored {
+        bytes
+            .write_u8(index as u8)
+            .map_err(WorldFormatError::EncodeError)?;
+
+        let states = section_values(16, |x, y, z| section.get_block(x, y, z));
+        let states = states
+            .into_iter()
+            .map(|id| blocks.index(id, || block_key(id)))
+            .collect::<Vec<_>>();
+        write_paletted(&mut bytes, &states)?;
+
+        let section_biomes = section_values(4, |x, y, z| section.get_biome(x, y, z));
+        let section_biomes = section_biomes
+            .into_iter()
+            .map(|id| biomes.index(id, || biome_key(id)))
+            .collect::<Vec<_>>();
+        write_paletted(&mut bytes, &section_biomes)?;
+
+        let mut mask = 0;
+        if section.sky_light.is_some() {
+            mask |= SKY_LIGHT;
+        }
+        if section.block_light.is_some() {
+            mask |= BLOCK_LIGHT;
+        }
+        bytes
+            .write_u8(mask)
+            .map_err(WorldFormatError::EncodeError)?;
+        for light in [&section.sky_light, &section.block_light]
+            .into_iter()
+            .flatten()
+        {
+            bytes.extend_from_slice(&light[..]);
+        }
+    }
+
+    let block_entities = chunk.block_entites();
+    bytes
+        .write_array(&block_entities, |w, block_entity| {
+            w.write_u8(block_entity.packed_xz)?;
+            w.write_i16(block_entity.y)?;
+            w.write_nbt_compound(&block_entity.data)
+        })
+        .map_err(WorldFormatError::EncodeError)?;
+    Ok(bytes)
+}
+
+fn read_chunk(
+    r: &mut Bytes,
+    world: &World,
+    blocks: &[&'static BlockState],
+    biomes: &[i32],
+) -> Result<Chunk, WorldFormatError> {
+    let x = r.read_i32().map_err(WorldFormatError::DecodeError)?;
+    let z = r.read_i32().map_err(WorldFormatError::DecodeError)?;
+    let dimension_type = world.dimension_type();
+    let chunk = Chunk::new(
+        ChunkPos::new(x, z),
+        dimension_type.min_y,
+        dimension_type.height,
+    );
+    let sections = chunk.section_count();
+
+    let count = r.read_varint().map_err(WorldFormatError::DecodeError)?;
+    for _ in 0..count {
+        let index = r.read_u8().map_err(WorldFormatError::DecodeError)? as usize;
+        if index >= sections {
+            return Err(WorldFormatError::InvalidData("section index"));
+        }
+
+        let mut section = ChunkSection::new();
+        let states = read_paletted(r, 16 * 16 * 16)?;
+        for (i, state) in states.into_iter().enumerate() {
+            let block = blocks
+                .get(state as usize)
+                .ok_or(WorldFormatError::InvalidData("block index"))?;
+            if block.state_id() != 0 {
+                section.set_block(i % 16, i / 256, i / 16 % 16, block.state_id());
+            }
+        }
+        let section_biomes = read_paletted(r, 4 * 4 * 4)?;
+        for (i, biome) in section_biomes.into_iter().enumerate() {
+            let biome = biomes
+                .get(biome as usize)
+                .ok_or(WorldFormatError::InvalidData("biome index"))?;
+            section.set_biome(i % 4, i / 16, i / 4 % 4, *biome);
+        }
+
+        let mask = r.read_u8().map_err(WorldFormatError::DecodeError)?;
+        if mask & !(SKY_LIGHT | BLOCK_LIGHT) != 0 {
+            return Err(WorldFormatError::InvalidData("light"));
+        }
+        if mask & SKY_LIGHT != 0 {
+            section.sky_light = Some(read_light(r)?);
+        }
+        if mask & BLOCK_LIGHT != 0 {
+            section.block_light = Some(read_light(r)?);
+        }
+
+        chunk.set_section(index, section);
+    }
+
+    let block_entities = r
+        .read_array(|r| Ok((r.read_u8()?, r.read_i16()?, r.read_nbt()?.root)))
+        .map_err(WorldFormatError::DecodeError)?;
+    for (packed_xz, y, data) in block_entities {
+        let (x, z) = ((packed_xz >> 4) as i32, (packed_xz & 0xF) as i32);
+        chunk.set_block_entity_data(x, y as i32, z, data);
+    }
+    Ok(chunk)
+}
+
+fn read_light(r: &mut Bytes) -> Result<Arc<[u8; LIGHT_SIZE]>, WorldFormatError> {
+    if r.remaining() < LIGHT_SIZE {
+        return Err(WorldFormatError::InvalidData("light"));
+    }
+    let mut light = [0; LIGHT_SIZE];
+    r.copy_to_slice(&mut light);
+    Ok(Arc::new(light))
+}
+
+fn is_empty(section: &ChunkSection) -> bool {
+    section.block_states.count() == 0
+        && section.sky_light.is_none()
+        && section.block_light.is_none()
+        && section_values(4, |x, y, z| section.get_biome(x, y, z))
+            .iter()
+            .all(|biome| *biome == 0)
+}
+
+/// Returns the values of a cube in the order they are stored in a palette.
+fn section_values(dim: usize, get: impl Fn(usize, usize, usize) -> u16) -> Vec<u16> {
+    let mut values = Vec::with_capacity(dim * dim * dim);
+    for y in 0..dim {
+        for z in 0..dim {
+            for x in 0..dim {
+                values.push(get(x, y, z));
+            }
+        }
+    }
+    values
+}
+
+/// Writes the indices with a palette, packed into longs with as few bits per entry as possible.
+fn write_paletted(bytes: &mut Vec<u8>, values: &[i32]) -> Result<(), WorldFormatError> {
+    let mut palette = Vec::new();
+    let mut local = HashMap::new();
+    let indices = values
+        .iter()
+        .map(|value| {
+            *local.entry(*value).or_insert_with(|| {
+                palette.push(*value);
+                palette.len() as u64 - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    bytes
+        .write_array(&palette, |w, value| w.write_varint(*value))
+        .map_err(WorldFormatError::EncodeError)?;
+    let bits = bits_per_entry(palette.len());
+    if bits == 0 {
+        return Ok(());
+    }
+
+    let per_long = 64 / bits;
+    let longs = indices
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u64, |long, (i, index)| long | index << (i * bits)) as i64
+        })
+        .collect::<Vec<_>>();
+    bytes
+        .write_unprefixed_array(&longs, |w, long| w.write_i64(*long))
+        .map_err(WorldFormatError::EncodeError)
+}
+
+fn read_paletted(r: &mut Bytes, count: usize) -> Result<Vec<i32>, WorldFormatError> {
+    let palette = r
+        .read_array(|r| r.read_varint())
+        .map_err(WorldFormatError::DecodeError)?;
+    let bits = bits_per_entry(palette.len());
+    if bits == 0 {
+        let value = *palette
+            .first()
+            .ok_or(WorldFormatError::InvalidData("empty palette"))?;
+        return Ok(vec![value; count]);
+    }
+
+    let per_long = 64 / bits;
+    let mask = (1u64 << bits) - 1;
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count.div_ceil(per_long) {
+        let long = r.read_i64().map_err(WorldFormatError::DecodeError)? as u64;
+        for i in 0..per_long.min(count - values.len()) {
+            let index = (long >> (i * bits) & mask) as usize;
+            let value = palette
+                .get(index)
+                .ok_or(WorldFormatError::InvalidData("palette index"))?;
+            values.push(*value);
+        }
+    }
+    Ok(values)
+}
+
+fn bits_per_entry(palette: usize) -> usize {
+    match palette {
+        0 | 1 => 0,
+        len => (usize::BITS - (len - 1).leading_zeros()) as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::DimensionType;
+    use crate::util::{BlockPosition, Identifier};
+    use crate::world::heightmap::HeightmapKind;
+    use crate::world::schematic::MAX_DECOMPRESSED_SIZE;
+
+    #[test]
+    fn test_roundtrip() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::with_name(Identifier::of("arena"), &dimension);
+        world.set_time(1234);
+        world.set_user_data(b"spawns".to_vec());
+        world.load_chunk((0, 0));
+        world.load_chunk((-1, 2));
+        world.set_block(1, 64, 2, Block::Stone);
+        world.set_block(
+            -3,
+            -60,
+            40,
+            Block::OakLog.with_property("axis", "x").unwrap(),
+        );
+        world.set_block(0, 100, 0, Block::Chest);
+        let mut data = cerium_nbt::NbtCompound::new();
+        data.insert("Lock", "key");
+        world.set_block_entity_data(BlockPosition::new(0, 100, 0), data.clone());
+        world.set_biome(4, 64, 4, 3);
+        let chunk = world.get_chunk((0, 0)).unwrap();
+        chunk.set_sky_light(3, 200, 5, 15);
+        chunk.set_block_light(1, 65, 2, 7);
+        chunk.set_block_light(2, 65, 2, 12);
+
+        let bytes = world.save_to().unwrap();
+        let loaded = World::load_from(&bytes).unwrap();
+
+        assert_eq!(loaded.name(), &Identifier::of("arena"));
+        assert_eq!(loaded.time(), 1234);
+        assert_eq!(&loaded.user_data()[..], b"spawns");
+        assert_eq!(loaded.loaded_chunks().len(), 2);
+        assert_eq!(loaded.get_block(1, 64, 2).id(), Block::Stone.id());
+        assert_eq!(loaded.get_block(-3, -60, 40).property("axis"), Some("x"));
+        assert_eq!(loaded.get_biome(4, 64, 4), 3);
+        assert_eq!(
+            loaded.get_block_entity_data(BlockPosition::new(0, 100, 0)),
+            Some(data)
+        );
+        assert_eq!(
+            loaded.highest_block_y(0, 0, HeightmapKind::WorldSurface),
+            Some(100)
+        );
+        let chunk = loaded.get_chunk((0, 0)).unwrap();
+        assert_eq!(chunk.get_sky_light(3, 200, 5), 15);
+        assert_eq!(chunk.get_sky_light(4, 200, 5), 0);
+        assert_eq!(chunk.get_block_light(1, 65, 2), 7);
+        assert_eq!(chunk.get_block_light(2, 65, 2), 12);
+        assert_eq!(chunk.get_block_light(1, 64, 2), 0);
+    }
+
+    #[test]
+    fn test_migration() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+        world.set_block(0, 0, 0, Block::Stone);
+        let mut bytes = world.save_to().unwrap();
+        // Pretend an older game version saved the world
+        bytes[6..10].copy_from_slice(&(DATA_VERSION - 1).to_be_bytes());
+
+        let loaded = World::load_from_with_migration(&bytes, |migration| {
+            assert_eq!(migration.format_version, FORMAT_VERSION);
+            assert_eq!(migration.data_version, DATA_VERSION - 1);
+            assert_eq!(migration.chunks[0], 1);
+            for block in migration.blocks.iter_mut() {
+                if block == "minecraft:stone" {
+                    *block = "minecraft:dirt".to_string();
+                }
+            }
+        })
+        .unwrap();
+        assert_eq!(loaded.get_block(0, 0, 0).id(), Block::Dirt.id());
+
+        let loaded = World::load_from_with_migration(&bytes, |migration| {
+            // Drop the chunks by rewriting them as no chunks at all
+            *migration.chunks = vec![0];
+        })
+        .unwrap();
+        assert!(loaded.loaded_chunks().is_empty());
+
+        assert!(matches!(
+            World::load_from(b"nope"),
+            Err(WorldFormatError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&DATA_VERSION.to_be_bytes());
+        let mut encoder = ZlibEncoder::new(bytes, Compression::best());
+        encoder
+            .write_all(&vec![0; MAX_DECOMPRESSED_SIZE as usize + 1])
+            .unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        assert!(matches!(
+            World::load_from(&bytes),
+            Err(WorldFormatError::TooLarge)
+        ));
+    }
+}
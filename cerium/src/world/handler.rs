@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use cerium_nbt::NbtCompound;
+
+use crate::entity::{Hand, Player};
+use crate::util::BlockPosition;
+use crate::world::block::REGISTRY;
+use crate::world::palette::Palette;
+use crate::world::{BlockFace, BlockState, World};
+
+/// Custom behavior of a block.
+///
+/// Handlers are registered for every state of a block with [`World::register_block_handler`],
+/// or for a single position with [`World::set_block_handler`].
+///
+/// # Example
+/// ```no_run
+/// use cerium::world::{Block, BlockHandler, BlockInteraction, World};
+///
+/// struct Checkpoint;
+///
+/// impl BlockHandler for Checkpoint {
+///     fn on_interact(&self, interaction: &BlockInteraction) -> bool {
+///         interaction.player.send_message("Checkpoint reached!");
+///         true
+///     }
+/// }
+///
+/// # fn register(world: &World) {
+/// world.register_block_handler(Block::LightWeightedPressurePlate, Checkpoint);
+/// # }
+/// ```
+pub trait BlockHandler: Send + Sync {
+    /// Called after the block was set, and after its state changed, e.g. the age of a crop.
+    fn on_place(&self, _placement: &BlockPlacement) {}
+
+    /// Called after the block was replaced by another block.
+    fn on_destroy(&self, _destroy: &BlockDestroy) {}
+
+    /// Called when a player uses an item on the block.
+    ///
+    /// Returns whether the interaction was handled, which prevents the held item from being used.
+    fn on_interact(&self, _interaction: &BlockInteraction) -> bool {
+        false
+    }
+
+    /// Called for scheduled ticks and random ticks of the block.
+    fn on_tick(&self, _tick: &BlockTick) {}
+
+    /// Returns the data the block entity of the block starts with.
+    fn block_entity_data(&self) -> Option<NbtCompound> {
+        None
+    }
+}
+
+pub struct BlockPlacement {
+    pub world: World,
+    pub position: BlockPosition,
+    pub block: BlockState,
+    /// The player that placed the block, if any.
+    pub player: Option<Player>,
+}
+
+pub struct BlockDestroy {
+    pub world: World,
+    pub position: BlockPosition,
+    pub block: BlockState,
+    /// The player that broke the block, if any.
+    pub player: Option<Player>,
+}
+
+pub struct BlockInteraction {
+    pub world: World,
+    pub position: BlockPosition,
+    pub block: BlockState,
+    pub player: Player,
+    pub hand: Hand,
+    pub face: BlockFace,
+    /// The position on the block face that was clicked, with every coordinate in `0..=1`.
+    pub cursor: (f32, f32, f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickKind {
+    /// A tick scheduled with [`World::schedule_block_tick`].
+    Scheduled,
+    /// A tick of a randomly chosen block, see [`World::set_random_tick_speed`].
+    Random,
+}
+
+pub struct BlockTick {
+    pub world: World,
+    pub position: BlockPosition,
+    pub block: BlockState,
+    pub kind: TickKind,
+}
+
+/// The block handlers of a world.
+#[derive(Default, Clone)]
+pub(crate) struct BlockHandlers {
+    by_block: HashMap<i32, Arc<dyn BlockHandler>>,
+    by_position: HashMap<BlockPosition, Arc<dyn BlockHandler>>,
+    /// The state ids of all blocks with a handler.
+    states: HashSet<u16>,
+}
+
+impl BlockHandlers {
+    pub fn is_empty(&self) -> bool {
+        self.by_block.is_empty() && self.by_position.is_empty()
+    }
+
+    pub fn register(&mut self, block: &BlockState, handler: Arc<dyn BlockHandler>) {
+        self.by_block.insert(block.id(), handler);
+        if let Some(states) = REGISTRY.3.get(&block.id()) {
+            self.states.extend(states.iter().map(|state| *state as u16));
+        }
+    }
+
+    pub fn set(&mut self, position: BlockPosition, handler: Arc<dyn BlockHandler>) {
+        self.by_position.insert(position, handler);
+    }
+
+    pub fn remove(&mut self, position: BlockPosition) -> Option<Arc<dyn BlockHandler>> {
+        self.by_position.remove(&position)
+    }
+
+    pub fn by_block(&self, block: &BlockState) -> Option<Arc<dyn BlockHandler>> {
+        self.by_block.get(&block.id()).cloned()
+    }
+
+    /// Returns if any block of the palette has a handler registered for its block.
+    pub fn any_registered(&self, block_states: &Palette) -> bool {
+        !self.states.is_empty() && block_states.any(|state| self.states.contains(&state))
+    }
+
+    /// Returns the chunk section coordinates of every position with a handler.
+    pub fn positioned_sections(&self) -> HashSet<(i64, i64, i64)> {
+        self.by_position
+            .keys()
+            .map(|position| (position.x() >> 4, position.y() >> 4, position.z() >> 4))
+            .collect()
+    }
+
+    /// Returns the handler of the position, or the handler of the block if the position has none.
+    pub fn get(
+        &self,
+        position: BlockPosition,
+        block: &BlockState,
+    ) -> Option<Arc<dyn BlockHandler>> {
+        self.by_position
+            .get(&position)
+            .cloned()
+            .or_else(|| self.by_block(block))
+    }
+}
@@ -1,5 +1,197 @@
+use crate::world::BlockState;
+
+/// The kinds of heightmaps a [`Chunk`](crate::world::Chunk) keeps track of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightmapKind {
+    /// The highest block that is not air.
+    WorldSurface,
+    /// The highest block that blocks motion.
+    OceanFloor,
+    /// The highest block that blocks motion or contains a fluid.
+    MotionBlocking,
+    /// Like [`HeightmapKind::MotionBlocking`], but ignoring leaves.
+    MotionBlockingNoLeaves,
+}
+
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 4] = [
+        HeightmapKind::WorldSurface,
+        HeightmapKind::OceanFloor,
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::MotionBlockingNoLeaves,
+    ];
+
+    /// Returns the protocol id of the heightmap.
+    pub fn id(&self) -> i32 {
+        match self {
+            HeightmapKind::WorldSurface => 1,
+            HeightmapKind::OceanFloor => 3,
+            HeightmapKind::MotionBlocking => 4,
+            HeightmapKind::MotionBlockingNoLeaves => 5,
+        }
+    }
+
+    /// Returns whether the client needs the heightmap, which is the case for all kinds but the ocean floor.
+    pub fn is_sent_to_client(&self) -> bool {
+        !matches!(self, HeightmapKind::OceanFloor)
+    }
+
+    /// Returns whether the block counts towards the height of the heightmap.
+    pub fn is_opaque(&self, block: &BlockState) -> bool {
+        match self {
+            HeightmapKind::WorldSurface => !block.is_air(),
+            HeightmapKind::OceanFloor => block.blocks_motion(),
+            HeightmapKind::MotionBlocking => block.blocks_motion() || block.has_fluid(),
+            HeightmapKind::MotionBlockingNoLeaves => {
+                (block.blocks_motion() || block.has_fluid()) && !block.is_leaves()
+            }
+        }
+    }
+}
+
+/// The highest block of a kind for every column of a chunk.
+///
+/// Heights are stored relative to the bottom of the world, where `0` means that the column contains no such block.
 #[derive(Debug, Clone)]
 pub struct Heightmap {
-    pub kind: i32,
-    pub data: Vec<i64>,
+    kind: HeightmapKind,
+    min_y: i32,
+    height: u32,
+    heights: Box<[u16; 256]>,
+}
+
+impl Heightmap {
+    pub fn new(kind: HeightmapKind, min_y: i32, height: u32) -> Self {
+        Self {
+            kind,
+            min_y,
+            height,
+            heights: Box::new([0; 256]),
+        }
+    }
+
+    pub fn kind(&self) -> HeightmapKind {
+        self.kind
+    }
+
+    /// Returns the y coordinate of the highest block in the column, or `None` if there is none.
+    pub fn highest_block_y(&self, x: i32, z: i32) -> Option<i32> {
+        match self.heights[Self::index(x, z)] {
+            0 => None,
+            height => Some(self.min_y + height as i32 - 1),
+        }
+    }
+
+    /// Updates the column after a block was set and returns whether its height changed.
+    ///
+    /// `get_block` is used to search for the next lower block when the highest one is removed.
+    pub(crate) fn update<F>(
+        &mut self,
+        x: i32,
+        y: i32,
+        z: i32,
+        block: &BlockState,
+        get_block: F,
+    ) -> bool
+    where
+        F: Fn(i32) -> &'static BlockState,
+    {
+        let index = Self::index(x, z);
+        let first_free = self.min_y + self.heights[index] as i32;
+        if y < first_free - 1 {
+            return false;
+        }
+
+        if self.kind.is_opaque(block) {
+            if y >= first_free {
+                self.heights[index] = (y - self.min_y + 1) as u16;
+                return true;
+            }
+        } else if y == first_free - 1 {
+            let below = (self.min_y..y)
+                .rev()
+                .find(|y| self.kind.is_opaque(get_block(*y)));
+            self.heights[index] = below.map_or(0, |y| (y - self.min_y + 1) as u16);
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the heights packed into longs the way the protocol expects them.
+    pub fn data(&self) -> Vec<i64> {
+        let bits = u32::BITS - self.height.leading_zeros();
+        let per_long = 64 / bits as usize;
+
+        let mut data = vec![0i64; self.heights.len().div_ceil(per_long)];
+        for (i, height) in self.heights.iter().enumerate() {
+            let shift = (i % per_long) as u32 * bits;
+            data[i / per_long] |= (*height as i64) << shift;
+        }
+        data
+    }
+
+    fn index(x: i32, z: i32) -> usize {
+        ((z & 0xF) << 4 | (x & 0xF)) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Block;
+
+    fn air(_y: i32) -> &'static BlockState {
+        &Block::Air
+    }
+
+    #[test]
+    fn test_update() {
+        let mut heightmap = Heightmap::new(HeightmapKind::MotionBlocking, -64, 384);
+        assert_eq!(heightmap.highest_block_y(3, 5), None);
+
+        assert!(heightmap.update(3, 10, 5, &Block::Stone, air));
+        assert_eq!(heightmap.highest_block_y(3, 5), Some(10));
+
+        // Blocks below the top do not change the column
+        assert!(!heightmap.update(3, -20, 5, &Block::Stone, air));
+        assert!(!heightmap.update(3, 40, 5, &Block::Air, air));
+
+        // Removing the top searches for the next lower block
+        let below = |y| -> &'static BlockState {
+            match y {
+                -20 => &Block::Stone,
+                _ => &Block::Air,
+            }
+        };
+        assert!(heightmap.update(3, 10, 5, &Block::Air, below));
+        assert_eq!(heightmap.highest_block_y(3, 5), Some(-20));
+        assert_eq!(heightmap.highest_block_y(-13, 21), Some(-20));
+    }
+
+    #[test]
+    fn test_leaves_and_fluids() {
+        let mut heightmap = Heightmap::new(HeightmapKind::MotionBlockingNoLeaves, -64, 384);
+        assert!(heightmap.update(0, 0, 0, &Block::Water, air));
+        assert!(!heightmap.update(0, 5, 0, &Block::OakLeaves, air));
+        assert_eq!(heightmap.highest_block_y(0, 0), Some(0));
+
+        let mut heightmap = Heightmap::new(HeightmapKind::OceanFloor, -64, 384);
+        assert!(!heightmap.update(0, 0, 0, &Block::Water, air));
+        assert!(heightmap.update(0, 5, 0, &Block::OakLeaves, air));
+    }
+
+    #[test]
+    fn test_data() {
+        let mut heightmap = Heightmap::new(HeightmapKind::WorldSurface, 0, 256);
+        heightmap.update(0, 0, 0, &Block::Stone, air);
+        heightmap.update(6, 255, 0, &Block::Stone, air);
+        heightmap.update(7, 1, 0, &Block::Stone, air);
+
+        // 9 bits per entry and 7 entries per long
+        let data = heightmap.data();
+        assert_eq!(data.len(), 37);
+        assert_eq!(data[0], 1 | 256 << 54);
+        assert_eq!(data[1], 2);
+    }
 }
@@ -1,52 +1,442 @@
 pub mod heightmap;
 pub mod palette;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 mod chunk;
 pub use chunk::Chunk;
 
-mod chunk_section;
+pub(crate) mod chunk_section;
 pub use chunk_section::ChunkSection;
 
 mod block;
 pub use block::{Block, BlockFace, BlockState};
 
 mod block_entity;
-pub use block_entity::BlockEntity;
-use parking_lot::RwLock;
+pub use block_entity::{BlockEntity, SignData, SignText, SkullData};
 
-use crate::protocol::packet::{BlockUpdatePacket, WorldEventPacket};
+mod weather;
+pub use weather::Weather;
+use weather::WeatherState;
+
+mod border;
+pub use border::{BorderMode, WorldBorder};
+
+mod digging;
+pub(crate) use digging::Digging;
+pub use digging::{DigConditions, break_progress, can_harvest, default_drops};
+
+mod placement;
+use placement::PlacementRules;
+pub use placement::{PlacementRule, PlacementState, PlacementUpdate};
+
+mod transform;
+pub use transform::{Mirror, Rotation, Transform};
+
+mod schematic;
+pub use schematic::{Schematic, SchematicError};
+
+mod structure;
+pub use structure::{
+    StructureBlock, StructureEntity, StructureError, StructurePlacement, StructureTemplate,
+};
+
+mod format;
+pub use format::{FORMAT_VERSION, Migration, WorldFormatError};
+
+mod raycast;
+pub use raycast::{BlockHit, EntityHit, EntityTarget};
+
+mod handler;
+use handler::BlockHandlers;
+pub use handler::{
+    BlockDestroy, BlockHandler, BlockInteraction, BlockPlacement, BlockTick, TickKind,
+};
+
+use heightmap::HeightmapKind;
+
+use bytes::Bytes;
+use cerium_nbt::NbtCompound;
+use parking_lot::{Mutex, RwLock};
+use rand::Rng as _;
+
+use crate::protocol::packet::{
+    BlockEntityDataPacket, BlockUpdatePacket, SetTimePacket, UnloadChunkPacket, WorldEventPacket,
+};
 use crate::registry::{DimensionType, REGISTRIES, RegistryKey};
 
-use crate::entity::{Entity, Player};
-use crate::util::BlockPosition;
+use crate::entity::{Entity, EntityLike as _, EntityType, GameMode, Hand, Player};
+use crate::event::player::PlayerBorderDamageEvent;
+use crate::item::ItemStack;
+use crate::tickable::Tickable;
+use crate::util::{
+    BlockPosition, BoundingBox, ChunkPos, Identifier, Position, Viewable, broadcast_packet,
+};
+
+/// Decides which chunks a [`World`] unloads once no player views them anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkUnloadPolicy {
+    /// Chunks stay loaded forever.
+    Never,
+    /// Chunks are unloaded after nobody viewed them for the grace period.
+    Unviewed { grace_period: Duration },
+    /// Like [`ChunkUnloadPolicy::Unviewed`], but chunks within `radius` of the spawn chunk are never unloaded.
+    KeepSpawn {
+        spawn: ChunkPos,
+        radius: i32,
+        grace_period: Duration,
+    },
+}
+
+impl ChunkUnloadPolicy {
+    fn should_unload(&self, chunk: &Chunk) -> bool {
+        let grace_period = match self {
+            Self::Never => return false,
+            Self::Unviewed { grace_period } => grace_period,
+            Self::KeepSpawn {
+                spawn,
+                radius,
+                grace_period,
+            } => {
+                if chunk.pos().distance(*spawn) <= *radius {
+                    return false;
+                }
+                grace_period
+            }
+        };
+
+        chunk
+            .unviewed_since()
+            .is_some_and(|since| since.elapsed() >= *grace_period)
+    }
+}
+
+type SaveHook = Box<dyn Fn(&Chunk) + Send + Sync>;
+
+/// How many ticks pass between two checks for unused chunks.
+const UNLOAD_INTERVAL: u64 = 20;
+
+/// How many ticks pass between two time updates sent to players.
+const TIME_SYNC_INTERVAL: u64 = 20;
+
+/// How many ticks dropped items exist before they despawn, the same as in vanilla.
+const ITEM_DESPAWN_AGE: u64 = 6000;
+
+/// How many ticks pass between two border damage events of players outside of the border.
+const BORDER_DAMAGE_INTERVAL: u64 = 10;
+
+/// How many blocks may change their state because of a single placed or broken block.
+const MAX_NEIGHBOR_UPDATES: usize = 512;
+
+/// How many blocks of every chunk section get a random tick each tick, the same as in vanilla.
+const DEFAULT_RANDOM_TICK_SPEED: u32 = 3;
 
 #[derive(Clone)]
 pub struct World(Arc<Inner>);
 
 impl World {
+    /// Creates a world that is named after its dimension type.
     pub fn new(dimension: &RegistryKey<DimensionType>) -> Self {
-        Self(Arc::new(Inner::new(dimension)))
+        Self::with_name(dimension.as_key().clone(), dimension)
+    }
+
+    /// Creates a world with a custom name, which is sent to clients as the dimension name.
+    pub fn with_name(name: Identifier, dimension: &RegistryKey<DimensionType>) -> Self {
+        Self(Arc::new(Inner::new(name, dimension)))
+    }
+
+    /// Creates a copy of the world with a new name that shares the sections of its chunks with this world,
+    /// e.g. to run many instances of the same arena. Each fork needs its own name to be registered next to
+    /// the world with [`Server::register_world`](crate::Server::register_world).
+    ///
+    /// A section is only copied once either world changes it, so a fork costs little more memory than
+    /// the sections it changes. Entities, players and the save hook are not copied.
+    pub fn fork(&self, name: Identifier) -> World {
+        World(Arc::new(self.0.fork(name)))
+    }
+
+    /// Loads a world saved with [`World::save_to`].
+    ///
+    /// Loading does not touch the tick thread, so many worlds can be loaded in parallel.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use cerium::world::World;
+    /// # async fn load(bytes: Vec<u8>) -> Result<World, Box<dyn std::error::Error>> {
+    /// let world = tokio::task::spawn_blocking(move || World::load_from(&bytes)).await??;
+    /// # Ok(world)
+    /// # }
+    /// ```
+    pub fn load_from(bytes: &[u8]) -> Result<World, WorldFormatError> {
+        format::load(bytes, |_| {})
+    }
+
+    /// Loads a world like [`World::load_from`], but lets the hook upgrade worlds saved by older versions,
+    /// e.g. by renaming blocks that no longer exist.
+    pub fn load_from_with_migration<F>(bytes: &[u8], migrate: F) -> Result<World, WorldFormatError>
+    where
+        F: FnOnce(Migration),
+    {
+        format::load(bytes, migrate)
+    }
+
+    /// Saves the loaded chunks, the time and the user data into a single compressed file.
+    ///
+    /// Only sections with blocks, biomes or light are stored. Entities and players are not saved.
+    pub fn save_to(&self) -> Result<Vec<u8>, WorldFormatError> {
+        format::save(self)
+    }
+
+    /// Returns the data saved with the world, e.g. the spawn points of an arena.
+    pub fn user_data(&self) -> Bytes {
+        self.0.user_data.read().clone()
+    }
+
+    pub fn set_user_data(&self, data: impl Into<Bytes>) {
+        *self.0.user_data.write() = data.into();
+    }
+
+    pub fn name(&self) -> &Identifier {
+        &self.0.name
+    }
+
+    pub fn dimension(&self) -> &RegistryKey<DimensionType> {
+        &self.0.dimension
+    }
+
+    pub fn dimension_type(&self) -> &DimensionType {
+        &self.0.dimension_type
+    }
+
+    pub fn get_chunk(&self, pos: impl Into<ChunkPos>) -> Option<Chunk> {
+        self.0.get_chunk(pos.into())
+    }
+
+    pub fn load_chunk(&self, pos: impl Into<ChunkPos>) -> Chunk {
+        self.0.load_chunk(pos.into())
+    }
+
+    /// Returns the chunk at the position, loading it if needed, and adds the player as a viewer.
+    ///
+    /// Both happen at once, so the chunk cannot be unloaded as unused in between.
+    pub(crate) fn view_chunk(&self, pos: ChunkPos, player: Option<Player>) -> Chunk {
+        self.0.view_chunk(pos, player)
+    }
+
+    /// Unloads a chunk, even if players still view it.
+    ///
+    /// The save hook is called before the chunk is dropped.
+    pub fn unload_chunk(&self, pos: impl Into<ChunkPos>) -> Option<Chunk> {
+        self.0.unload_chunk(pos.into())
+    }
+
+    /// Returns all currently loaded chunks.
+    pub fn loaded_chunks(&self) -> Vec<Chunk> {
+        self.0.loaded_chunks()
+    }
+
+    pub fn unload_policy(&self) -> ChunkUnloadPolicy {
+        *self.0.unload_policy.read()
+    }
+
+    /// Sets which chunks are unloaded automatically. Defaults to [`ChunkUnloadPolicy::Never`].
+    pub fn set_unload_policy(&self, policy: ChunkUnloadPolicy) {
+        *self.0.unload_policy.write() = policy;
     }
 
-    pub fn get_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<Chunk> {
-        self.0.get_chunk(chunk_x, chunk_z)
+    /// Sets a hook that is called with every chunk right before it is unloaded.
+    pub fn set_save_hook<F>(&self, hook: F)
+    where
+        F: Fn(&Chunk) + Send + Sync + 'static,
+    {
+        *self.0.save_hook.write() = Some(Box::new(hook));
+    }
+
+    pub(crate) fn add_player(&self, player: Player) {
+        let mut players = self.0.players.write();
+        if !players.contains(&player) {
+            players.push(player);
+        }
+    }
+
+    pub(crate) fn remove_player(&self, player: &Player) {
+        self.0.players.write().retain(|other| other != player);
+    }
+
+    /// Sends the time of day as seen by the player.
+    pub(crate) fn send_time(&self, player: &Player) {
+        player.send_packet(&self.0.time_packet(player));
+    }
+
+    /// Sends the current time, weather and border to a player that just entered the world.
+    pub(crate) fn send_environment(&self, player: &Player) {
+        self.send_time(player);
+        for packet in self.0.weather.lock().packets() {
+            player.send_packet(&packet);
+        }
+        player.send_packet(&self.0.border.lock().initialize_packet());
+    }
+
+    /// Returns the players that are currently in this world.
+    pub fn players(&self) -> Vec<Player> {
+        self.0.players.read().clone()
+    }
+
+    // ===== Time & Weather ======
+
+    /// Returns the number of ticks the world has existed for.
+    pub fn age(&self) -> i64 {
+        self.0.age.load(Ordering::Acquire) as i64
+    }
+
+    /// Returns the time of day in ticks. A full day has 24000 ticks.
+    pub fn time(&self) -> i64 {
+        self.0.time.load(Ordering::Acquire)
+    }
+
+    pub fn set_time(&self, time: i64) {
+        self.0.time.store(time, Ordering::Release);
+        self.0.broadcast_time();
+    }
+
+    pub fn is_day_cycle_enabled(&self) -> bool {
+        self.0.day_cycle.load(Ordering::Acquire)
+    }
+
+    /// Sets whether the time of day advances every tick. Enabled by default.
+    pub fn set_day_cycle_enabled(&self, enabled: bool) {
+        self.0.day_cycle.store(enabled, Ordering::Release);
+        self.0.broadcast_time();
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.0.weather.lock().weather()
+    }
+
+    /// Changes the weather until it is changed again.
+    pub fn set_weather(&self, weather: Weather) {
+        self.0.set_weather(weather, None);
+    }
+
+    /// Changes the weather, which clears up again after the given duration.
+    pub fn set_weather_for(&self, weather: Weather, duration: Duration) {
+        self.0.set_weather(weather, Some(duration));
+    }
+
+    // ===== World Border ======
+
+    pub fn border(&self) -> WorldBorder {
+        *self.0.border.lock()
     }
 
-    pub fn load_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
-        self.0.load_chunk(chunk_x, chunk_z)
+    /// Changes the world border and sends the changes to all players in the world.
+    pub fn set_border(&self, border: WorldBorder) {
+        self.0.set_border(border)
     }
 
     pub fn get_block(&self, x: i32, y: i32, z: i32) -> &BlockState {
         self.0.get_block(x, y, z)
     }
 
+    /// Returns the block at the position, or `None` if its chunk is not loaded or it is outside of the world.
+    pub fn get_block_at(&self, position: BlockPosition) -> Option<&'static BlockState> {
+        self.0.get_block_at(position)
+    }
+
     pub fn set_block<B>(&self, x: i32, y: i32, z: i32, block: B)
     where
         B: AsRef<BlockState>,
     {
-        self.0.set_block(x, y, z, block)
+        let block = block.as_ref();
+        let previous = self.0.set_block(x, y, z, block);
+        let position = BlockPosition::new(x as i64, y as i64, z as i64);
+        self.call_block_handlers(position, previous, block, None);
+    }
+
+    /// Registers a handler for every state of a block, replacing the previous handler of the block.
+    pub fn register_block_handler<B, H>(&self, block: B, handler: H)
+    where
+        B: AsRef<BlockState>,
+        H: BlockHandler + 'static,
+    {
+        self.0
+            .block_handlers
+            .write()
+            .register(block.as_ref(), Arc::new(handler));
+    }
+
+    /// Registers the rule that computes the placed state of a block, replacing the previous rule of the block.
+    pub fn register_placement_rule<B, R>(&self, block: B, rule: R)
+    where
+        B: AsRef<BlockState>,
+        R: PlacementRule + 'static,
+    {
+        self.0
+            .placement_rules
+            .write()
+            .register(block.as_ref(), Arc::new(rule));
+    }
+
+    /// Sets the handler of a single position, which takes precedence over the handler of the block.
+    ///
+    /// The handler is removed once the block at the position is replaced by another block.
+    pub fn set_block_handler<H>(&self, position: BlockPosition, handler: H)
+    where
+        H: BlockHandler + 'static,
+    {
+        if let Some(data) = handler.block_entity_data() {
+            self.set_block_entity_data(position, data);
+        }
+        self.0
+            .block_handlers
+            .write()
+            .set(position, Arc::new(handler));
+    }
+
+    /// Schedules a tick of the block at the position in `delay` ticks.
+    pub fn schedule_block_tick(&self, position: BlockPosition, delay: u64) {
+        let tick = self.0.age.load(Ordering::Acquire) + delay.max(1);
+        self.0
+            .scheduled_ticks
+            .lock()
+            .entry(tick)
+            .or_default()
+            .push(position);
+    }
+
+    pub fn random_tick_speed(&self) -> u32 {
+        self.0.random_tick_speed.load(Ordering::Acquire)
+    }
+
+    /// Sets how many random blocks of every chunk section are ticked each tick. Defaults to 3.
+    pub fn set_random_tick_speed(&self, speed: u32) {
+        self.0.random_tick_speed.store(speed, Ordering::Release);
+    }
+
+    /// Returns the data of the block entity at the position, or `None` if there is none.
+    pub fn get_block_entity_data(&self, position: BlockPosition) -> Option<NbtCompound> {
+        self.0.get_block_entity_data(position)
+    }
+
+    /// Replaces the data of the block entity at the position and sends it to the viewers of its chunk.
+    ///
+    /// Returns `false` if there is no block entity at the position.
+    pub fn set_block_entity_data(&self, position: BlockPosition, data: NbtCompound) -> bool {
+        self.0.set_block_entity_data(position, data)
+    }
+
+    /// Returns the y coordinate of the highest block of a kind at the column, or `None` if there is none
+    /// or the chunk is not loaded.
+    pub fn highest_block_y(&self, x: i32, z: i32, kind: HeightmapKind) -> Option<i32> {
+        self.get_chunk(ChunkPos::from_block(x, z))?
+            .highest_block_y(x, z, kind)
     }
 
     pub fn get_biome(&self, x: i32, y: i32, z: i32) -> u16 {
@@ -65,95 +455,922 @@ impl World {
         self.0.entities()
     }
 
+    /// Breaks a block for a player, dropping its items unless the player is in creative mode.
     pub fn break_block(&self, player: Player, position: BlockPosition, face: BlockFace) {
-        self.0.break_block(player, position, face);
+        let Some(previous) = self.0.break_block(player.clone(), position, face) else {
+            return;
+        };
+
+        if player.game_mode() != GameMode::Creative {
+            let item = player.get_item_in_hand(Hand::MainHand);
+            for drop in self.block_drops(previous, item.as_ref()) {
+                self.drop_item_at(position, drop);
+            }
+        }
+
+        self.call_block_handlers(position, previous, &Block::Air, Some(player));
+        self.update_neighbors(position);
+    }
+
+    /// Sets the items every state of a block drops instead of its [default drops](default_drops).
+    ///
+    /// The items are only dropped if the block was broken with a tool that can harvest it.
+    pub fn set_block_drops<B>(&self, block: B, drops: Vec<ItemStack>)
+    where
+        B: AsRef<BlockState>,
+    {
+        self.0
+            .block_drops
+            .write()
+            .insert(block.as_ref().id(), drops);
+    }
+
+    /// Returns the items the block drops when it is broken with the item.
+    pub fn block_drops(&self, block: &BlockState, item: Option<&ItemStack>) -> Vec<ItemStack> {
+        match self.0.block_drops.read().get(&block.id()) {
+            Some(drops) if can_harvest(block, item) => drops.clone(),
+            Some(_) => Vec::new(),
+            None => default_drops(block, item),
+        }
+    }
+
+    /// Spawns an item entity at the position and shows it to the players viewing its chunk.
+    ///
+    /// The item despawns after 5 minutes.
+    pub fn drop_item(&self, position: impl Into<Position>, item: ItemStack) -> Entity {
+        let position = position.into();
+
+        let entity = Entity::new(EntityType::Item);
+        entity.set_position(position);
+        entity.set_item(item);
+        self.spawn_entity(entity.clone());
+        self.show_to_chunk_viewers(&entity);
+
+        let despawn = self.0.age.load(Ordering::Acquire) + ITEM_DESPAWN_AGE;
+        self.0
+            .item_despawns
+            .lock()
+            .entry(despawn)
+            .or_default()
+            .push(entity.clone());
+        entity
     }
 
+    /// Shows a spawned entity to the players viewing the chunk it is in.
+    fn show_to_chunk_viewers(&self, entity: &Entity) {
+        if let Some(chunk) = self.get_chunk(entity.position()) {
+            for viewer in chunk.viewers() {
+                entity.add_viewer(viewer);
+            }
+        }
+    }
+
+    /// Shows the player the spawned entities in the chunks they view, like they were spawned after they joined.
+    pub(crate) fn show_entities(&self, player: &Player) {
+        for entity in self.entities() {
+            if let Some(chunk) = self.get_chunk(entity.position())
+                && chunk.viewers().contains(player)
+                && !entity.viewers().contains(player)
+            {
+                entity.add_viewer(player.clone());
+            }
+        }
+    }
+
+    /// Hides all spawned entities from the player, e.g. when they leave this world.
+    pub(crate) fn hide_entities(&self, player: &Player) {
+        for entity in self.entities() {
+            if entity.viewers().contains(player) {
+                entity.remove_viewer(player.clone());
+            }
+        }
+    }
+
+    /// Drops an item somewhere around the center of a block, like vanilla does for broken blocks.
+    fn drop_item_at(&self, position: BlockPosition, item: ItemStack) {
+        let mut rng = rand::thread_rng();
+        let mut offset = || 0.5 + rng.gen_range(-0.25..0.25);
+        let (x, y, z) = (offset(), offset(), offset());
+        let position = Position::new(
+            position.x() as f64 + x,
+            position.y() as f64 + y - 0.125,
+            position.z() as f64 + z,
+            0.,
+            0.,
+        );
+        self.drop_item(position, item);
+    }
+
+    /// Places a block like a player using a block item on the clicked face of another block.
+    ///
+    /// The placed state is computed by the [`PlacementRule`] of the block. Returns `false` if the block cannot be
+    /// placed, e.g. because the space is occupied by another block or an entity. The client of the player already
+    /// shows the blocks it predicted, so in that case every position the placement would have changed is resent.
     pub fn place_block(
         &self,
         player: Player,
         position: BlockPosition,
         face: BlockFace,
-        block: BlockState,
+        cursor: (f32, f32, f32),
+        block: &BlockState,
+    ) -> bool {
+        let Some(clicked) = self.get_block_at(position) else {
+            return false;
+        };
+        let reset = |positions: &[BlockPosition]| {
+            for &position in positions {
+                if let Some(block) = self.get_block_at(position) {
+                    player.send_packet(&BlockUpdatePacket {
+                        position,
+                        block_id: block.state_id(),
+                    });
+                }
+            }
+        };
+
+        let rule = self.0.placement_rules.read().get(block);
+        let can_replace = |state: &PlacementState| {
+            state.existing.is_replaceable()
+                || rule.as_ref().is_some_and(|rule| rule.can_replace(state))
+        };
+
+        let mut state = PlacementState {
+            world: self.clone(),
+            block: block.default_state(),
+            position,
+            existing: clicked,
+            face,
+            cursor,
+            replacing_clicked: true,
+            yaw: player.position().yaw(),
+        };
+        if !can_replace(&state) {
+            let (x, y, z) = face.offset();
+            state.position = position.add(x, y, z);
+            state.replacing_clicked = false;
+            let Some(existing) = self.get_block_at(state.position) else {
+                reset(&[position]);
+                return false;
+            };
+            state.existing = existing;
+
+            if !can_replace(&state) {
+                reset(&[position, state.position]);
+                return false;
+            }
+        }
+
+        let placed = match &rule {
+            Some(rule) => rule.place(&state),
+            None => BlockState::from_id(block.state_id()),
+        };
+        let Some(placed) = placed else {
+            reset(&[position, state.position]);
+            return false;
+        };
+
+        let mut blocks = vec![(state.position, placed)];
+        if let Some(rule) = &rule {
+            blocks.extend(rule.additional_blocks(&state, placed));
+        }
+        if blocks
+            .iter()
+            .any(|(position, block)| block.blocks_motion() && self.is_occupied(*position, block))
+        {
+            let mut positions = vec![position];
+            positions.extend(blocks.iter().map(|(position, _)| *position));
+            reset(&positions);
+            return false;
+        }
+
+        for (position, block) in blocks {
+            let previous = self.0.set_block_at(position, block);
+            self.0.send_block_update(position, block);
+            self.call_block_handlers(position, previous, block, Some(player.clone()));
+            self.update_neighbors(position);
+        }
+        true
+    }
+
+    /// Returns whether a player or another entity that blocks building is inside of the collision shape of
+    /// the block at the position. Items, experience orbs and projectiles do not keep blocks from being placed.
+    fn is_occupied(&self, position: BlockPosition, block: &BlockState) -> bool {
+        let shape: Vec<_> = block
+            .collision_shape()
+            .iter()
+            .map(|part| {
+                part.offset(
+                    position.x() as f64,
+                    position.y() as f64,
+                    position.z() as f64,
+                )
+            })
+            .collect();
+        let occupied = |hitbox: BoundingBox| shape.iter().any(|part| part.intersects(&hitbox));
+
+        self.players()
+            .iter()
+            .filter(|player| player.game_mode() != GameMode::Spectator)
+            .any(|player| occupied(player.bounding_box()))
+            || self
+                .entities()
+                .iter()
+                .filter(|entity| entity.r#type().blocks_building())
+                .any(|entity| occupied(entity.bounding_box()))
+    }
+
+    /// Returns the collision boxes of the blocks overlapping the area, in world coordinates.
+    ///
+    /// Blocks in chunks that are not loaded have no collision.
+    pub fn block_collisions(&self, area: &BoundingBox) -> Vec<BoundingBox> {
+        let dimension_type = &self.0.dimension_type;
+        let (min_y, height) = (dimension_type.min_y as i64, dimension_type.height as i64);
+        let min = BlockPosition::new(
+            area.min_x().floor() as i64,
+            // Fences and walls reach up into the block above them
+            (area.min_y().floor() as i64 - 1).max(min_y),
+            area.min_z().floor() as i64,
+        );
+        let max = BlockPosition::new(
+            area.max_x().ceil() as i64,
+            (area.max_y().ceil() as i64).min(min_y + height),
+            area.max_z().ceil() as i64,
+        );
+
+        let mut boxes = Vec::new();
+        for chunk in self.loaded_chunks_in(min, max) {
+            let (chunk_x, chunk_z) = (chunk.x() as i64 * 16, chunk.z() as i64 * 16);
+            for x in min.x().max(chunk_x)..max.x().min(chunk_x + 16) {
+                for y in min.y()..max.y() {
+                    for z in min.z().max(chunk_z)..max.z().min(chunk_z + 16) {
+                        let Some(block) = self.get_block_at(BlockPosition::new(x, y, z)) else {
+                            continue;
+                        };
+                        boxes.extend(
+                            block
+                                .collision_shape()
+                                .iter()
+                                .map(|part| part.offset(x as f64, y as f64, z as f64))
+                                .filter(|part| part.intersects(area)),
+                        );
+                    }
+                }
+            }
+        }
+        boxes
+    }
+
+    /// Returns the positions of the loaded chunks that contain blocks from `min` up to but excluding `max`.
+    ///
+    /// Areas spanning more chunks than are loaded are checked against the loaded chunks instead, so huge areas
+    /// do not have to visit every chunk in them.
+    fn loaded_chunks_in(&self, min: BlockPosition, max: BlockPosition) -> Vec<ChunkPos> {
+        if max.x() <= min.x() || max.z() <= min.z() {
+            return Vec::new();
+        }
+        let (min_x, max_x) = (min.x() >> 4, (max.x() - 1) >> 4);
+        let (min_z, max_z) = (min.z() >> 4, (max.z() - 1) >> 4);
+        let contains = |pos: &ChunkPos| {
+            (min_x..=max_x).contains(&(pos.x() as i64))
+                && (min_z..=max_z).contains(&(pos.z() as i64))
+        };
+
+        let chunks = self.0.chunks.read();
+        let count = (max_x - min_x + 1).saturating_mul(max_z - min_z + 1);
+        if count > chunks.len() as i64 {
+            return chunks.keys().copied().filter(contains).collect();
+        }
+        (min_x..=max_x)
+            .flat_map(|x| (min_z..=max_z).map(move |z| (x, z)))
+            .filter_map(|(x, z)| {
+                Some(ChunkPos::new(
+                    i32::try_from(x).ok()?,
+                    i32::try_from(z).ok()?,
+                ))
+            })
+            .filter(|pos| chunks.contains_key(pos))
+            .collect()
+    }
+
+    /// Lets the placement rules of the blocks around a changed block update their state.
+    fn update_neighbors(&self, position: BlockPosition) {
+        let mut changed = VecDeque::from([position]);
+        let mut updates = 0;
+
+        while let Some(position) = changed.pop_front() {
+            for face in BlockFace::ALL {
+                let (x, y, z) = face.offset();
+                let neighbor = position.add(x, y, z);
+                let Some(block) = self.get_block_at(neighbor) else {
+                    continue;
+                };
+                let Some(rule) = self.0.placement_rules.read().get(block) else {
+                    continue;
+                };
+                let Some(updated) = rule.update(&PlacementUpdate {
+                    world: self.clone(),
+                    position: neighbor,
+                    block,
+                }) else {
+                    continue;
+                };
+
+                let previous = self.0.set_block_at(neighbor, updated);
+                self.0.send_block_update(neighbor, updated);
+                self.call_block_handlers(neighbor, previous, updated, None);
+
+                updates += 1;
+                if updates >= MAX_NEIGHBOR_UPDATES {
+                    return;
+                }
+                changed.push_back(neighbor);
+            }
+        }
+    }
+
+    /// Lets the handler of the clicked block handle a player using an item on it.
+    ///
+    /// Returns whether the interaction was handled.
+    pub(crate) fn interact_block(
+        &self,
+        player: &Player,
+        position: BlockPosition,
+        hand: Hand,
+        face: BlockFace,
+        cursor: (f32, f32, f32),
+    ) -> bool {
+        let Some(block) = self.get_block_at(position) else {
+            return false;
+        };
+        let Some(handler) = self.0.block_handlers.read().get(position, block) else {
+            return false;
+        };
+
+        handler.on_interact(&BlockInteraction {
+            world: self.clone(),
+            position,
+            block: block.clone(),
+            player: player.clone(),
+            hand,
+            face,
+            cursor,
+        })
+    }
+
+    /// Calls the handlers of a block that was replaced by another block.
+    fn call_block_handlers(
+        &self,
+        position: BlockPosition,
+        previous: &BlockState,
+        block: &BlockState,
+        player: Option<Player>,
     ) {
-        self.0.place_block(player, position, face, block);
+        if previous.state_id() == block.state_id() {
+            return;
+        }
+
+        // Only the state changed, so the block keeps its handler and block entity
+        if previous.id() == block.id() {
+            let handler = self.0.block_handlers.read().get(position, block);
+            if let Some(handler) = handler {
+                handler.on_place(&BlockPlacement {
+                    world: self.clone(),
+                    position,
+                    block: block.clone(),
+                    player,
+                });
+            }
+            return;
+        }
+
+        let (destroyed, placed) = {
+            let mut handlers = self.0.block_handlers.write();
+            let destroyed = handlers
+                .remove(position)
+                .or_else(|| handlers.by_block(previous));
+            (destroyed, handlers.by_block(block))
+        };
+
+        if let Some(handler) = destroyed {
+            handler.on_destroy(&BlockDestroy {
+                world: self.clone(),
+                position,
+                block: previous.clone(),
+                player: player.clone(),
+            });
+        }
+
+        if let Some(handler) = placed {
+            if block.block_entity().is_some()
+                && let Some(data) = handler.block_entity_data()
+            {
+                self.set_block_entity_data(position, data);
+            }
+
+            handler.on_place(&BlockPlacement {
+                world: self.clone(),
+                position,
+                block: block.clone(),
+                player,
+            });
+        }
+    }
+
+    /// Runs the scheduled ticks that are due and the random ticks of blocks with a handler.
+    fn tick_blocks(&self) {
+        let age = self.0.age.load(Ordering::Acquire);
+        let due = {
+            let mut scheduled = self.0.scheduled_ticks.lock();
+            let pending = scheduled.split_off(&(age + 1));
+            std::mem::replace(&mut *scheduled, pending)
+        };
+        for position in due.into_values().flatten() {
+            self.tick_block(position, TickKind::Scheduled);
+        }
+
+        let speed = self.random_tick_speed();
+        if speed == 0 {
+            return;
+        }
+
+        // Only sections containing a block with a handler are ticked
+        let min_y = self.0.dimension_type.min_y;
+        let ticking = {
+            let handlers = self.0.block_handlers.read();
+            if handlers.is_empty() {
+                return;
+            }
+            let positioned = handlers.positioned_sections();
+
+            let mut ticking = Vec::new();
+            for chunk in self.loaded_chunks() {
+                let pos = chunk.pos();
+                for (index, section) in chunk.sections().iter().enumerate() {
+                    let y = min_y + index as i32 * 16;
+                    let key = (pos.x() as i64, (y >> 4) as i64, pos.z() as i64);
+                    if positioned.contains(&key) || handlers.any_registered(&section.block_states) {
+                        ticking.push((pos, y));
+                    }
+                }
+            }
+            ticking
+        };
+
+        let mut rng = rand::thread_rng();
+        for (pos, y) in ticking {
+            for _ in 0..speed {
+                let position = BlockPosition::new(
+                    (pos.min_block_x() + rng.gen_range(0..16)) as i64,
+                    (y + rng.gen_range(0..16)) as i64,
+                    (pos.min_block_z() + rng.gen_range(0..16)) as i64,
+                );
+                self.tick_block(position, TickKind::Random);
+            }
+        }
+    }
+
+    fn tick_block(&self, position: BlockPosition, kind: TickKind) {
+        // Scheduled ticks may point outside of the world or at chunks that were unloaded since
+        let Some(block) = self.0.get_block_at(position) else {
+            return;
+        };
+        let Some(handler) = self.0.block_handlers.read().get(position, block) else {
+            return;
+        };
+
+        handler.on_tick(&BlockTick {
+            world: self.clone(),
+            position,
+            block: block.clone(),
+            kind,
+        });
+    }
+}
+
+impl PartialEq for World {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Tickable for World {
+    fn tick(&self) {
+        self.0.tick();
+        self.tick_blocks();
     }
 }
 
 struct Inner {
+    name: Identifier,
+    dimension: RegistryKey<DimensionType>,
     dimension_type: DimensionType,
-    chunks: RwLock<HashMap<(i32, i32), Chunk>>,
+    chunks: RwLock<HashMap<ChunkPos, Chunk>>,
     entities: RwLock<Vec<Entity>>,
+    unload_policy: RwLock<ChunkUnloadPolicy>,
+    save_hook: RwLock<Option<SaveHook>>,
+    players: RwLock<Vec<Player>>,
+    age: AtomicU64,
+    time: AtomicI64,
+    day_cycle: AtomicBool,
+    weather: Mutex<WeatherState>,
+    border: Mutex<WorldBorder>,
+    placement_rules: RwLock<PlacementRules>,
+    block_drops: RwLock<HashMap<i32, Vec<ItemStack>>>,
+    block_handlers: RwLock<BlockHandlers>,
+    /// The positions of the scheduled block ticks, by the world age they are due at.
+    scheduled_ticks: Mutex<BTreeMap<u64, Vec<BlockPosition>>>,
+    random_tick_speed: AtomicU32,
+    /// The dropped items, by the world age they despawn at.
+    item_despawns: Mutex<BTreeMap<u64, Vec<Entity>>>,
+    user_data: RwLock<Bytes>,
 }
 
 impl Inner {
-    fn new(dimension: &RegistryKey<DimensionType>) -> Self {
+    fn new(name: Identifier, dimension: &RegistryKey<DimensionType>) -> Self {
         let dimension_type = REGISTRIES.dimension_type.get(dimension).unwrap().clone();
 
         Self {
+            name,
+            dimension: dimension.clone(),
             dimension_type,
             chunks: RwLock::new(HashMap::new()),
             entities: RwLock::new(Vec::new()),
+            unload_policy: RwLock::new(ChunkUnloadPolicy::Never),
+            save_hook: RwLock::new(None),
+            players: RwLock::new(Vec::new()),
+            age: AtomicU64::new(0),
+            time: AtomicI64::new(0),
+            day_cycle: AtomicBool::new(true),
+            weather: Mutex::new(WeatherState::new()),
+            border: Mutex::new(WorldBorder::new()),
+            placement_rules: RwLock::new(PlacementRules::vanilla()),
+            block_drops: RwLock::new(HashMap::new()),
+            block_handlers: RwLock::new(BlockHandlers::default()),
+            scheduled_ticks: Mutex::new(BTreeMap::new()),
+            random_tick_speed: AtomicU32::new(DEFAULT_RANDOM_TICK_SPEED),
+            item_despawns: Mutex::new(BTreeMap::new()),
+            user_data: RwLock::new(Bytes::new()),
+        }
+    }
+
+    fn fork(&self, name: Identifier) -> Self {
+        let chunks = self
+            .chunks
+            .read()
+            .iter()
+            .map(|(pos, chunk)| (*pos, chunk.fork()))
+            .collect();
+
+        Self {
+            name,
+            dimension: self.dimension.clone(),
+            dimension_type: self.dimension_type.clone(),
+            chunks: RwLock::new(chunks),
+            entities: RwLock::new(Vec::new()),
+            unload_policy: RwLock::new(*self.unload_policy.read()),
+            save_hook: RwLock::new(None),
+            players: RwLock::new(Vec::new()),
+            age: AtomicU64::new(self.age.load(Ordering::Acquire)),
+            time: AtomicI64::new(self.time.load(Ordering::Acquire)),
+            day_cycle: AtomicBool::new(self.day_cycle.load(Ordering::Acquire)),
+            weather: Mutex::new(self.weather.lock().clone()),
+            border: Mutex::new(*self.border.lock()),
+            placement_rules: RwLock::new(self.placement_rules.read().clone()),
+            block_drops: RwLock::new(self.block_drops.read().clone()),
+            block_handlers: RwLock::new(self.block_handlers.read().clone()),
+            scheduled_ticks: Mutex::new(self.scheduled_ticks.lock().clone()),
+            random_tick_speed: AtomicU32::new(self.random_tick_speed.load(Ordering::Acquire)),
+            item_despawns: Mutex::new(BTreeMap::new()),
+            user_data: RwLock::new(self.user_data.read().clone()),
         }
     }
 
-    fn get_chunk(&self, chunk_x: i32, chunk_z: i32) -> Option<Chunk> {
+    fn get_chunk(&self, pos: ChunkPos) -> Option<Chunk> {
         let chunks = self.chunks.read();
-        chunks.get(&(chunk_x, chunk_z)).cloned()
+        chunks.get(&pos).cloned()
     }
 
-    fn load_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+    fn load_chunk(&self, pos: ChunkPos) -> Chunk {
         let mut chunks = self.chunks.write();
 
-        let chunk = Chunk::new(chunk_x, chunk_z, self.dimension_type.min_y);
-        chunks.insert((chunk_x, chunk_z), chunk.clone());
+        let chunk = Chunk::new(pos, self.dimension_type.min_y, self.dimension_type.height);
+        chunks.insert(pos, chunk.clone());
 
         chunk
     }
 
+    fn view_chunk(&self, pos: ChunkPos, player: Option<Player>) -> Chunk {
+        let view = |chunk: &Chunk| {
+            if let Some(player) = player.clone() {
+                chunk.add_viewer(player);
+            }
+            chunk.clone()
+        };
+
+        if let Some(chunk) = self.chunks.read().get(&pos) {
+            return view(chunk);
+        }
+        let mut chunks = self.chunks.write();
+        let chunk = chunks.entry(pos).or_insert_with(|| {
+            Chunk::new(pos, self.dimension_type.min_y, self.dimension_type.height)
+        });
+        view(chunk)
+    }
+
+    fn unload_chunk(&self, pos: ChunkPos) -> Option<Chunk> {
+        let chunk = self.chunks.write().remove(&pos)?;
+        self.release_chunk(pos, &chunk);
+        Some(chunk)
+    }
+
+    /// Sends the viewers of a removed chunk away and saves it.
+    fn release_chunk(&self, pos: ChunkPos, chunk: &Chunk) {
+        for viewer in chunk.viewers() {
+            viewer.send_packet(&UnloadChunkPacket { chunk: pos });
+            chunk.remove_viewer(viewer);
+        }
+
+        if let Some(hook) = &*self.save_hook.read() {
+            hook(chunk);
+        }
+    }
+
+    fn loaded_chunks(&self) -> Vec<Chunk> {
+        self.chunks.read().values().cloned().collect()
+    }
+
+    fn tick(&self) {
+        let age = self.age.fetch_add(1, Ordering::AcqRel) + 1;
+        if self.day_cycle.load(Ordering::Acquire) {
+            self.time.fetch_add(1, Ordering::AcqRel);
+        }
+
+        let packets = self.weather.lock().tick();
+        for packet in &packets {
+            broadcast_packet(self.players.read().iter(), packet);
+        }
+
+        if age.is_multiple_of(TIME_SYNC_INTERVAL) {
+            self.broadcast_time();
+        }
+        if age.is_multiple_of(BORDER_DAMAGE_INTERVAL) {
+            self.fire_border_damage_events();
+        }
+        if age.is_multiple_of(UNLOAD_INTERVAL) {
+            self.unload_unused_chunks();
+        }
+        self.despawn_items(age);
+    }
+
+    fn despawn_items(&self, age: u64) {
+        let despawned = {
+            let mut despawns = self.item_despawns.lock();
+            let pending = despawns.split_off(&(age + 1));
+            std::mem::replace(&mut *despawns, pending)
+        };
+        if despawned.is_empty() {
+            return;
+        }
+
+        let despawned: Vec<Entity> = despawned.into_values().flatten().collect();
+        for entity in &despawned {
+            entity.despawn();
+        }
+        self.entities
+            .write()
+            .retain(|entity| despawned.iter().all(|item| item.id() != entity.id()));
+    }
+
+    fn time_packet(&self, player: &Player) -> SetTimePacket {
+        let world_age = self.age.load(Ordering::Acquire) as i64;
+        match player.time_override() {
+            Some(time) => SetTimePacket {
+                world_age,
+                time_of_day: time,
+                time_of_day_increasing: false,
+            },
+            None => SetTimePacket {
+                world_age,
+                time_of_day: self.time.load(Ordering::Acquire),
+                time_of_day_increasing: self.day_cycle.load(Ordering::Acquire),
+            },
+        }
+    }
+
+    fn broadcast_time(&self) {
+        for player in self.players.read().iter() {
+            player.send_packet(&self.time_packet(player));
+        }
+    }
+
+    fn set_weather(&self, weather: Weather, duration: Option<Duration>) {
+        let packets = self.weather.lock().set(weather, duration);
+        for packet in &packets {
+            broadcast_packet(self.players.read().iter(), packet);
+        }
+    }
+
+    fn set_border(&self, border: WorldBorder) {
+        let packets = {
+            let mut current = self.border.lock();
+            let packets = border.update_packets(&current);
+            *current = border;
+            packets
+        };
+
+        let players = self.players.read();
+        if let Some(packet) = &packets.center {
+            broadcast_packet(players.iter(), packet);
+        }
+        if let Some(packet) = &packets.size {
+            broadcast_packet(players.iter(), packet);
+        }
+        if let Some(packet) = &packets.lerp_size {
+            broadcast_packet(players.iter(), packet);
+        }
+        if let Some(packet) = &packets.warning_distance {
+            broadcast_packet(players.iter(), packet);
+        }
+        if let Some(packet) = &packets.warning_delay {
+            broadcast_packet(players.iter(), packet);
+        }
+    }
+
+    fn fire_border_damage_events(&self) {
+        let border = *self.border.lock();
+        if border.mode() != BorderMode::DamageEvent {
+            return;
+        }
+
+        let players = self.players.read().clone();
+        for player in players {
+            if player.invurnable()
+                || matches!(player.game_mode(), GameMode::Creative | GameMode::Spectator)
+            {
+                continue;
+            }
+            if let Some(damage) = border.damage_at(player.position()) {
+                let server = player.server().clone();
+                server
+                    .events()
+                    .fire(&mut PlayerBorderDamageEvent { player, damage });
+            }
+        }
+    }
+
+    fn unload_unused_chunks(&self) {
+        let policy = *self.unload_policy.read();
+        if policy == ChunkUnloadPolicy::Never {
+            return;
+        }
+
+        let unused = self
+            .chunks
+            .read()
+            .values()
+            .filter(|chunk| policy.should_unload(chunk))
+            .map(Chunk::pos)
+            .collect::<Vec<_>>();
+
+        for pos in unused {
+            // A player may have started viewing the chunk since it was checked
+            let removed = {
+                let mut chunks = self.chunks.write();
+                match chunks.get(&pos) {
+                    Some(chunk) if policy.should_unload(chunk) => chunks.remove(&pos),
+                    _ => None,
+                }
+            };
+            if let Some(chunk) = removed {
+                self.release_chunk(pos, &chunk);
+            }
+        }
+    }
+
+    /// Returns whether the y coordinate is between the bottom and the top of the world.
+    fn is_in_height(&self, y: i64) -> bool {
+        let (min_y, height) = (self.dimension_type.min_y, self.dimension_type.height);
+        y >= min_y as i64 && y < min_y as i64 + height as i64
+    }
+
+    fn get_block_at(&self, position: BlockPosition) -> Option<&'static BlockState> {
+        if !self.is_in_height(position.y()) {
+            return None;
+        }
+
+        let state = self.get_chunk(position.into())?.get_block(
+            position.x() as i32,
+            position.y() as i32,
+            position.z() as i32,
+        );
+        BlockState::from_id(state as i32)
+    }
+
     fn get_block(&self, x: i32, y: i32, z: i32) -> &BlockState {
-        let cx = x / 16;
-        let cz = z / 16;
+        let pos = ChunkPos::from_block(x, z);
 
-        let chunk = self.get_chunk(cx, cz).unwrap_or_else(|| {
-            panic!("Chunk ({},{}) is not loaded!", cx, cz);
+        let chunk = self.get_chunk(pos).unwrap_or_else(|| {
+            panic!("Chunk ({},{}) is not loaded!", pos.x(), pos.z());
         });
 
         BlockState::from_id(chunk.get_block(x, y, z) as i32).unwrap()
     }
 
-    fn set_block<B>(&self, x: i32, y: i32, z: i32, block: B)
+    /// Sets a block and returns the block it replaced.
+    fn set_block<B>(&self, x: i32, y: i32, z: i32, block: B) -> &'static BlockState
     where
         B: AsRef<BlockState>,
     {
-        let cx = x / 16;
-        let cz = z / 16;
+        let pos = ChunkPos::from_block(x, z);
 
-        let chunk = match self.get_chunk(cx, cz) {
+        let chunk = match self.get_chunk(pos) {
             Some(chunk) => chunk,
-            None => self.load_chunk(cx, cz),
+            None => self.load_chunk(pos),
         };
+        let previous = BlockState::from_id(chunk.get_block(x, y, z) as i32).unwrap();
         chunk.set_block(x, y, z, block.as_ref());
+        previous
+    }
+
+    fn set_block_at(&self, position: BlockPosition, block: &BlockState) -> &'static BlockState {
+        self.set_block(
+            position.x() as i32,
+            position.y() as i32,
+            position.z() as i32,
+            block,
+        )
+    }
+
+    /// Sends a changed block to the players viewing its chunk.
+    fn send_block_update(&self, position: BlockPosition, block: &BlockState) {
+        if let Some(chunk) = self.get_chunk(position.into()) {
+            chunk.send_packet_to_viewers(&BlockUpdatePacket {
+                position,
+                block_id: block.state_id(),
+            });
+        }
+    }
+
+    /// Resends whole chunks to their viewers, after too many blocks changed to send them one by one.
+    fn resend_chunks(&self, chunks: impl IntoIterator<Item = ChunkPos>) {
+        for pos in chunks {
+            let Some(chunk) = self.get_chunk(pos) else {
+                continue;
+            };
+            match chunk.packet() {
+                Ok(packet) => chunk.send_framed_packet_to_viewers(&packet),
+                Err(e) => log::error!("Failed to encode chunk {:?}: {}", pos, e),
+            }
+        }
+    }
+
+    fn get_block_entity_data(&self, position: BlockPosition) -> Option<NbtCompound> {
+        let (x, y, z) = (
+            position.x() as i32,
+            position.y() as i32,
+            position.z() as i32,
+        );
+        let block_entity = self.get_chunk(position.into())?.get_block_entity(x, y, z)?;
+        Some(block_entity.data)
+    }
+
+    fn set_block_entity_data(&self, position: BlockPosition, data: NbtCompound) -> bool {
+        let (x, y, z) = (
+            position.x() as i32,
+            position.y() as i32,
+            position.z() as i32,
+        );
+        let Some(chunk) = self.get_chunk(position.into()) else {
+            return false;
+        };
+        let Some(block_entity) = chunk.set_block_entity_data(x, y, z, data) else {
+            return false;
+        };
+
+        chunk.send_packet_to_viewers(&BlockEntityDataPacket {
+            position,
+            r#type: block_entity.r#type,
+            data: block_entity.data,
+        });
+        true
     }
 
     fn get_biome(&self, x: i32, y: i32, z: i32) -> u16 {
-        let cx = x / 16;
-        let cz = z / 16;
+        let pos = ChunkPos::from_block(x, z);
 
-        let chunk = self.get_chunk(cx, cz).unwrap_or_else(|| {
-            panic!("Chunk ({},{}) is not loaded!", cx, cz);
+        let chunk = self.get_chunk(pos).unwrap_or_else(|| {
+            panic!("Chunk ({},{}) is not loaded!", pos.x(), pos.z());
         });
 
         chunk.get_biome(x, y, z)
     }
 
     fn set_biome(&self, x: i32, y: i32, z: i32, biome: i32) {
-        let cx = x / 16;
-        let cz = z / 16;
+        let pos = ChunkPos::from_block(x, z);
 
-        let chunk = match self.get_chunk(cx, cz) {
+        let chunk = match self.get_chunk(pos) {
             Some(chunk) => chunk,
-            None => self.load_chunk(cx, cz),
+            None => self.load_chunk(pos),
         };
         chunk.set_biome(x, y, z, biome);
     }
@@ -166,85 +1383,494 @@ impl Inner {
         self.entities.read().iter().cloned().collect()
     }
 
-    fn break_block(&self, player: Player, position: BlockPosition, _face: BlockFace) {
-        // let (cx, cz) = Chunk::to_chunk_pos(position);
-        // let Some(chunk) = self.get_chunk(cx, cz) else {
-        //     return;
-        // };
-
-        let block = self.get_block(
-            position.x() as i32,
-            position.y() as i32,
-            position.z() as i32,
-        );
-        self.set_block(
-            position.x() as i32,
-            position.y() as i32,
-            position.z() as i32,
-            Block::Air,
-        );
+    /// Breaks a block and returns the block that was broken.
+    fn break_block(
+        &self,
+        player: Player,
+        position: BlockPosition,
+        _face: BlockFace,
+    ) -> Option<&'static BlockState> {
+        // Setting the block would load its chunk, or panic outside of the world height
+        self.get_block_at(position)?;
+        let block = self.set_block_at(position, &Block::Air);
+        self.send_block_update(position, &Block::Air);
 
-        // todo: should be only sent to players that are viewing the block/chunk
-        for ele in player.server().players.lock().clone() {
-            ele.send_packet(&BlockUpdatePacket {
-                position,
-                block_id: Block::Air.state_id(),
-            });
-            if ele == player {
+        let Some(chunk) = self.get_chunk(position.into()) else {
+            return Some(block);
+        };
+        for viewer in chunk.viewers() {
+            if viewer == player {
                 continue;
             }
-            ele.send_packet(&WorldEventPacket {
+            viewer.send_packet(&WorldEventPacket {
                 event: 2001,
                 position,
                 data: block.id(),
                 disable_relative_volume: false,
             });
         }
-    }
-
-    pub fn place_block(
-        &self,
-        player: Player,
-        position: BlockPosition,
-        face: BlockFace,
-        block: BlockState,
-    ) {
-        let new_position = match face {
-            BlockFace::Bottom => position.add(0, -1, 0),
-            BlockFace::East => position.add(1, 0, 0),
-            BlockFace::North => position.add(0, 0, -1),
-            BlockFace::South => position.add(0, 0, 1),
-            BlockFace::Top => position.add(0, 1, 0),
-            BlockFace::West => position.add(-1, 0, 0),
-        };
-
-        self.set_block(
-            new_position.x() as i32,
-            new_position.y() as i32,
-            new_position.z() as i32,
-            &block,
-        );
 
-        // todo: should be only sent to players that are viewing the block/chunk
-        for ele in player.server().players.lock().clone() {
-            ele.send_packet(&BlockUpdatePacket {
-                position: new_position,
-                block_id: block.state_id(),
-            });
-        }
+        Some(block)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::item::Material;
+    use crate::protocol::packet::ChunkDataAndUpdateLightPacket;
+    use crate::test_util::{connect_player, run_with_server};
 
     #[tokio::test]
     async fn test_get_block() {
-        let world = Inner::new(&DimensionType::OVERWORLD);
+        let dimension = DimensionType::OVERWORLD;
+        let world = Inner::new(dimension.as_key().clone(), &dimension);
 
-        world.load_chunk(0, 0);
+        world.load_chunk(ChunkPos::ZERO);
         world.set_block(0, 0, 0, Block::MangrovePlanks);
-        assert_eq!(world.get_block(0, 0, 0).state_id(), 26);
+        assert_eq!(
+            world.get_block(0, 0, 0).state_id(),
+            Block::MangrovePlanks.state_id()
+        );
+
+        // Positions just below zero belong to the block and chunk at -1
+        world.load_chunk(ChunkPos::new(-1, -1));
+        world.set_block(-1, 64, -1, Block::Stone);
+        let position = BlockPosition::from(Position::new(-0.5, 64.5, -0.01, 0., 0.));
+        assert_eq!(position, BlockPosition::new(-1, 64, -1));
+        let block = world.get_block_at(position).unwrap();
+        assert_eq!(block.state_id(), Block::Stone.state_id());
+    }
+
+    #[test]
+    fn test_highest_block_y() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        assert_eq!(
+            world.highest_block_y(-3, 7, HeightmapKind::WorldSurface),
+            None
+        );
+
+        world.set_block(-3, 20, 7, Block::Stone);
+        world.set_block(-3, 40, 7, Block::OakLeaves);
+        assert_eq!(
+            world.highest_block_y(-3, 7, HeightmapKind::WorldSurface),
+            Some(40)
+        );
+        assert_eq!(
+            world.highest_block_y(-3, 7, HeightmapKind::MotionBlockingNoLeaves),
+            Some(20)
+        );
+
+        world.set_block(-3, 40, 7, Block::Air);
+        assert_eq!(
+            world.highest_block_y(-3, 7, HeightmapKind::WorldSurface),
+            Some(20)
+        );
+        assert_eq!(
+            world.highest_block_y(-2, 7, HeightmapKind::WorldSurface),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nether_height() {
+        let dimension = DimensionType::THE_NETHER;
+        let world = World::new(&dimension);
+        let chunk = world.load_chunk((0, 0));
+
+        // The nether is 256 blocks high and starts at y 0
+        assert_eq!(chunk.section_count(), 16);
+        world.set_block(0, 255, 0, Block::Netherrack);
+        assert_eq!(
+            world.highest_block_y(0, 0, HeightmapKind::WorldSurface),
+            Some(255)
+        );
+
+        let packet: ChunkDataAndUpdateLightPacket = (&chunk).into();
+        assert_eq!(packet.light.sky_light_mask, vec![(1 << 18) - 1]);
+        assert_eq!(packet.light.sky_light.len(), 18);
+    }
+
+    #[test]
+    fn test_set_world() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let player = client.player.clone();
+            let old_world = player.world();
+            player.0.load_chunks();
+            let old_entity = old_world.drop_item((0.5, 64.0, 0.5), ItemStack::of(Material::Stone));
+            assert!(old_entity.viewers().contains(&player));
+
+            let dimension = DimensionType::OVERWORLD;
+            let world = server.create_world("arena", &dimension);
+            let entity = Entity::new(EntityType::Item);
+            entity.set_position(Position::new(-38.5, 64.0, 22.5, 0.0, 0.0));
+            world.spawn_entity(entity.clone());
+            let distant = Entity::new(EntityType::Item);
+            distant.set_position(Position::new(1000.0, 64.0, 1000.0, 0.0, 0.0));
+            world.spawn_entity(distant.clone());
+            player.set_world(world.clone(), (-40.5, 64.0, 20.5));
+
+            assert!(entity.viewers().contains(&player));
+            assert!(distant.viewers().is_empty());
+            assert!(old_entity.viewers().is_empty());
+
+            assert!(player.world() == world);
+            let chunk = world.get_chunk(ChunkPos::new(-3, 1)).unwrap();
+            assert!(chunk.viewers().contains(&player));
+            let old_chunk = old_world.get_chunk(ChunkPos::ZERO).unwrap();
+            assert!(old_chunk.viewers().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_block_entity_data() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let lower = BlockPosition::new(17, 10, -3);
+        let upper = BlockPosition::new(17, 11, -3);
+
+        world.set_block(17, 10, -3, Block::Chest);
+        world.set_block(17, 11, -3, Block::OakSign);
+        assert_eq!(world.get_block_entity_data(lower), Some(NbtCompound::new()));
+
+        let mut data = NbtCompound::new();
+        data.insert("CustomName", "Loot");
+        assert!(world.set_block_entity_data(lower, data.clone()));
+        assert!(world.set_block_entity_data(upper, SignData::default().try_into().unwrap()));
+        assert_eq!(world.get_block_entity_data(lower), Some(data));
+
+        world.set_block(17, 10, -3, Block::Stone);
+        assert_eq!(world.get_block_entity_data(lower), None);
+        assert!(!world.set_block_entity_data(lower, NbtCompound::new()));
+        assert!(world.get_block_entity_data(upper).is_some());
+    }
+
+    #[derive(Default)]
+    struct Calls {
+        placed: AtomicU32,
+        destroyed: AtomicU32,
+        ticked: AtomicU32,
+    }
+
+    struct CountingHandler(Arc<Calls>);
+
+    impl BlockHandler for CountingHandler {
+        fn on_place(&self, _placement: &BlockPlacement) {
+            self.0.placed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_destroy(&self, _destroy: &BlockDestroy) {
+            self.0.destroyed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_tick(&self, tick: &BlockTick) {
+            assert_eq!(tick.kind, TickKind::Scheduled);
+            self.0.ticked.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn block_entity_data(&self) -> Option<NbtCompound> {
+            let mut data = NbtCompound::new();
+            data.insert("lock", "key");
+            Some(data)
+        }
+    }
+
+    #[test]
+    fn test_block_handlers() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.set_random_tick_speed(0);
+
+        let calls = Arc::new(Calls::default());
+        world.register_block_handler(Block::Chest, CountingHandler(calls.clone()));
+
+        let position = BlockPosition::new(1, 2, 3);
+        world.set_block(1, 2, 3, Block::Chest);
+        world.set_block(1, 2, 3, Block::Chest);
+        assert_eq!(calls.placed.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            world.get_block_entity_data(position),
+            CountingHandler(calls.clone()).block_entity_data()
+        );
+
+        // A state change of the same block is no destruction
+        let east = Block::Chest.with_property("facing", "east").unwrap();
+        world.set_block(1, 2, 3, east);
+        assert_eq!(calls.placed.load(Ordering::Relaxed), 2);
+        assert_eq!(calls.destroyed.load(Ordering::Relaxed), 0);
+        assert!(world.get_block_entity_data(position).is_some());
+
+        world.schedule_block_tick(position, 2);
+        world.tick();
+        assert_eq!(calls.ticked.load(Ordering::Relaxed), 0);
+        world.tick();
+        assert_eq!(calls.ticked.load(Ordering::Relaxed), 1);
+
+        world.set_block(1, 2, 3, Block::Stone);
+        assert_eq!(calls.destroyed.load(Ordering::Relaxed), 1);
+
+        // Position handlers are removed with their block
+        world.set_block_handler(position, CountingHandler(calls.clone()));
+        world.set_block(1, 2, 3, Block::Air);
+        world.set_block(1, 2, 3, Block::Stone);
+        assert_eq!(calls.destroyed.load(Ordering::Relaxed), 2);
+        assert_eq!(calls.placed.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_interact_outside_of_height() {
+        run_with_server(async |server| {
+            let client = connect_player(&server).await;
+            let player = client.player.clone();
+            let world = player.world();
+            world.load_chunk((0, 0));
+
+            let cursor = (0.5, 0.5, 0.5);
+            for y in [10000, -10000] {
+                let position = BlockPosition::new(0, y, 0);
+                assert!(!world.interact_block(
+                    &player,
+                    position,
+                    Hand::MainHand,
+                    BlockFace::Top,
+                    cursor
+                ));
+            }
+        });
+    }
+
+    #[test]
+    fn test_scheduled_tick_outside_of_height() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.set_random_tick_speed(0);
+        world.load_chunk((0, 0));
+
+        let calls = Arc::new(Calls::default());
+        world.register_block_handler(Block::Air, CountingHandler(calls.clone()));
+        for y in [10000, -10000] {
+            world.schedule_block_tick(BlockPosition::new(0, y, 0), 1);
+        }
+        world.tick();
+        assert_eq!(calls.ticked.load(Ordering::Relaxed), 0);
+    }
+
+    struct RandomTicks(Arc<AtomicU32>);
+
+    impl BlockHandler for RandomTicks {
+        fn on_tick(&self, tick: &BlockTick) {
+            assert_eq!(tick.kind, TickKind::Random);
+            assert_eq!(tick.position.y() >> 4, 4);
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_random_ticks() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.set_random_tick_speed(5);
+
+        let ticks = Arc::new(AtomicU32::new(0));
+        world.register_block_handler(Block::Wheat, RandomTicks(ticks.clone()));
+
+        // Only the section filled with wheat is ticked, the stone one next to it is skipped
+        for x in 0..16 {
+            for y in 64..80 {
+                for z in 0..16 {
+                    world.set_block(x, y, z, Block::Wheat);
+                    world.set_block(x, y + 16, z, Block::Stone);
+                }
+            }
+        }
+        world.tick();
+        assert_eq!(ticks.load(Ordering::Relaxed), 5);
+
+        world.set_random_tick_speed(0);
+        world.tick();
+        assert_eq!(ticks.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_fork() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk(ChunkPos::ZERO);
+        world.set_block(0, 64, 0, Block::Stone);
+        world.spawn_entity(Entity::new(EntityType::Pig));
+
+        let fork = world.fork(Identifier::of("arena_1"));
+        fork.set_block(0, 64, 0, Block::Dirt);
+        fork.set_block(1, 64, 0, Block::Dirt);
+
+        assert_eq!(world.get_block(0, 64, 0).id(), Block::Stone.id());
+        assert_eq!(world.get_block(1, 64, 0).id(), Block::Air.id());
+        assert_eq!(fork.get_block(0, 64, 0).id(), Block::Dirt.id());
+        assert!(fork.entities().is_empty());
+        assert_eq!(world.entities().len(), 1);
+        assert_eq!(fork.name(), &Identifier::of("arena_1"));
+        assert_eq!(world.name(), DimensionType::OVERWORLD.as_key());
+    }
+
+    #[test]
+    fn test_block_collisions() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk(ChunkPos::ZERO);
+        world.set_block(0, 64, 0, Block::Stone);
+        let bottom_slab = Block::OakSlab.with_property("type", "bottom").unwrap();
+        world.set_block(1, 64, 0, bottom_slab);
+        world.set_block(2, 64, 0, Block::ShortGrass);
+        world.set_block(3, 63, 0, Block::OakFence);
+
+        let area = BoundingBox::new(0., 64., 0., 4., 65., 1.);
+        let boxes = world.block_collisions(&area);
+        assert_eq!(boxes.len(), 3);
+        assert!(boxes.contains(&BoundingBox::new(1., 64., 0., 2., 64.5, 1.)));
+        // The fence below reaches into the area
+        assert!(boxes.contains(&BoundingBox::new(3.375, 63., 0.375, 3.625, 64.5, 0.625)));
+
+        let pig = Entity::new(EntityType::Pig);
+        pig.set_position(Position::new(5.5, 64., 0.5, 0., 0.));
+        world.spawn_entity(pig);
+        let top_slab = Block::OakSlab.with_property("type", "top").unwrap();
+        assert!(world.is_occupied(BlockPosition::new(5, 64, 0), top_slab));
+        assert!(!world.is_occupied(BlockPosition::new(6, 64, 0), &Block::Stone));
+        assert!(!world.is_occupied(BlockPosition::new(5, 65, 0), &Block::Stone));
+        assert!(!world.is_occupied(BlockPosition::new(5, 64, 0), &Block::ShortGrass));
+
+        // Items and projectiles do not keep blocks from being placed
+        for entity_type in [
+            EntityType::Item,
+            EntityType::Arrow,
+            EntityType::ExperienceOrb,
+        ] {
+            let entity = Entity::new(entity_type);
+            entity.set_position(Position::new(7.5, 64., 0.5, 0., 0.));
+            world.spawn_entity(entity);
+        }
+        assert!(!world.is_occupied(BlockPosition::new(7, 64, 0), &Block::Stone));
+
+        // Areas reaching far outside of the world height only look at the blocks within it
+        let tall = BoundingBox::new(0., -1e12, 0., 1., 1e12, 1.);
+        assert_eq!(world.block_collisions(&tall).len(), 1);
+
+        // Areas reaching far outside of the loaded chunks only look at the loaded blocks in them
+        let wide = BoundingBox::new(-1e12, 64., 0., 1e12, 65., 1.);
+        assert_eq!(world.block_collisions(&wide).len(), 3);
+        let far = BoundingBox::new(1e12, 64., 0., 1e12 + 1., 65., 1.);
+        assert!(world.block_collisions(&far).is_empty());
+    }
+
+    #[test]
+    fn test_block_drops() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let pickaxe = ItemStack::of(Material::IronPickaxe);
+
+        let drops = world.block_drops(&Block::Stone, Some(&pickaxe));
+        assert_eq!(drops[0].material(), Material::Stone);
+
+        world.set_block_drops(Block::Stone, vec![ItemStack::of(Material::Cobblestone)]);
+        let drops = world.block_drops(&Block::Stone, Some(&pickaxe));
+        assert_eq!(drops[0].material(), Material::Cobblestone);
+        assert!(world.block_drops(&Block::Stone, None).is_empty());
+
+        let item = world.drop_item(Position::ZERO, ItemStack::of(Material::Dirt));
+        assert_eq!(item.item().material(), Material::Dirt);
+        assert_eq!(world.entities().len(), 1);
+
+        for _ in 1..ITEM_DESPAWN_AGE {
+            world.tick();
+        }
+        assert_eq!(world.entities().len(), 1);
+        world.tick();
+        assert!(world.entities().is_empty());
+    }
+
+    #[test]
+    fn test_unload_unviewed_chunks() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+
+        let saved = Arc::new(AtomicU64::new(0));
+        world.set_save_hook({
+            let saved = saved.clone();
+            move |_| {
+                saved.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        world.load_chunk(ChunkPos::new(0, 0));
+        world.load_chunk(ChunkPos::new(-5, 3));
+
+        world.tick();
+        assert_eq!(world.loaded_chunks().len(), 2);
+
+        world.set_unload_policy(ChunkUnloadPolicy::KeepSpawn {
+            spawn: ChunkPos::ZERO,
+            radius: 2,
+            grace_period: Duration::ZERO,
+        });
+        world.0.unload_unused_chunks();
+        assert_eq!(world.loaded_chunks().len(), 1);
+        assert!(world.get_chunk(ChunkPos::ZERO).is_some());
+        assert_eq!(saved.load(Ordering::Relaxed), 1);
+
+        world.set_unload_policy(ChunkUnloadPolicy::Unviewed {
+            grace_period: Duration::from_secs(60),
+        });
+        world.0.unload_unused_chunks();
+        assert_eq!(world.loaded_chunks().len(), 1);
+
+        assert!(world.unload_chunk(ChunkPos::ZERO).is_some());
+        assert!(world.loaded_chunks().is_empty());
+        assert_eq!(saved.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_day_cycle() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+
+        world.set_time(12000);
+        for _ in 0..20 {
+            world.tick();
+        }
+        assert_eq!(world.age(), 20);
+        assert_eq!(world.time(), 12020);
+
+        world.set_day_cycle_enabled(false);
+        world.tick();
+        assert_eq!(world.age(), 21);
+        assert_eq!(world.time(), 12020);
+
+        world.set_weather_for(Weather::Rain, Duration::from_millis(100));
+        assert_eq!(world.weather(), Weather::Rain);
+        world.tick();
+        world.tick();
+        assert_eq!(world.weather(), Weather::Clear);
+    }
+
+    proptest! {
+        #[test]
+        fn set_block_uses_floor_chunk(x in -4096..4096i32, y in -64..320i32, z in -4096..4096i32) {
+            let dimension = DimensionType::OVERWORLD;
+            let world = Inner::new(dimension.as_key().clone(), &dimension);
+
+            world.set_block(x, y, z, Block::Stone);
+
+            let chunks = world.chunks.read().keys().copied().collect::<Vec<_>>();
+            prop_assert_eq!(chunks, vec![ChunkPos::from_block(x, z)]);
+            prop_assert_eq!(world.get_block(x, y, z).state_id(), Block::Stone.state_id());
+            prop_assert_eq!(
+                world.get_block(x ^ 1, y, z).state_id(),
+                Block::Air.state_id()
+            );
+        }
     }
 }
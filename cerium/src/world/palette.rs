@@ -1,12 +1,21 @@
-use std::collections::{HashMap, hash_map::Entry};
+use rustc_hash::FxHashMap;
 
-#[derive(Debug, Clone)]
+/// How the entries of a [`Palette`] refer to their values.
+#[derive(Debug, Clone, PartialEq)]
 pub enum PaletteFormat {
+    /// Every entry has the same value and no data is stored.
     SingleValued { value: i32 },
+    /// The entries are indices into the values.
     Indirect { values: Vec<i32> },
+    /// The entries are the values themselves.
     Direct,
 }
 
+/// A cube of block states or biomes, stored in the format they are sent to the client in.
+///
+/// The entries are packed into longs without spanning two of them, in network byte order, so encoding a
+/// palette only copies its data. Once the values no longer fit into the entries, the palette grows to
+/// more bits per entry, which repacks the entries.
 #[derive(Debug, Clone)]
 pub struct Palette {
     dim: usize,
@@ -14,12 +23,15 @@ pub struct Palette {
     max_bpe: u8,
     direct_bpe: u8,
 
-    pub bpe: u8,
-    pub format: PaletteFormat,
-    pub values: Vec<i64>,
+    bpe: u8,
+    format: PaletteFormat,
+    data: Vec<u8>,
 
-    data: Box<[u16]>,
-    count: HashMap<u16, i32>,
+    /// The entry of each value of the palette, empty for the direct format.
+    indices: FxHashMap<u16, u64>,
+    /// How many entries refer to each value of the palette, empty for the direct format.
+    counts: Vec<u16>,
+    non_zero: i32,
 }
 
 impl Palette {
@@ -32,9 +44,6 @@ impl Palette {
     }
 
     fn empty(dim: usize, min_bpe: u8, max_bpe: u8, direct_bpe: u8) -> Self {
-        let mut count = HashMap::with_capacity(1);
-        count.insert(0, (dim * dim * dim) as i32);
-
         Self {
             dim,
             min_bpe,
@@ -43,10 +52,11 @@ impl Palette {
 
             bpe: 0,
             format: PaletteFormat::SingleValued { value: 0 },
-            values: vec![],
+            data: Vec::new(),
 
-            data: vec![0u16; dim * dim * dim].into_boxed_slice(),
-            count,
+            indices: FxHashMap::from_iter([(0, 0)]),
+            counts: vec![(dim * dim * dim) as u16],
+            non_zero: 0,
         }
     }
 
@@ -55,39 +65,80 @@ impl Palette {
         y * self.dim * self.dim + z * self.dim + x
     }
 
+    #[inline]
+    const fn len(&self) -> usize {
+        self.dim * self.dim * self.dim
+    }
+
+    #[inline]
     pub fn get(&self, x: usize, y: usize, z: usize) -> u16 {
         if x >= self.dim || y >= self.dim || z >= self.dim {
             panic!("Index out of bounds: ({}, {}, {})", x, y, z);
         }
 
-        self.data[self.index(x, y, z)]
+        self.value(self.entry(self.index(x, y, z)))
     }
 
     pub fn set(&mut self, x: usize, y: usize, z: usize, value: u16) {
+        if x >= self.dim || y >= self.dim || z >= self.dim {
+            panic!("Index out of bounds: ({}, {}, {})", x, y, z);
+        }
+
         let index = self.index(x, y, z);
-        let original = self.data[index];
+        let entry = self.entry(index);
+        let original = self.value(entry);
+        if original == value {
+            return;
+        }
 
-        if let Entry::Occupied(mut entry) = self.count.entry(original) {
-            let count = entry.get_mut();
-            *count -= 1;
-            if *count == 0 {
-                let _ = entry.remove();
-            }
+        if original != 0 {
+            self.non_zero -= 1;
+        }
+        if value != 0 {
+            self.non_zero += 1;
         }
 
-        self.data[index] = value;
-        self.count
-            .entry(value)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
+        if let Some(count) = self.counts.get_mut(entry as usize) {
+            *count -= 1;
+        }
+        let entry = self.entry_for(value);
+        if let Some(count) = self.counts.get_mut(entry as usize) {
+            *count += 1;
+        }
+        self.set_entry(index, entry);
     }
 
     /// Returns the number of non-air blocks in the palette.
     pub fn count(&self) -> i32 {
-        self.count
-            .iter()
-            .map(|(k, v)| if *k == 0 { 0 } else { *v }) // todo: add cave- and void air
-            .sum()
+        // todo: add cave- and void air
+        self.non_zero
+    }
+
+    /// Returns if any entry has a value matching the predicate.
+    pub fn any(&self, predicate: impl Fn(u16) -> bool) -> bool {
+        match &self.format {
+            PaletteFormat::SingleValued { value } => predicate(*value as u16),
+            PaletteFormat::Indirect { values } => values
+                .iter()
+                .zip(&self.counts)
+                .any(|(value, count)| *count > 0 && predicate(*value as u16)),
+            PaletteFormat::Direct => {
+                (0..self.len()).any(|index| predicate(self.entry(index) as u16))
+            }
+        }
+    }
+
+    pub fn bpe(&self) -> u8 {
+        self.bpe
+    }
+
+    pub fn format(&self) -> &PaletteFormat {
+        &self.format
+    }
+
+    /// Returns the packed entries as they are sent to the client.
+    pub fn data(&self) -> &[u8] {
+        &self.data
     }
 
     /// Calculates the required bits per entry for the given number of block states.
@@ -100,76 +151,105 @@ impl Palette {
         }
     }
 
-    pub fn compute(&self) -> (u8, PaletteFormat, Vec<i64>) {
-        let bpe = Palette::required_bpe(self.count.len() as i32) as u8;
+    #[inline]
+    fn value(&self, entry: u64) -> u16 {
+        match &self.format {
+            PaletteFormat::SingleValued { value } => *value as u16,
+            PaletteFormat::Indirect { values } => values[entry as usize] as u16,
+            PaletteFormat::Direct => entry as u16,
+        }
+    }
+
+    /// Returns the entry referring to the value, growing the palette if it is not part of it yet.
+    fn entry_for(&mut self, value: u16) -> u64 {
+        let values = match &mut self.format {
+            PaletteFormat::SingleValued { value: single } => {
+                let values = vec![*single, value as i32];
+                let bpe = self.min_bpe.max(1);
+                self.indices.insert(value, 1);
+                self.counts.push(0);
+                self.repack(bpe, PaletteFormat::Indirect { values }, |entry| entry);
+                return 1;
+            }
+            PaletteFormat::Indirect { values } => values,
+            PaletteFormat::Direct => return value as u64,
+        };
 
-        if bpe == 0 {
-            // SingleValued Format
-            return (
-                0,
-                PaletteFormat::SingleValued {
-                    value: *self.count.keys().next().unwrap_or(&0) as i32,
-                },
-                vec![],
-            );
+        if let Some(entry) = self.indices.get(&value) {
+            return *entry;
+        }
+        // Values no entry refers to anymore are replaced before the palette grows
+        if let Some(entry) = self.counts.iter().position(|count| *count == 0) {
+            self.indices.remove(&(values[entry] as u16));
+            self.indices.insert(value, entry as u64);
+            values[entry] = value as i32;
+            return entry as u64;
+        }
+
+        self.indices.insert(value, values.len() as u64);
+        values.push(value as i32);
+        self.counts.push(0);
+        let bpe = Palette::required_bpe(values.len() as i32) as u8;
+        if bpe <= self.bpe {
+            return (values.len() - 1) as u64;
         }
 
         if bpe <= self.max_bpe {
-            // Indirect Format
-            let bpe = bpe.max(self.min_bpe) as u8;
-            let palette: Box<[u16]> = self
-                .count
-                .keys()
-                .copied()
-                .collect::<Vec<u16>>()
-                .into_boxed_slice();
-            let key_to_index_map: HashMap<u16, usize> = palette
-                .iter()
-                .enumerate()
-                .map(|(index, key)| (*key, index))
-                .collect();
-            let flat_values = &self.data;
-            let values: Vec<i64> = flat_values
-                .chunks(64 / bpe as usize)
-                .map(|chunk| {
-                    let mut packed_long = 0u64;
-                    for (entry_index, &block_id) in chunk.iter().enumerate() {
-                        let palette_index = *key_to_index_map
-                            .get(&block_id)
-                            .expect("Block ID not found in palette");
-                        debug_assert!(palette_index < (1 << bpe));
-                        let bit_offset = entry_index * bpe as usize;
-                        packed_long |= (palette_index as u64) << bit_offset;
-                    }
-                    packed_long as i64
-                })
-                .collect();
-
-            return (
-                bpe,
-                PaletteFormat::Indirect {
-                    values: palette.iter().map(|v| *v as i32).collect(),
-                },
-                values,
-            );
+            let format = std::mem::replace(&mut self.format, PaletteFormat::Direct);
+            self.repack(bpe, format, |entry| entry);
+            self.counts.len() as u64 - 1
         } else {
-            // Direct Format
-            let bpe = self.direct_bpe;
-            let values: Vec<i64> = self
-                .data
-                .chunks(64 / bpe as usize)
-                .map(|chunk| {
-                    chunk.iter().enumerate().fold(0, |acc, (index, value)| {
-                        debug_assert!((1 << bpe) > *value);
-                        let packed_offset_index = (*value as i64) << (bpe as u64 * index as u64);
-                        acc | packed_offset_index
-                    })
-                })
-                .collect();
-
-            return (bpe, PaletteFormat::Direct, values);
+            let values = std::mem::take(values);
+            self.indices.clear();
+            self.counts.clear();
+            self.repack(self.direct_bpe, PaletteFormat::Direct, |entry| {
+                values[entry as usize] as u64
+            });
+            value as u64
         }
     }
+
+    /// Rewrites every entry with the bits per entry of the new format.
+    fn repack(&mut self, bpe: u8, format: PaletteFormat, f: impl Fn(u64) -> u64) {
+        let entries: Vec<u64> = (0..self.len()).map(|index| f(self.entry(index))).collect();
+
+        let per_long = 64 / bpe as usize;
+        self.bpe = bpe;
+        self.format = format;
+        self.data = vec![0; self.len().div_ceil(per_long) * 8];
+        for (index, entry) in entries.into_iter().enumerate() {
+            self.set_entry(index, entry);
+        }
+    }
+
+    #[inline]
+    fn entry(&self, index: usize) -> u64 {
+        if self.bpe == 0 {
+            return 0;
+        }
+        let (long, offset) = self.position(index);
+        let long = u64::from_be_bytes(self.data[long..long + 8].try_into().unwrap());
+        (long >> offset) & ((1 << self.bpe) - 1)
+    }
+
+    #[inline]
+    fn set_entry(&mut self, index: usize, entry: u64) {
+        if self.bpe == 0 {
+            return;
+        }
+        let (long, offset) = self.position(index);
+        let bytes: &mut [u8; 8] = (&mut self.data[long..long + 8]).try_into().unwrap();
+        let mask = ((1 << self.bpe) - 1) << offset;
+        let packed = (u64::from_be_bytes(*bytes) & !mask) | (entry << offset);
+        *bytes = packed.to_be_bytes();
+    }
+
+    /// Returns the byte offset of the long holding the entry, and the bit offset within it.
+    #[inline]
+    fn position(&self, index: usize) -> (usize, usize) {
+        let per_long = 64 / self.bpe as usize;
+        (index / per_long * 8, index % per_long * self.bpe as usize)
+    }
 }
 
 #[cfg(test)]
@@ -204,4 +284,65 @@ mod tests {
         palette.set(0, 0, 0, 0);
         assert_eq!(palette.count(), 1);
     }
+
+    #[test]
+    fn test_palette_resize() {
+        let mut palette = Palette::blocks();
+        assert_eq!(palette.format(), &PaletteFormat::SingleValued { value: 0 });
+        assert!(palette.data().is_empty());
+
+        palette.set(1, 2, 3, 7);
+        assert_eq!(palette.bpe(), 4);
+        assert_eq!(palette.data().len(), 4096 / 16 * 8);
+        assert_eq!(palette.get(1, 2, 3), 7);
+
+        for i in 0..20 {
+            palette.set(i % 16, 0, i / 16, 100 + i as u16);
+        }
+        assert_eq!(palette.bpe(), 5);
+        assert_eq!(palette.get(1, 2, 3), 7);
+        assert_eq!(palette.get(3, 0, 1), 119);
+        assert_eq!(palette.get(0, 1, 0), 0);
+        assert_eq!(palette.get(15, 0, 0), 115);
+
+        for i in 0..300 {
+            let i = i as usize;
+            palette.set(i % 16, 1 + i / 256, i / 16 % 16, 1000 + i as u16);
+        }
+        assert_eq!(palette.bpe(), 15);
+        assert_eq!(palette.format(), &PaletteFormat::Direct);
+        assert_eq!(palette.get(1, 2, 3), 7);
+        assert_eq!(palette.get(15, 0, 0), 115);
+        assert_eq!(palette.get(4, 1, 2), 1036);
+        assert_eq!(palette.count(), 1 + 20 + 300);
+    }
+
+    #[test]
+    fn test_palette_reuses_unused_values() {
+        let mut palette = Palette::blocks();
+        for value in 1..100 {
+            palette.set(0, 0, 0, value);
+        }
+        assert_eq!(palette.bpe(), 4);
+        assert_eq!(palette.get(0, 0, 0), 99);
+    }
+
+    #[test]
+    fn test_palette_any() {
+        let mut palette = Palette::blocks();
+        assert!(palette.any(|value| value == 0));
+        assert!(!palette.any(|value| value == 5));
+
+        palette.set(0, 0, 0, 5);
+        assert!(palette.any(|value| value == 5));
+        palette.set(0, 0, 0, 6);
+        assert!(!palette.any(|value| value == 5));
+
+        for i in 0..300 {
+            palette.set(i % 16, i / 256, i / 16 % 16, 1000 + i as u16);
+        }
+        assert_eq!(palette.format(), &PaletteFormat::Direct);
+        assert!(palette.any(|value| value == 1299));
+        assert!(!palette.any(|value| value == 5));
+    }
 }
@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::util::BlockPosition;
+use crate::world::block::REGISTRY;
+use crate::world::{Block, BlockFace, BlockState, World};
+
+/// Computes the state of a block that a player places.
+///
+/// Rules are registered per block with [`World::register_placement_rule`]. Blocks without a rule are placed in
+/// their default state.
+pub trait PlacementRule: Send + Sync {
+    /// Returns the state that is placed, or `None` if the block cannot be placed.
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState>;
+
+    /// Returns whether the placed block may replace the existing block, e.g. to merge two slabs.
+    fn can_replace(&self, _state: &PlacementState) -> bool {
+        false
+    }
+
+    /// Returns further blocks that are placed together with the block, e.g. the upper half of a door.
+    fn additional_blocks(
+        &self,
+        _state: &PlacementState,
+        _placed: &'static BlockState,
+    ) -> Vec<(BlockPosition, &'static BlockState)> {
+        Vec::new()
+    }
+
+    /// Returns the new state of a block after one of its neighbours changed, or `None` if it stays the same.
+    fn update(&self, _update: &PlacementUpdate) -> Option<&'static BlockState> {
+        None
+    }
+}
+
+pub struct PlacementState {
+    pub world: World,
+    /// The block in its default state.
+    pub block: &'static BlockState,
+    /// The position the block is placed at.
+    pub position: BlockPosition,
+    /// The block that is currently at the position.
+    pub existing: &'static BlockState,
+    /// The face of the block the player clicked.
+    pub face: BlockFace,
+    /// The position on the clicked face, with every coordinate in `0..=1`.
+    pub cursor: (f32, f32, f32),
+    /// Whether the block replaces the clicked block instead of being placed next to it.
+    pub replacing_clicked: bool,
+    /// The yaw of the player placing the block.
+    pub yaw: f32,
+}
+
+impl PlacementState {
+    /// Returns the horizontal direction the player is looking in.
+    pub fn facing(&self) -> BlockFace {
+        match ((self.yaw / 90. + 0.5).floor() as i32) & 3 {
+            0 => BlockFace::South,
+            1 => BlockFace::West,
+            2 => BlockFace::North,
+            _ => BlockFace::East,
+        }
+    }
+
+    /// Returns whether the player clicked the upper half of a block, which places stairs and slabs upside down.
+    pub fn clicked_upper_half(&self) -> bool {
+        self.face == BlockFace::Bottom || (self.face != BlockFace::Top && self.cursor.1 > 0.5)
+    }
+
+    /// Returns the neighbouring block in the direction of a face, or `None` if its chunk is not loaded.
+    pub fn neighbor(&self, face: BlockFace) -> Option<&'static BlockState> {
+        self.world.get_block_at(relative(self.position, face))
+    }
+}
+
+pub struct PlacementUpdate {
+    pub world: World,
+    pub position: BlockPosition,
+    pub block: &'static BlockState,
+}
+
+impl PlacementUpdate {
+    /// Returns the neighbouring block in the direction of a face, or `None` if its chunk is not loaded.
+    pub fn neighbor(&self, face: BlockFace) -> Option<&'static BlockState> {
+        self.world.get_block_at(relative(self.position, face))
+    }
+}
+
+fn relative(position: BlockPosition, face: BlockFace) -> BlockPosition {
+    let (x, y, z) = face.offset();
+    position.add(x, y, z)
+}
+
+/// Sets the `waterlogged` property if the block is placed into a water source.
+fn waterlogged(state: &PlacementState, block: &'static BlockState) -> &'static BlockState {
+    let in_water =
+        state.existing.name() == "minecraft:water" && state.existing.property("level") == Some("0");
+    block
+        .with_property("waterlogged", if in_water { "true" } else { "false" })
+        .unwrap_or(block)
+}
+
+/// Places stairs facing away from the player, upside down when the upper half of a block was clicked.
+struct StairsRule;
+
+impl PlacementRule for StairsRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        let half = if state.clicked_upper_half() {
+            "top"
+        } else {
+            "bottom"
+        };
+        let block = state
+            .block
+            .with_property("facing", state.facing().name())?
+            .with_property("half", half)?;
+        Some(waterlogged(state, block))
+    }
+}
+
+/// Places slabs in the clicked half and merges two slabs of the same kind into a double slab.
+struct SlabRule;
+
+impl PlacementRule for SlabRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        if state.existing.id() == state.block.id() {
+            return state
+                .existing
+                .with_property("type", "double")?
+                .with_property("waterlogged", "false");
+        }
+
+        let half = if state.clicked_upper_half() {
+            "top"
+        } else {
+            "bottom"
+        };
+        Some(waterlogged(state, state.block.with_property("type", half)?))
+    }
+
+    fn can_replace(&self, state: &PlacementState) -> bool {
+        let existing = state.existing;
+        if existing.id() != state.block.id() || existing.property("type") == Some("double") {
+            return false;
+        }
+        if !state.replacing_clicked {
+            return true;
+        }
+
+        let upper = state.cursor.1 > 0.5;
+        match existing.property("type") {
+            Some("bottom") => state.face == BlockFace::Top || (upper && state.face.is_horizontal()),
+            _ => state.face == BlockFace::Bottom || (!upper && state.face.is_horizontal()),
+        }
+    }
+}
+
+/// Rotates logs and other pillars along the axis of the clicked face.
+struct AxisRule;
+
+impl PlacementRule for AxisRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        let axis = match state.face {
+            BlockFace::Bottom | BlockFace::Top => "y",
+            BlockFace::North | BlockFace::South => "z",
+            BlockFace::West | BlockFace::East => "x",
+        };
+        state.block.with_property("axis", axis)
+    }
+}
+
+/// Places the lower half of a door facing away from the player, together with its upper half.
+struct DoorRule;
+
+impl DoorRule {
+    fn hinge(state: &PlacementState, facing: BlockFace) -> &'static str {
+        let (step_x, _, step_z) = facing.offset();
+        let (x, _, z) = state.cursor;
+        let left = (step_x >= 0 || z >= 0.5)
+            && (step_x <= 0 || z <= 0.5)
+            && (step_z >= 0 || x <= 0.5)
+            && (step_z <= 0 || x >= 0.5);
+        if left { "left" } else { "right" }
+    }
+}
+
+impl PlacementRule for DoorRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        let above = state.neighbor(BlockFace::Top)?;
+        let below = state.neighbor(BlockFace::Bottom)?;
+        if !above.is_replaceable() || !below.blocks_motion() {
+            return None;
+        }
+
+        let facing = state.facing();
+        state
+            .block
+            .with_property("facing", facing.name())?
+            .with_property("hinge", Self::hinge(state, facing))?
+            .with_property("half", "lower")
+    }
+
+    fn additional_blocks(
+        &self,
+        state: &PlacementState,
+        placed: &'static BlockState,
+    ) -> Vec<(BlockPosition, &'static BlockState)> {
+        let upper = placed.with_property("half", "upper").unwrap();
+        vec![(relative(state.position, BlockFace::Top), upper)]
+    }
+
+    fn update(&self, update: &PlacementUpdate) -> Option<&'static BlockState> {
+        remove_without_other_half(update)
+    }
+}
+
+/// Places the lower half of a two blocks high plant, e.g. a sunflower, together with its upper half.
+struct DoublePlantRule;
+
+impl PlacementRule for DoublePlantRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        if !state.neighbor(BlockFace::Top)?.is_replaceable() {
+            return None;
+        }
+        state.block.with_property("half", "lower")
+    }
+
+    fn additional_blocks(
+        &self,
+        state: &PlacementState,
+        placed: &'static BlockState,
+    ) -> Vec<(BlockPosition, &'static BlockState)> {
+        let upper = placed.with_property("half", "upper").unwrap();
+        vec![(relative(state.position, BlockFace::Top), upper)]
+    }
+
+    fn update(&self, update: &PlacementUpdate) -> Option<&'static BlockState> {
+        remove_without_other_half(update)
+    }
+}
+
+/// Removes either half of a two blocks high block once the other half is gone.
+fn remove_without_other_half(update: &PlacementUpdate) -> Option<&'static BlockState> {
+    let face = match update.block.property("half")? {
+        "lower" => BlockFace::Top,
+        _ => BlockFace::Bottom,
+    };
+    let other = update.neighbor(face)?;
+    if other.id() == update.block.id() {
+        return None;
+    }
+    Some(&Block::Air)
+}
+
+/// Places the foot of a bed at the clicked position and its head in the direction the player is looking.
+struct BedRule;
+
+impl BedRule {
+    fn head_face(block: &BlockState) -> Option<BlockFace> {
+        let facing = BlockFace::HORIZONTAL
+            .into_iter()
+            .find(|face| Some(face.name()) == block.property("facing"))?;
+        Some(match block.property("part")? {
+            "foot" => facing,
+            _ => facing.opposite(),
+        })
+    }
+}
+
+impl PlacementRule for BedRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        let facing = state.facing();
+        if !state.neighbor(facing)?.is_replaceable() {
+            return None;
+        }
+
+        state
+            .block
+            .with_property("facing", facing.name())?
+            .with_property("part", "foot")
+    }
+
+    fn additional_blocks(
+        &self,
+        state: &PlacementState,
+        placed: &'static BlockState,
+    ) -> Vec<(BlockPosition, &'static BlockState)> {
+        let head = placed.with_property("part", "head").unwrap();
+        vec![(relative(state.position, state.facing()), head)]
+    }
+
+    fn update(&self, update: &PlacementUpdate) -> Option<&'static BlockState> {
+        let other = update.neighbor(Self::head_face(update.block)?)?;
+        if other.id() == update.block.id() {
+            return None;
+        }
+        Some(&Block::Air)
+    }
+}
+
+/// Places torches on the floor, or on walls when the side of a block was clicked.
+struct TorchRule {
+    wall: Option<&'static BlockState>,
+}
+
+impl PlacementRule for TorchRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        match state.face {
+            BlockFace::Top => state
+                .neighbor(BlockFace::Bottom)?
+                .blocks_motion()
+                .then_some(state.block),
+            BlockFace::Bottom => None,
+            // Wall torches face away from the block they are attached to
+            face => {
+                let support = state.neighbor(face.opposite())?;
+                if !support.blocks_motion() {
+                    return None;
+                }
+                self.wall?.with_property("facing", face.name())
+            }
+        }
+    }
+}
+
+/// Connects fences to neighbouring fences, fence gates and solid blocks.
+struct FenceRule;
+
+impl FenceRule {
+    fn connections(
+        block: &'static BlockState,
+        neighbor: impl Fn(BlockFace) -> Option<&'static BlockState>,
+    ) -> Option<&'static BlockState> {
+        let mut block = block;
+        for face in BlockFace::HORIZONTAL {
+            let connects = neighbor(face).is_some_and(|neighbor| {
+                neighbor.name().ends_with("_fence")
+                    || neighbor.name().ends_with("_fence_gate")
+                    || (neighbor.blocks_motion() && !neighbor.is_leaves())
+            });
+            block = block.with_property(face.name(), if connects { "true" } else { "false" })?;
+        }
+        Some(block)
+    }
+}
+
+impl PlacementRule for FenceRule {
+    fn place(&self, state: &PlacementState) -> Option<&'static BlockState> {
+        let block = Self::connections(state.block, |face| state.neighbor(face))?;
+        Some(waterlogged(state, block))
+    }
+
+    fn update(&self, update: &PlacementUpdate) -> Option<&'static BlockState> {
+        let block = Self::connections(update.block, |face| update.neighbor(face))?;
+        (block.state_id() != update.block.state_id()).then_some(block)
+    }
+}
+
+/// The placement rules of a world.
+#[derive(Clone)]
+pub(crate) struct PlacementRules {
+    rules: HashMap<i32, Arc<dyn PlacementRule>>,
+}
+
+impl PlacementRules {
+    /// Creates the rules for stairs, slabs, pillars, doors, tall plants, beds, torches and fences.
+    pub fn vanilla() -> Self {
+        let stairs: Arc<dyn PlacementRule> = Arc::new(StairsRule);
+        let slab: Arc<dyn PlacementRule> = Arc::new(SlabRule);
+        let axis: Arc<dyn PlacementRule> = Arc::new(AxisRule);
+        let door: Arc<dyn PlacementRule> = Arc::new(DoorRule);
+        let double_plant: Arc<dyn PlacementRule> = Arc::new(DoublePlantRule);
+        let bed: Arc<dyn PlacementRule> = Arc::new(BedRule);
+        let fence: Arc<dyn PlacementRule> = Arc::new(FenceRule);
+
+        let mut rules = HashMap::new();
+        for state_id in REGISTRY.0.values() {
+            let block = BlockState::from_id(*state_id).unwrap();
+            let name = block.name();
+
+            let rule = if name.ends_with("_stairs") {
+                stairs.clone()
+            } else if name.ends_with("_slab") {
+                slab.clone()
+            } else if name.ends_with("_door") {
+                door.clone()
+            } else if matches!(block.property("half"), Some("lower" | "upper")) {
+                double_plant.clone()
+            } else if name.ends_with("_bed") {
+                bed.clone()
+            } else if name.ends_with("_fence") {
+                fence.clone()
+            } else if name.ends_with("torch") && !name.ends_with("wall_torch") {
+                let wall = name.replace("torch", "wall_torch");
+                Arc::new(TorchRule {
+                    wall: BlockState::from_key(wall),
+                })
+            } else if block.property("axis").is_some() {
+                axis.clone()
+            } else {
+                continue;
+            };
+            rules.insert(block.id(), rule);
+        }
+
+        Self { rules }
+    }
+
+    pub fn register(&mut self, block: &BlockState, rule: Arc<dyn PlacementRule>) {
+        self.rules.insert(block.id(), rule);
+    }
+
+    pub fn get(&self, block: &BlockState) -> Option<Arc<dyn PlacementRule>> {
+        self.rules.get(&block.id()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::entity::EntityLike as _;
+    use crate::item::{ItemStack, Material};
+    use crate::protocol::{
+        ProtocolState,
+        decode::PacketRead as _,
+        encode::{PacketWrite as _, packet_id},
+        packet::BlockUpdatePacket,
+    };
+    use crate::registry::DimensionType;
+    use crate::test_util::{connect_player, next_packet, run_with_server};
+
+    fn state(world: &World, block: &BlockState, face: BlockFace) -> PlacementState {
+        PlacementState {
+            world: world.clone(),
+            block: block.default_state(),
+            position: BlockPosition::new(1, 1, 1),
+            existing: &Block::Air,
+            face,
+            cursor: (0.5, 0.25, 0.5),
+            replacing_clicked: false,
+            yaw: 0.,
+        }
+    }
+
+    #[test]
+    fn test_slab() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+
+        let mut state = state(&world, &Block::OakSlab, BlockFace::Top);
+        let bottom = SlabRule.place(&state).unwrap();
+        assert_eq!(bottom.property("type"), Some("bottom"));
+
+        // Clicking the top of a bottom slab merges it into a double slab
+        state.existing = bottom;
+        state.replacing_clicked = true;
+        assert!(SlabRule.can_replace(&state));
+        let double = SlabRule.place(&state).unwrap();
+        assert_eq!(double.property("type"), Some("double"));
+
+        state.existing = double;
+        assert!(!SlabRule.can_replace(&state));
+    }
+
+    #[test]
+    fn test_axis_and_stairs() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+
+        let state = state(&world, &Block::OakLog, BlockFace::East);
+        assert_eq!(AxisRule.place(&state).unwrap().property("axis"), Some("x"));
+
+        let mut state = state;
+        state.block = Block::OakStairs.default_state();
+        state.yaw = 90.;
+        let stairs = StairsRule.place(&state).unwrap();
+        assert_eq!(stairs.property("facing"), Some("west"));
+        assert_eq!(stairs.property("half"), Some("bottom"));
+    }
+
+    #[test]
+    fn test_door() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+        world.set_block(1, 0, 1, Block::Stone);
+
+        let state = state(&world, &Block::OakDoor, BlockFace::Top);
+        let lower = DoorRule.place(&state).unwrap();
+        let blocks = DoorRule.additional_blocks(&state, lower);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].0, BlockPosition::new(1, 2, 1));
+        assert_eq!(blocks[0].1.property("half"), Some("upper"));
+
+        // A lower half without its upper half breaks
+        world.set_block(1, 1, 1, lower);
+        let update = PlacementUpdate {
+            world: world.clone(),
+            position: state.position,
+            block: lower,
+        };
+        assert!(DoorRule.update(&update).unwrap().is_air());
+
+        // Doors need a solid block below them
+        world.set_block(1, 0, 1, Block::Air);
+        assert!(DoorRule.place(&state).is_none());
+    }
+
+    #[test]
+    fn test_torch() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+        let rule = TorchRule {
+            wall: Some(Block::WallTorch.default_state()),
+        };
+
+        // The wall torch at (1, 1, 1) is attached to the block west of it
+        let wall = state(&world, &Block::Torch, BlockFace::East);
+        assert!(rule.place(&wall).is_none());
+        world.set_block(0, 1, 1, Block::Stone);
+        let torch = rule.place(&wall).unwrap();
+        assert_eq!(torch.name(), "minecraft:wall_torch");
+        assert_eq!(torch.property("facing"), Some("east"));
+
+        assert!(
+            rule.place(&state(&world, &Block::Torch, BlockFace::Bottom))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_fence_connections() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk((0, 0));
+        world.set_block(1, 1, 0, Block::Stone);
+        world.set_block(2, 1, 1, Block::OakFence);
+
+        let state = state(&world, &Block::OakFence, BlockFace::Top);
+        let fence = FenceRule.place(&state).unwrap();
+        assert_eq!(fence.property("north"), Some("true"));
+        assert_eq!(fence.property("east"), Some("true"));
+        assert_eq!(fence.property("south"), Some("false"));
+        assert_eq!(fence.property("west"), Some("false"));
+    }
+
+    #[test]
+    fn test_failed_placement_resends_blocks() {
+        run_with_server(async |server| {
+            let mut client = connect_player(&server).await;
+            let player = client.player.clone();
+            let world = player.world();
+            world.add_player(player.clone());
+            world.load_chunk((0, 0));
+            world.set_block(0, 63, 0, Block::Stone);
+            let clicked = BlockPosition::new(0, 63, 0);
+            let id = packet_id::<BlockUpdatePacket>(&ProtocolState::Play).unwrap();
+
+            // The block above is occupied by the player itself, and the upper half of the door has to be reset too
+            for (sequence, material, height) in [(1, Material::Stone, 1), (2, Material::OakDoor, 2)]
+            {
+                player
+                    .inventory()
+                    .set_item_stack(36, ItemStack::of(material));
+
+                let mut data = BytesMut::new();
+                data.write_varint(0).unwrap();
+                data.write_position(&clicked).unwrap();
+                data.write_varint(BlockFace::Top as i32).unwrap();
+                for _ in 0..3 {
+                    data.write_f32(0.5).unwrap();
+                }
+                data.write_bool(false).unwrap();
+                data.write_bool(false).unwrap();
+                data.write_varint(sequence).unwrap();
+                client
+                    .conn
+                    .clone()
+                    .handle_packet(0x3F, &mut Cursor::new(&data[..]))
+                    .await
+                    .unwrap();
+
+                assert!(world.get_block_at(clicked.add(0, 1, 0)).unwrap().is_air());
+                let resent = (1..=height).map(|y| (clicked.add(0, y, 0), 0));
+                for (position, block) in [(clicked, Block::Stone.state_id())]
+                    .into_iter()
+                    .chain(resent)
+                {
+                    let data = next_packet(&mut client.rx, id);
+                    let mut data = Cursor::new(&data[..]);
+                    assert_eq!(data.read_position().unwrap(), position);
+                    assert_eq!(data.read_varint().unwrap(), block);
+                }
+            }
+        });
+    }
+}
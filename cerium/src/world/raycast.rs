@@ -0,0 +1,321 @@
+use crate::{
+    entity::{Entity, EntityLike as _, Player},
+    util::{BlockPosition, BoundingBox, Position},
+    world::{BlockFace, BlockState, World},
+};
+
+/// The farthest a block raycast looks, far beyond the view distance of clients.
+const MAX_DISTANCE: f64 = 1024.;
+
+/// A block hit by a ray.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockHit {
+    pub position: BlockPosition,
+    pub block: &'static BlockState,
+    /// The face of the block the ray entered through.
+    pub face: BlockFace,
+    /// The point at which the ray hit the block.
+    pub point: Position,
+    pub distance: f64,
+}
+
+/// Something with a hitbox that a ray can hit.
+#[derive(Clone)]
+pub enum EntityTarget {
+    Entity(Entity),
+    Player(Player),
+}
+
+impl EntityTarget {
+    pub fn id(&self) -> i32 {
+        match self {
+            EntityTarget::Entity(entity) => entity.id(),
+            EntityTarget::Player(player) => player.id(),
+        }
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self {
+            EntityTarget::Entity(entity) => entity.bounding_box(),
+            EntityTarget::Player(player) => player.bounding_box(),
+        }
+    }
+}
+
+/// An entity or player hit by a ray.
+#[derive(Clone)]
+pub struct EntityHit {
+    pub target: EntityTarget,
+    /// The face of the hitbox the ray entered through.
+    pub face: BlockFace,
+    /// The point at which the ray hit the hitbox.
+    pub point: Position,
+    pub distance: f64,
+}
+
+impl World {
+    /// Returns the first block a ray hits within the distance.
+    ///
+    /// The blocks are visited one after another along the ray, so long rays stay cheap. The ray hits the
+    /// outline shape of the blocks, passing through air and fluids. It ends where it leaves the world height
+    /// or the loaded chunks, and after 1024 blocks. A distance that is not finite hits nothing.
+    pub fn raycast_block(
+        &self,
+        origin: Position,
+        direction: (f64, f64, f64),
+        max_distance: f64,
+    ) -> Option<BlockHit> {
+        if !max_distance.is_finite() {
+            return None;
+        }
+        let max_distance = max_distance.min(MAX_DISTANCE);
+        let direction = normalize(direction)?;
+        let start = [origin.x(), origin.y(), origin.z()];
+        let dir = [direction.0, direction.1, direction.2];
+        let min_y = self.0.dimension_type.min_y as i64;
+        let max_y = min_y + self.0.dimension_type.height as i64 - 1;
+
+        let mut block = start.map(|v| v.floor() as i64);
+        let mut step = [0i64; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        // The distance at which the ray crosses into the next block on each axis
+        let mut t_max = [f64::INFINITY; 3];
+        for axis in 0..3 {
+            if dir[axis] > 0. {
+                step[axis] = 1;
+                t_max[axis] = (block[axis] as f64 + 1. - start[axis]) / dir[axis];
+            } else if dir[axis] < 0. {
+                step[axis] = -1;
+                t_max[axis] = (start[axis] - block[axis] as f64) / -dir[axis];
+            }
+            if dir[axis] != 0. {
+                t_delta[axis] = 1. / dir[axis].abs();
+            }
+        }
+
+        let mut distance = 0.;
+        while distance <= max_distance {
+            // Outside of the world height, the ray only hits something if it heads back in
+            if (block[1] < min_y && step[1] <= 0) || (block[1] > max_y && step[1] >= 0) {
+                return None;
+            }
+            let position = BlockPosition::new(block[0], block[1], block[2]);
+            if self.0.is_in_height(position.y()) && self.get_chunk(position).is_none() {
+                return None;
+            }
+            if let Some(hit) = self.hit_block(position, origin, direction)
+                && hit.distance <= max_distance
+            {
+                return Some(hit);
+            }
+
+            let axis = (0..3)
+                .min_by(|a, b| t_max[*a].total_cmp(&t_max[*b]))
+                .unwrap();
+            distance = t_max[axis];
+            t_max[axis] += t_delta[axis];
+            block[axis] += step[axis];
+        }
+        None
+    }
+
+    /// Returns the closest entity or player a ray hits within the distance, of those the filter accepts.
+    pub fn raycast_entity<F>(
+        &self,
+        origin: Position,
+        direction: (f64, f64, f64),
+        max_distance: f64,
+        mut filter: F,
+    ) -> Option<EntityHit>
+    where
+        F: FnMut(&EntityTarget) -> bool,
+    {
+        let direction = normalize(direction)?;
+        let start = (origin.x(), origin.y(), origin.z());
+
+        let players = self.players().into_iter().map(EntityTarget::Player);
+        let entities = self.entities().into_iter().map(EntityTarget::Entity);
+        players
+            .chain(entities)
+            .filter(|target| filter(target))
+            .filter_map(|target| {
+                let (distance, face) = target.bounding_box().intersect_ray(start, direction)?;
+                (distance <= max_distance).then(|| EntityHit {
+                    target,
+                    face,
+                    point: point_at(origin, direction, distance),
+                    distance,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn hit_block(
+        &self,
+        position: BlockPosition,
+        origin: Position,
+        direction: (f64, f64, f64),
+    ) -> Option<BlockHit> {
+        let block = self.get_block_at(position)?;
+        if block.is_air() || (block.has_fluid() && block.is_replaceable()) {
+            return None;
+        }
+
+        // Blocks without an outline in the data, like torches or grass, can still be targeted
+        let shape = match block.outline_shape() {
+            [] => &[BoundingBox::BLOCK],
+            shape => shape,
+        };
+        let (x, y, z) = (
+            position.x() as f64,
+            position.y() as f64,
+            position.z() as f64,
+        );
+        let start = (origin.x(), origin.y(), origin.z());
+        let (distance, face) = shape
+            .iter()
+            .filter_map(|part| part.offset(x, y, z).intersect_ray(start, direction))
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+        Some(BlockHit {
+            position,
+            block,
+            face,
+            point: point_at(origin, direction, distance),
+            distance,
+        })
+    }
+}
+
+fn normalize(direction: (f64, f64, f64)) -> Option<(f64, f64, f64)> {
+    let length =
+        (direction.0 * direction.0 + direction.1 * direction.1 + direction.2 * direction.2).sqrt();
+    if length < 1e-9 || !length.is_finite() {
+        return None;
+    }
+    Some((
+        direction.0 / length,
+        direction.1 / length,
+        direction.2 / length,
+    ))
+}
+
+fn point_at(origin: Position, direction: (f64, f64, f64), distance: f64) -> Position {
+    origin.add(
+        direction.0 * distance,
+        direction.1 * distance,
+        direction.2 * distance,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity::EntityType, registry::DimensionType, util::ChunkPos, world::Block};
+
+    fn world() -> World {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        world.load_chunk(ChunkPos::ZERO);
+        world
+    }
+
+    #[test]
+    fn test_raycast_block() {
+        let world = world();
+        world.set_block(5, 64, 3, Block::Stone);
+        world.set_block(3, 64, 3, Block::Water);
+
+        let origin = Position::new(0.5, 64.5, 3.5, 0., 0.);
+        let hit = world.raycast_block(origin, (1., 0., 0.), 10.).unwrap();
+        assert_eq!(hit.position, BlockPosition::new(5, 64, 3));
+        assert_eq!(hit.face, BlockFace::West);
+        assert_eq!(hit.distance, 4.5);
+        assert_eq!(hit.point.x(), 5.);
+
+        assert!(world.raycast_block(origin, (1., 0., 0.), 4.).is_none());
+        assert!(world.raycast_block(origin, (-1., 0., 0.), 10.).is_none());
+
+        // Huge distances are clamped and rays end at the loaded chunks and the world height
+        assert_eq!(
+            world
+                .raycast_block(origin, (1., 0., 0.), f64::MAX)
+                .unwrap()
+                .position,
+            hit.position
+        );
+        assert!(
+            world
+                .raycast_block(origin, (1., 0., 0.), f64::INFINITY)
+                .is_none()
+        );
+        assert!(
+            world
+                .raycast_block(origin, (1., 0., 0.), f64::NAN)
+                .is_none()
+        );
+        assert!(
+            world
+                .raycast_block(origin, (-1., 0., 0.), f64::MAX)
+                .is_none()
+        );
+        assert!(
+            world
+                .raycast_block(origin, (0., 1., 0.), f64::MAX)
+                .is_none()
+        );
+        assert!(
+            world
+                .raycast_block(origin, (0., -1., 0.), f64::MAX)
+                .is_none()
+        );
+
+        // The ray passes over a bottom slab and hits the top half of the stairs
+        let origin = Position::new(0.5, 65.75, 8.25, 0., 0.);
+        world.set_block(
+            2,
+            65,
+            8,
+            Block::OakSlab.with_property("type", "bottom").unwrap(),
+        );
+        world.set_block(4, 65, 8, Block::OakStairs);
+        let hit = world.raycast_block(origin, (1., 0., 0.), 10.).unwrap();
+        assert_eq!(hit.position, BlockPosition::new(4, 65, 8));
+        assert_eq!(hit.distance, 3.5);
+
+        // Looking down at the block from above and at an angle
+        world.set_block(7, 60, 9, Block::Dirt);
+        let origin = Position::new(4.5, 64., 6.5, 0., 0.);
+        let hit = world.raycast_block(origin, (3., -3.5, 3.), 10.).unwrap();
+        assert_eq!(hit.position, BlockPosition::new(7, 60, 9));
+        assert_eq!(hit.face, BlockFace::Top);
+    }
+
+    #[test]
+    fn test_raycast_entity() {
+        let world = world();
+        let near = Entity::new(EntityType::Pig);
+        near.set_position(Position::new(4., 64., 0.5, 0., 0.));
+        let far = Entity::new(EntityType::Pig);
+        far.set_position(Position::new(8., 64., 0.5, 0., 0.));
+        world.spawn_entity(near.clone());
+        world.spawn_entity(far.clone());
+
+        let origin = Position::new(0., 64.5, 0.5, 0., 0.);
+        let hit = world
+            .raycast_entity(origin, (1., 0., 0.), 20., |_| true)
+            .unwrap();
+        assert_eq!(hit.target.id(), near.id());
+        assert_eq!(hit.face, BlockFace::West);
+        assert!((hit.distance - (4. - EntityType::Pig.width() as f64 / 2.)).abs() < 1e-6);
+
+        let hit = world
+            .raycast_entity(origin, (1., 0., 0.), 20., |target| target.id() != near.id())
+            .unwrap();
+        assert_eq!(hit.target.id(), far.id());
+        assert!(
+            world
+                .raycast_entity(origin, (0., 0., 1.), 20., |_| true)
+                .is_none()
+        );
+    }
+}
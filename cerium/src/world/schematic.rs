@@ -0,0 +1,773 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+
+use cerium_nbt::{Nbt, NbtCompound, NbtTag};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+use crate::registry::{REGISTRIES, RegistryKey};
+use crate::util::{BlockPosition, ChunkPos};
+use crate::world::{Block, BlockState, Transform, World};
+
+/// The data version of the game version the server implements, written into saved structures.
+pub(crate) const DATA_VERSION: i32 = 4556;
+
+/// The most blocks a schematic may have, 256 blocks in every direction.
+const MAX_VOLUME: usize = 1 << 24;
+
+/// The most bytes a gzipped schematic, structure or world file may decompress to.
+pub(crate) const MAX_DECOMPRESSED_SIZE: u64 = 1 << 26;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SchematicError {
+    #[error("std::io::Error: {0}")]
+    IoError(std::io::Error),
+    #[error("Failed to read NBT: {0}")]
+    NbtError(cerium_nbt::Error),
+    #[error("Missing or invalid field: {0}")]
+    InvalidField(&'static str),
+    #[error("Unsupported schematic version: {0}")]
+    UnsupportedVersion(i32),
+    #[error("The schematic exceeds the size limit")]
+    TooLarge,
+}
+
+/// A cuboid of blocks in the [Sponge schematic format](https://github.com/SpongePowered/Schematic-Specification),
+/// the format used by WorldEdit.
+///
+/// Versions 2 and 3 can be read and written. Entities are not supported and skipped.
+///
+/// # Example
+/// ```no_run
+/// # use cerium::world::{Rotation, Schematic, Transform, World};
+/// # use cerium::util::BlockPosition;
+/// # fn paste(world: &World) -> Result<(), Box<dyn std::error::Error>> {
+/// let schematic = Schematic::read(std::fs::File::open("arena.schem")?)?;
+/// let transform = Transform::new().with_rotation(Rotation::Clockwise90);
+/// schematic.paste(world, BlockPosition::new(0, 64, 0), transform);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    width: u16,
+    height: u16,
+    length: u16,
+    /// The position of the first block relative to the origin of the schematic.
+    offset: (i32, i32, i32),
+    blocks: Vec<&'static BlockState>,
+    /// The data of block entities, keyed by their position within the schematic.
+    block_entities: HashMap<(i32, i32, i32), NbtCompound>,
+    /// The biome id of every block, in the same order as the blocks.
+    biomes: Option<Vec<i32>>,
+}
+
+impl Schematic {
+    /// Creates a schematic of air.
+    pub fn new(width: u16, height: u16, length: u16) -> Self {
+        let volume = width as usize * height as usize * length as usize;
+        Self {
+            width,
+            height,
+            length,
+            offset: (0, 0, 0),
+            blocks: vec![Block::Air.default_state(); volume],
+            block_entities: HashMap::new(),
+            biomes: None,
+        }
+    }
+
+    /// Copies the blocks, block entities and biomes between two corners of the world, including both corners.
+    ///
+    /// Blocks in unloaded chunks are copied as air. Returns [`SchematicError::TooLarge`] if the region has more
+    /// than 2<sup>24</sup> blocks or is longer than 65535 blocks in any direction.
+    pub fn copy(
+        world: &World,
+        from: BlockPosition,
+        to: BlockPosition,
+    ) -> Result<Self, SchematicError> {
+        let min = BlockPosition::new(
+            from.x().min(to.x()),
+            from.y().min(to.y()),
+            from.z().min(to.z()),
+        );
+        let max = BlockPosition::new(
+            from.x().max(to.x()),
+            from.y().max(to.y()),
+            from.z().max(to.z()),
+        );
+        let size =
+            |min: i64, max: i64| u16::try_from(max - min + 1).map_err(|_| SchematicError::TooLarge);
+        let (width, height, length) = (
+            size(min.x(), max.x())?,
+            size(min.y(), max.y())?,
+            size(min.z(), max.z())?,
+        );
+        check_volume(width, height, length)?;
+
+        let mut schematic = Self::new(width, height, length);
+        let mut biomes = vec![0; schematic.blocks.len()];
+
+        for (x, y, z) in schematic.positions() {
+            let position = min.add(x as i64, y as i64, z as i64);
+            let Some(block) = world.get_block_at(position) else {
+                continue;
+            };
+            let index = schematic.index(x, y, z);
+            schematic.blocks[index] = block;
+            biomes[index] = world.get_biome(
+                position.x() as i32,
+                position.y() as i32,
+                position.z() as i32,
+            ) as i32;
+            if let Some(data) = world.get_block_entity_data(position) {
+                schematic.block_entities.insert((x, y, z), data);
+            }
+        }
+        schematic.biomes = Some(biomes);
+        Ok(schematic)
+    }
+
+    /// Reads a gzipped schematic.
+    pub fn read(reader: impl Read) -> Result<Self, SchematicError> {
+        let bytes = read_decompressed(GzDecoder::new(reader))
+            .map_err(SchematicError::IoError)?
+            .ok_or(SchematicError::TooLarge)?;
+        let nbt = Nbt::read_named(&mut bytes.as_slice()).map_err(SchematicError::NbtError)?;
+        Self::from_nbt(&nbt.root)
+    }
+
+    /// Writes the schematic gzipped in the version, either 2 or 3.
+    pub fn write(&self, writer: impl Write, version: i32) -> Result<(), SchematicError> {
+        let nbt = Nbt::new(String::new(), self.to_nbt(version)?);
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        nbt.write_named(&mut encoder)
+            .map_err(SchematicError::NbtError)?;
+        encoder.finish().map_err(SchematicError::IoError)?;
+        Ok(())
+    }
+
+    /// Reads a schematic from its root compound.
+    pub fn from_nbt(root: &NbtCompound) -> Result<Self, SchematicError> {
+        // Version 3 nests everything in a `Schematic` compound
+        let (root, version) = match root.get("Schematic") {
+            Some(NbtTag::Compound(schematic)) => (schematic, get_int(schematic, "Version")?),
+            _ => (root, get_int(root, "Version")?),
+        };
+
+        let width = get_size(root, "Width")?;
+        let height = get_size(root, "Height")?;
+        let length = get_size(root, "Length")?;
+        check_volume(width, height, length)?;
+        let mut schematic = Self::new(width, height, length);
+        if let Some(NbtTag::IntArray(offset)) = root.get("Offset")
+            && let [x, y, z] = offset[..]
+        {
+            schematic.offset = (x, y, z);
+        }
+
+        let (blocks, biomes) = match version {
+            2 => (root, None),
+            3 => (
+                get_compound(root, "Blocks")?,
+                get_compound(root, "Biomes").ok(),
+            ),
+            version => return Err(SchematicError::UnsupportedVersion(version)),
+        };
+        let data_field = if version == 2 { "BlockData" } else { "Data" };
+
+        let palette = read_palette(get_compound(blocks, "Palette")?, |key| {
+            BlockState::from_state_key(key).or_else(|| {
+                log::warn!("Unknown block {key} in schematic, replacing it with air");
+                None
+            })
+        });
+        let data = read_varints(get_byte_array(blocks, data_field)?, schematic.blocks.len())?;
+        for (index, id) in data.into_iter().enumerate() {
+            schematic.blocks[index] = palette
+                .get(&id)
+                .copied()
+                .flatten()
+                .unwrap_or(Block::Air.default_state());
+        }
+
+        if let Some(NbtTag::List(block_entities)) = blocks.get("BlockEntities") {
+            for block_entity in block_entities {
+                let NbtTag::Compound(block_entity) = block_entity else {
+                    continue;
+                };
+                let Some(NbtTag::IntArray(pos)) = block_entity.get("Pos") else {
+                    continue;
+                };
+                let [x, y, z] = pos[..] else {
+                    continue;
+                };
+                if !schematic.contains(x, y, z) {
+                    log::warn!(
+                        "Block entity at ({x}, {y}, {z}) is outside of the schematic, skipping it"
+                    );
+                    continue;
+                }
+                let data = match block_entity.get("Data") {
+                    Some(NbtTag::Compound(data)) if version == 3 => data.clone(),
+                    // Version 2 stores the data next to the position and id
+                    _ => {
+                        let mut data = NbtCompound::new();
+                        for (name, value) in &block_entity.children {
+                            if !matches!(name.as_str(), "Pos" | "Id") {
+                                data.insert(name, value.clone());
+                            }
+                        }
+                        data
+                    }
+                };
+                schematic.block_entities.insert((x, y, z), data);
+            }
+        }
+
+        let biomes = match biomes {
+            Some(biomes) => Some((
+                get_compound(biomes, "Palette")?,
+                get_byte_array(biomes, "Data")?,
+            )),
+            None => match (root.get("BiomePalette"), root.get("BiomeData")) {
+                (Some(NbtTag::Compound(palette)), Some(NbtTag::ByteArray(data))) => {
+                    Some((palette, &data[..]))
+                }
+                _ => None,
+            },
+        };
+        if let Some((palette, data)) = biomes {
+            let palette = read_palette(palette, |key| {
+                let id = REGISTRIES.biome.get_id(&RegistryKey::of(key));
+                if id.is_none() {
+                    log::warn!(
+                        "Unknown biome {key} in schematic, replacing it with the default biome"
+                    );
+                }
+                id.map(|id| id as i32)
+            });
+            // Version 2 only stores one biome per column
+            let count = if version == 2 {
+                width as usize * length as usize
+            } else {
+                schematic.blocks.len()
+            };
+            let data = read_varints(data, count)?;
+            let biome = |id: i32| palette.get(&id).copied().flatten().unwrap_or(0);
+
+            let mut biomes = vec![0; schematic.blocks.len()];
+            for (x, y, z) in schematic.positions() {
+                let index = schematic.index(x, y, z);
+                biomes[index] = if version == 2 {
+                    biome(data[(x + z * width as i32) as usize])
+                } else {
+                    biome(data[index])
+                };
+            }
+            schematic.biomes = Some(biomes);
+        }
+
+        Ok(schematic)
+    }
+
+    /// Returns the root compound of the schematic in the version, either 2 or 3.
+    pub fn to_nbt(&self, version: i32) -> Result<NbtCompound, SchematicError> {
+        if !matches!(version, 2 | 3) {
+            return Err(SchematicError::UnsupportedVersion(version));
+        }
+
+        let mut root = NbtCompound::new();
+        root.insert("Version", version);
+        root.insert("DataVersion", DATA_VERSION);
+        // The sizes are unsigned shorts, stored in signed short tags
+        root.insert("Width", self.width as i16);
+        root.insert("Height", self.height as i16);
+        root.insert("Length", self.length as i16);
+        let (x, y, z) = self.offset;
+        root.insert("Offset", vec![x, y, z]);
+
+        let (palette, data) = write_palette(self.blocks.iter().map(|block| block.state_key()));
+        let block_entities = self
+            .block_entities
+            .iter()
+            .filter_map(|(&(x, y, z), data)| {
+                let info = self.blocks[self.index(x, y, z)].block_entity()?;
+                let mut block_entity = NbtCompound::new();
+                block_entity.insert("Pos", vec![x, y, z]);
+                block_entity.insert("Id", info.namespace.as_str());
+                if version == 2 {
+                    block_entity.children.extend(data.children.iter().cloned());
+                } else {
+                    block_entity.insert("Data", data.clone());
+                }
+                Some(NbtTag::Compound(block_entity))
+            })
+            .collect::<Vec<_>>();
+
+        let biome_key = |id: i32| {
+            REGISTRIES
+                .biome
+                .entries()
+                .get_index(id as usize)
+                .map_or("minecraft:plains".to_string(), |(key, _)| {
+                    key.as_key().to_string()
+                })
+        };
+
+        if version == 2 {
+            root.insert("PaletteMax", palette.children.len() as i32);
+            root.insert("Palette", palette);
+            root.insert("BlockData", NbtTag::ByteArray(data.into_boxed_slice()));
+            root.insert("BlockEntities", NbtTag::List(block_entities));
+            if let Some(biomes) = &self.biomes {
+                // Version 2 only stores one biome per column, the one at the bottom
+                let columns = (0..self.length as i32)
+                    .flat_map(|z| (0..self.width as i32).map(move |x| (x, z)))
+                    .map(|(x, z)| biome_key(biomes[self.index(x, 0, z)]));
+                let (palette, data) = write_palette(columns);
+                root.insert("BiomePaletteMax", palette.children.len() as i32);
+                root.insert("BiomePalette", palette);
+                root.insert("BiomeData", NbtTag::ByteArray(data.into_boxed_slice()));
+            }
+            return Ok(root);
+        }
+
+        let mut blocks = NbtCompound::new();
+        blocks.insert("Palette", palette);
+        blocks.insert("Data", NbtTag::ByteArray(data.into_boxed_slice()));
+        blocks.insert("BlockEntities", NbtTag::List(block_entities));
+        root.insert("Blocks", blocks);
+        if let Some(biomes) = &self.biomes {
+            let (palette, data) = write_palette(biomes.iter().map(|&id| biome_key(id)));
+            let mut compound = NbtCompound::new();
+            compound.insert("Palette", palette);
+            compound.insert("Data", NbtTag::ByteArray(data.into_boxed_slice()));
+            root.insert("Biomes", compound);
+        }
+
+        let mut schematic = NbtCompound::new();
+        schematic.insert("Schematic", root);
+        Ok(schematic)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn offset(&self) -> (i32, i32, i32) {
+        self.offset
+    }
+
+    /// Sets the position of the first block relative to the position the schematic is pasted at.
+    pub fn with_offset(mut self, x: i32, y: i32, z: i32) -> Self {
+        self.offset = (x, y, z);
+        self
+    }
+
+    /// Returns the block at the coordinates within the schematic, or `None` if they are outside of it.
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> Option<&'static BlockState> {
+        self.contains(x, y, z)
+            .then(|| self.blocks[self.index(x, y, z)])
+    }
+
+    /// Sets the block at the coordinates within the schematic, removing the data of its block entity.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the schematic.
+    pub fn set_block<B>(&mut self, x: i32, y: i32, z: i32, block: B)
+    where
+        B: AsRef<BlockState>,
+    {
+        assert!(
+            self.contains(x, y, z),
+            "({x}, {y}, {z}) is outside of the schematic"
+        );
+        let index = self.index(x, y, z);
+        self.blocks[index] = BlockState::from_id(block.as_ref().state_id()).unwrap();
+        self.block_entities.remove(&(x, y, z));
+    }
+
+    pub fn get_block_entity_data(&self, x: i32, y: i32, z: i32) -> Option<&NbtCompound> {
+        self.block_entities.get(&(x, y, z))
+    }
+
+    /// Sets the data of the block entity at the coordinates within the schematic.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the schematic.
+    pub fn set_block_entity_data(&mut self, x: i32, y: i32, z: i32, data: NbtCompound) {
+        assert!(
+            self.contains(x, y, z),
+            "({x}, {y}, {z}) is outside of the schematic"
+        );
+        self.block_entities.insert((x, y, z), data);
+    }
+
+    /// Returns the biome id at the coordinates within the schematic, or `None` if it has no biomes.
+    pub fn get_biome(&self, x: i32, y: i32, z: i32) -> Option<i32> {
+        let biomes = self.biomes.as_ref()?;
+        self.contains(x, y, z).then(|| biomes[self.index(x, y, z)])
+    }
+
+    /// Pastes the schematic with its origin at the position.
+    ///
+    /// Blocks are set in one batch: no block handlers are called and every changed chunk is resent to its
+    /// viewers once, instead of sending every block.
+    pub fn paste(&self, world: &World, position: BlockPosition, transform: Transform) {
+        let mut chunks = HashSet::new();
+
+        for (x, y, z) in self.positions() {
+            let (ox, oy, oz) = self.offset;
+            let (dx, dy, dz) = transform.apply(x + ox, y + oy, z + oz);
+            let target = position.add(dx as i64, dy as i64, dz as i64);
+            if !world.0.is_in_height(target.y()) {
+                continue;
+            }
+            let (tx, ty, tz) = (target.x() as i32, target.y() as i32, target.z() as i32);
+
+            let index = self.index(x, y, z);
+            let block = transform.apply_block(self.blocks[index]);
+            world.0.set_block_at(target, block);
+            if let Some(biomes) = &self.biomes {
+                world.0.set_biome(tx, ty, tz, biomes[index]);
+            }
+
+            if let Some(data) = self.block_entities.get(&(x, y, z))
+                && let Some(chunk) = world.get_chunk(target)
+            {
+                chunk.set_block_entity_data(tx, ty, tz, data.clone());
+            }
+            chunks.insert(ChunkPos::from(target));
+        }
+
+        world.0.resend_chunks(chunks);
+    }
+
+    fn contains(&self, x: i32, y: i32, z: i32) -> bool {
+        (0..self.width as i32).contains(&x)
+            && (0..self.height as i32).contains(&y)
+            && (0..self.length as i32).contains(&z)
+    }
+
+    fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        let (width, length) = (self.width as usize, self.length as usize);
+        x as usize + z as usize * width + y as usize * width * length
+    }
+
+    /// Returns every position within the schematic in the order of its blocks.
+    fn positions(&self) -> impl Iterator<Item = (i32, i32, i32)> + use<> {
+        let (width, height, length) = (self.width as i32, self.height as i32, self.length as i32);
+        (0..height)
+            .flat_map(move |y| (0..length).flat_map(move |z| (0..width).map(move |x| (x, y, z))))
+    }
+}
+
+/// Reads all bytes of a decoder, or returns `None` if they exceed [`MAX_DECOMPRESSED_SIZE`] instead of filling
+/// the memory.
+pub(crate) fn read_decompressed(decoder: impl Read) -> std::io::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    decoder
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_end(&mut bytes)?;
+    Ok((bytes.len() as u64 <= MAX_DECOMPRESSED_SIZE).then_some(bytes))
+}
+
+fn check_volume(width: u16, height: u16, length: u16) -> Result<(), SchematicError> {
+    if width as usize * height as usize * length as usize > MAX_VOLUME {
+        return Err(SchematicError::TooLarge);
+    }
+    Ok(())
+}
+
+/// Reads a length of the schematic, an unsigned short which must not be zero.
+fn get_size(compound: &NbtCompound, name: &'static str) -> Result<u16, SchematicError> {
+    match get_short(compound, name)? as u16 {
+        0 => Err(SchematicError::InvalidField(name)),
+        size => Ok(size),
+    }
+}
+
+fn get_int(compound: &NbtCompound, name: &'static str) -> Result<i32, SchematicError> {
+    match compound.get(name) {
+        Some(NbtTag::Int(value)) => Ok(*value),
+        _ => Err(SchematicError::InvalidField(name)),
+    }
+}
+
+fn get_short(compound: &NbtCompound, name: &'static str) -> Result<i16, SchematicError> {
+    match compound.get(name) {
+        Some(NbtTag::Short(value)) => Ok(*value),
+        _ => Err(SchematicError::InvalidField(name)),
+    }
+}
+
+fn get_compound<'a>(
+    compound: &'a NbtCompound,
+    name: &'static str,
+) -> Result<&'a NbtCompound, SchematicError> {
+    match compound.get(name) {
+        Some(NbtTag::Compound(value)) => Ok(value),
+        _ => Err(SchematicError::InvalidField(name)),
+    }
+}
+
+fn get_byte_array<'a>(
+    compound: &'a NbtCompound,
+    name: &'static str,
+) -> Result<&'a [u8], SchematicError> {
+    match compound.get(name) {
+        Some(NbtTag::ByteArray(value)) => Ok(value),
+        _ => Err(SchematicError::InvalidField(name)),
+    }
+}
+
+/// Reads a palette of keys to indices, resolving every key once.
+fn read_palette<T>(
+    palette: &NbtCompound,
+    resolve: impl Fn(&str) -> Option<T>,
+) -> HashMap<i32, Option<T>> {
+    palette
+        .children
+        .iter()
+        .filter_map(|(key, index)| match index {
+            NbtTag::Int(index) => Some((*index, resolve(key))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Builds a palette of the keys and the varint encoded indices of every key.
+fn write_palette(keys: impl Iterator<Item = String>) -> (NbtCompound, Vec<u8>) {
+    let mut indices: HashMap<String, i32> = HashMap::new();
+    let mut palette = NbtCompound::new();
+    let mut data = Vec::new();
+    for key in keys {
+        let index = *indices.entry(key).or_insert_with_key(|key| {
+            let index = palette.children.len() as i32;
+            palette.insert(key, index);
+            index
+        });
+        write_varint(&mut data, index);
+    }
+    (palette, data)
+}
+
+fn read_varints(mut bytes: &[u8], count: usize) -> Result<Vec<i32>, SchematicError> {
+    let mut values = Vec::with_capacity(count);
+    while values.len() < count {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let (&byte, rest) = bytes
+                .split_first()
+                .ok_or(SchematicError::InvalidField("Data"))?;
+            bytes = rest;
+            value |= ((byte & 0x7F) as i32) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift >= 32 {
+                return Err(SchematicError::InvalidField("Data"));
+            }
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            bytes.push(value as u8);
+            return;
+        }
+        bytes.push((value & 0x7F | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::DimensionType;
+    use crate::world::Rotation;
+
+    fn schematic() -> Schematic {
+        let mut schematic = Schematic::new(3, 2, 2).with_offset(-1, 0, 0);
+        schematic.set_block(0, 0, 0, Block::Stone);
+        schematic.set_block(
+            2,
+            1,
+            1,
+            Block::OakStairs.with_property("facing", "north").unwrap(),
+        );
+        schematic.set_block(1, 0, 1, Block::Chest);
+        let mut data = NbtCompound::new();
+        data.insert("Lock", "key");
+        schematic.set_block_entity_data(1, 0, 1, data);
+        schematic
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for version in [2, 3] {
+            let schematic = schematic();
+            let mut bytes = Vec::new();
+            schematic.write(&mut bytes, version).unwrap();
+            let read = Schematic::read(bytes.as_slice()).unwrap();
+
+            assert_eq!((read.width(), read.height(), read.length()), (3, 2, 2));
+            assert_eq!(read.offset(), (-1, 0, 0));
+            let states = |schematic: &Schematic| {
+                schematic
+                    .blocks
+                    .iter()
+                    .map(|block| block.state_id())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(states(&read), states(&schematic));
+            assert_eq!(
+                read.get_block_entity_data(1, 0, 1),
+                schematic.get_block_entity_data(1, 0, 1)
+            );
+        }
+
+        assert!(matches!(
+            schematic().write(Vec::new(), 1),
+            Err(SchematicError::UnsupportedVersion(1))
+        ));
+    }
+
+    #[test]
+    fn test_roundtrip_long() {
+        let mut schematic = Schematic::new(40_000, 1, 1);
+        schematic.set_block(39_999, 0, 0, Block::Stone);
+        for version in [2, 3] {
+            let mut bytes = Vec::new();
+            schematic.write(&mut bytes, version).unwrap();
+            let read = Schematic::read(bytes.as_slice()).unwrap();
+
+            assert_eq!((read.width(), read.height(), read.length()), (40_000, 1, 1));
+            assert_eq!(
+                read.get_block(39_999, 0, 0).unwrap().id(),
+                Block::Stone.id()
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_entity_outside() {
+        let mut root = schematic().to_nbt(2).unwrap();
+        let Some(NbtTag::List(block_entities)) = root.get("BlockEntities") else {
+            panic!("Expected block entities");
+        };
+        let mut block_entities = block_entities.clone();
+        for pos in [vec![1, 0, -1], vec![3, 0, 0], vec![0, 0, 2]] {
+            let mut block_entity = NbtCompound::new();
+            block_entity.insert("Pos", pos);
+            block_entity.insert("Id", "minecraft:chest");
+            block_entities.push(NbtTag::Compound(block_entity));
+        }
+        root.children.retain(|(key, _)| key != "BlockEntities");
+        root.insert("BlockEntities", NbtTag::List(block_entities));
+
+        // Only the block entity within the schematic is kept, so it can be written again
+        let schematic = Schematic::from_nbt(&root).unwrap();
+        assert_eq!(schematic.block_entities.len(), 1);
+        assert!(schematic.get_block_entity_data(1, 0, -1).is_none());
+        schematic.write(Vec::new(), 3).unwrap();
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let root = schematic().to_nbt(2).unwrap();
+        let with_sizes = |sizes: &[(&str, i16)]| {
+            let mut root = root.clone();
+            root.children
+                .retain(|(key, _)| sizes.iter().all(|(name, _)| key != name));
+            for (name, size) in sizes {
+                root.insert(name, *size);
+            }
+            Schematic::from_nbt(&root)
+        };
+        assert!(matches!(
+            with_sizes(&[("Width", 0)]),
+            Err(SchematicError::InvalidField("Width"))
+        ));
+        // A negative short is a length above 32767, which does not match the block data
+        assert!(matches!(
+            with_sizes(&[("Length", -3)]),
+            Err(SchematicError::InvalidField("Data"))
+        ));
+        assert!(matches!(
+            with_sizes(&[("Width", i16::MAX), ("Height", i16::MAX)]),
+            Err(SchematicError::TooLarge)
+        ));
+
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let copy = Schematic::copy(
+            &world,
+            BlockPosition::new(0, 0, 0),
+            BlockPosition::new(70_000, 0, 0),
+        );
+        assert!(matches!(copy, Err(SchematicError::TooLarge)));
+        let copy = Schematic::copy(
+            &world,
+            BlockPosition::new(0, 0, 0),
+            BlockPosition::new(1000, 100, 1000),
+        );
+        assert!(matches!(copy, Err(SchematicError::TooLarge)));
+
+        // A small file must not decompress to more than the limit
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&vec![0; MAX_DECOMPRESSED_SIZE as usize + 1])
+            .unwrap();
+        let bytes = encoder.finish().unwrap();
+        assert!(matches!(
+            Schematic::read(bytes.as_slice()),
+            Err(SchematicError::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_paste() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let transform = Transform::new().with_rotation(Rotation::Clockwise90);
+        schematic().paste(&world, BlockPosition::new(0, 64, 0), transform);
+
+        // The offset moves the stone to x = -1, which the rotation turns to z = -1
+        assert_eq!(world.get_block(0, 64, -1).id(), Block::Stone.id());
+        let stairs = world.get_block(-1, 65, 1);
+        assert_eq!(stairs.id(), Block::OakStairs.id());
+        assert_eq!(stairs.property("facing"), Some("east"));
+        assert!(
+            world
+                .get_block_entity_data(BlockPosition::new(-1, 64, 0))
+                .is_some()
+        );
+
+        let copy = Schematic::copy(
+            &world,
+            BlockPosition::new(-1, 64, -1),
+            BlockPosition::new(0, 65, 1),
+        )
+        .unwrap();
+        assert_eq!(
+            copy.get_block(1, 0, 0).map(|block| block.id()),
+            Some(Block::Stone.id())
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use std::collections::HashSet;
+use std::io::Read;
+
+use cerium_nbt::{Nbt, NbtCompound, NbtTag};
+use flate2::read::GzDecoder;
+use rand::{Rng as _, SeedableRng as _, rngs::StdRng};
+
+use crate::entity::{Entity, EntityType};
+use crate::util::{BlockPosition, ChunkPos, Position};
+use crate::world::schematic::read_decompressed;
+use crate::world::{Block, BlockState, Mirror, Rotation, Transform, World};
+
+/// An error reading a [`StructureTemplate`].
+#[derive(thiserror::Error, Debug)]
+pub enum StructureError {
+    #[error("std::io::Error: {0}")]
+    IoError(std::io::Error),
+    #[error("Failed to read NBT: {0}")]
+    NbtError(cerium_nbt::Error),
+    #[error("Missing or invalid structure field: {0}")]
+    InvalidField(&'static str),
+    #[error("The structure exceeds the size limit")]
+    TooLarge,
+}
+
+/// A structure saved by a structure block, the `.nbt` files of vanilla.
+///
+/// # Example
+/// ```no_run
+/// # use cerium::world::{Rotation, StructurePlacement, StructureTemplate, World};
+/// # use cerium::util::BlockPosition;
+/// # fn place(world: &World) -> Result<(), Box<dyn std::error::Error>> {
+/// let room = StructureTemplate::read(std::fs::File::open("room.nbt")?)?;
+/// let placement = StructurePlacement::new()
+///     .with_rotation(Rotation::Clockwise90)
+///     .with_integrity(0.9)
+///     .with_seed(42);
+/// room.place(world, BlockPosition::new(0, 64, 0), &placement);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StructureTemplate {
+    size: (i32, i32, i32),
+    /// The states the blocks refer to. Structures like shipwrecks have several palettes of which one is picked.
+    palettes: Vec<Vec<&'static BlockState>>,
+    blocks: Vec<StructureBlock>,
+    entities: Vec<StructureEntity>,
+}
+
+/// A block of a [`StructureTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureBlock {
+    pub position: (i32, i32, i32),
+    /// The index of the state in the palette.
+    pub state: usize,
+    /// The data of the block entity.
+    pub nbt: Option<NbtCompound>,
+}
+
+/// An entity of a [`StructureTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureEntity {
+    pub position: (f64, f64, f64),
+    pub entity_type: EntityType,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// The data of the entity. Only the type, position and rotation are applied when the structure is placed,
+    /// the rest is kept for the caller to apply to the spawned entity.
+    pub nbt: NbtCompound,
+}
+
+/// How a [`StructureTemplate`] is placed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructurePlacement {
+    transform: Transform,
+    integrity: f32,
+    seed: Option<u64>,
+    palette: Option<usize>,
+    entities: bool,
+}
+
+impl StructurePlacement {
+    pub fn new() -> Self {
+        Self {
+            transform: Transform::new(),
+            integrity: 1.,
+            seed: None,
+            palette: None,
+            entities: true,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.transform.rotation = rotation;
+        self
+    }
+
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.transform.mirror = mirror;
+        self
+    }
+
+    /// Sets the chance of every block to be placed, from `0.0` to `1.0`. Defaults to `1.0`.
+    pub fn with_integrity(mut self, integrity: f32) -> Self {
+        self.integrity = integrity.clamp(0., 1.);
+        self
+    }
+
+    /// Sets the seed that decides which blocks are left out and which palette is used, so placing the
+    /// structure again gives the same result. Without a seed, a random one is used.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Uses the palette with the index instead of a random one.
+    pub fn with_palette(mut self, palette: usize) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Sets whether the entities of the structure are spawned. Defaults to `true`.
+    pub fn with_entities(mut self, entities: bool) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn integrity(&self) -> f32 {
+        self.integrity
+    }
+}
+
+impl Default for StructurePlacement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StructureTemplate {
+    /// Reads a gzipped structure.
+    pub fn read(reader: impl Read) -> Result<Self, StructureError> {
+        let bytes = read_decompressed(GzDecoder::new(reader))
+            .map_err(StructureError::IoError)?
+            .ok_or(StructureError::TooLarge)?;
+        let nbt = Nbt::read_named(&mut bytes.as_slice()).map_err(StructureError::NbtError)?;
+        Self::from_nbt(&nbt.root)
+    }
+
+    /// Reads a structure from its root compound.
+    pub fn from_nbt(root: &NbtCompound) -> Result<Self, StructureError> {
+        let size = match get_ints(root, "size")?[..] {
+            [x, y, z] => (x, y, z),
+            _ => return Err(StructureError::InvalidField("size")),
+        };
+
+        let palettes = match (root.get("palette"), root.get("palettes")) {
+            (Some(NbtTag::List(palette)), _) => vec![read_palette(palette)],
+            (_, Some(NbtTag::List(palettes))) => palettes
+                .iter()
+                .map(|palette| match palette {
+                    NbtTag::List(palette) => Ok(read_palette(palette)),
+                    _ => Err(StructureError::InvalidField("palettes")),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(StructureError::InvalidField("palette")),
+        };
+        if palettes.is_empty() {
+            return Err(StructureError::InvalidField("palettes"));
+        }
+
+        let blocks = get_compounds(root, "blocks")?
+            .map(|block| {
+                let position = match get_ints(block, "pos")?[..] {
+                    [x, y, z] => (x, y, z),
+                    _ => return Err(StructureError::InvalidField("pos")),
+                };
+                let state = match block.get("state") {
+                    Some(NbtTag::Int(state))
+                        if palettes.iter().all(|p| (*state as usize) < p.len()) =>
+                    {
+                        *state as usize
+                    }
+                    _ => return Err(StructureError::InvalidField("state")),
+                };
+                let nbt = match block.get("nbt") {
+                    Some(NbtTag::Compound(nbt)) => Some(nbt.clone()),
+                    _ => None,
+                };
+                Ok(StructureBlock {
+                    position,
+                    state,
+                    nbt,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut entities = Vec::new();
+        if root.get("entities").is_some() {
+            for entity in get_compounds(root, "entities")? {
+                let position = match get_doubles(entity, "pos")?[..] {
+                    [x, y, z] => (x, y, z),
+                    _ => return Err(StructureError::InvalidField("pos")),
+                };
+                let Some(NbtTag::Compound(nbt)) = entity.get("nbt") else {
+                    return Err(StructureError::InvalidField("nbt"));
+                };
+                let Some(NbtTag::String(id)) = nbt.get("id") else {
+                    return Err(StructureError::InvalidField("id"));
+                };
+                let Some(entity_type) = EntityType::from_key(id) else {
+                    log::warn!("Unknown entity {id} in structure, skipping it");
+                    continue;
+                };
+                let (yaw, pitch) = match nbt.get("Rotation") {
+                    Some(NbtTag::List(rotation)) => match rotation[..] {
+                        [NbtTag::Float(yaw), NbtTag::Float(pitch)] => (yaw, pitch),
+                        _ => (0., 0.),
+                    },
+                    _ => (0., 0.),
+                };
+                entities.push(StructureEntity {
+                    position,
+                    entity_type,
+                    yaw,
+                    pitch,
+                    nbt: nbt.clone(),
+                });
+            }
+        }
+
+        Ok(Self {
+            size,
+            palettes,
+            blocks,
+            entities,
+        })
+    }
+
+    /// Returns the size of the structure along the x, y and z axes.
+    pub fn size(&self) -> (i32, i32, i32) {
+        self.size
+    }
+
+    pub fn palettes(&self) -> &[Vec<&'static BlockState>] {
+        &self.palettes
+    }
+
+    pub fn blocks(&self) -> &[StructureBlock] {
+        &self.blocks
+    }
+
+    pub fn entities(&self) -> &[StructureEntity] {
+        &self.entities
+    }
+
+    /// Places the structure with its origin at the position and returns the spawned entities.
+    ///
+    /// Like [`Schematic::paste`](crate::world::Schematic::paste), the blocks are set in one batch without
+    /// calling block handlers. Structure voids leave the blocks of the world untouched.
+    pub fn place(
+        &self,
+        world: &World,
+        position: BlockPosition,
+        placement: &StructurePlacement,
+    ) -> Vec<Entity> {
+        let mut rng = StdRng::seed_from_u64(placement.seed.unwrap_or_else(rand::random));
+        let palette = match placement.palette {
+            Some(palette) => &self.palettes[palette.min(self.palettes.len() - 1)],
+            None => &self.palettes[rng.gen_range(0..self.palettes.len())],
+        };
+        let transform = placement.transform;
+        let mut chunks = HashSet::new();
+
+        for block in &self.blocks {
+            let state = palette[block.state];
+            if state.id() == Block::StructureVoid.id() {
+                continue;
+            }
+            if placement.integrity < 1. && rng.r#gen::<f32>() > placement.integrity {
+                continue;
+            }
+
+            let (x, y, z) = block.position;
+            let (dx, dy, dz) = transform.apply(x, y, z);
+            let target = position.add(dx as i64, dy as i64, dz as i64);
+            if !world.0.is_in_height(target.y()) {
+                continue;
+            }
+
+            world.0.set_block_at(target, transform.apply_block(state));
+            if let Some(nbt) = &block.nbt
+                && let Some(chunk) = world.get_chunk(target)
+            {
+                let mut data = nbt.clone();
+                data.children
+                    .retain(|(name, _)| !matches!(name.as_str(), "x" | "y" | "z" | "id"));
+                let (x, y, z) = (target.x() as i32, target.y() as i32, target.z() as i32);
+                chunk.set_block_entity_data(x, y, z, data);
+            }
+            chunks.insert(ChunkPos::from(target));
+        }
+        world.0.resend_chunks(chunks);
+
+        if !placement.entities {
+            return Vec::new();
+        }
+        self.entities
+            .iter()
+            .map(|structure_entity| {
+                let (x, y, z) = structure_entity.position;
+                let (dx, dy, dz) = transform.apply_precise(x, y, z);
+                let entity = Entity::new(structure_entity.entity_type);
+                entity.set_position(Position::new(
+                    position.x() as f64 + dx,
+                    position.y() as f64 + dy,
+                    position.z() as f64 + dz,
+                    transform.apply_yaw(structure_entity.yaw),
+                    structure_entity.pitch,
+                ));
+                world.spawn_entity(entity.clone());
+                world.show_to_chunk_viewers(&entity);
+                entity
+            })
+            .collect()
+    }
+}
+
+fn read_palette(palette: &[NbtTag]) -> Vec<&'static BlockState> {
+    palette
+        .iter()
+        .map(|state| {
+            let NbtTag::Compound(state) = state else {
+                return Block::Air.default_state();
+            };
+            let Some(NbtTag::String(name)) = state.get("Name") else {
+                return Block::Air.default_state();
+            };
+            let Some(mut block) = BlockState::from_key(name.clone()) else {
+                log::warn!("Unknown block {name} in structure, replacing it with air");
+                return Block::Air.default_state();
+            };
+            if let Some(NbtTag::Compound(properties)) = state.get("Properties") {
+                for (property, value) in &properties.children {
+                    if let NbtTag::String(value) = value {
+                        block = block.with_property(property, value).unwrap_or(block);
+                    }
+                }
+            }
+            block
+        })
+        .collect()
+}
+
+fn get_ints(compound: &NbtCompound, name: &'static str) -> Result<Vec<i32>, StructureError> {
+    match compound.get(name) {
+        Some(NbtTag::List(list)) => list
+            .iter()
+            .map(|tag| match tag {
+                NbtTag::Int(value) => Ok(*value),
+                _ => Err(StructureError::InvalidField(name)),
+            })
+            .collect(),
+        Some(NbtTag::IntArray(values)) => Ok(values.clone()),
+        _ => Err(StructureError::InvalidField(name)),
+    }
+}
+
+fn get_doubles(compound: &NbtCompound, name: &'static str) -> Result<Vec<f64>, StructureError> {
+    match compound.get(name) {
+        Some(NbtTag::List(list)) => list
+            .iter()
+            .map(|tag| match tag {
+                NbtTag::Double(value) => Ok(*value),
+                _ => Err(StructureError::InvalidField(name)),
+            })
+            .collect(),
+        _ => Err(StructureError::InvalidField(name)),
+    }
+}
+
+fn get_compounds<'a>(
+    compound: &'a NbtCompound,
+    name: &'static str,
+) -> Result<impl Iterator<Item = &'a NbtCompound>, StructureError> {
+    match compound.get(name) {
+        Some(NbtTag::List(list)) => Ok(list.iter().filter_map(|tag| match tag {
+            NbtTag::Compound(compound) => Some(compound),
+            _ => None,
+        })),
+        _ => Err(StructureError::InvalidField(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::*;
+    use crate::entity::EntityLike as _;
+    use crate::registry::DimensionType;
+    use crate::world::schematic::MAX_DECOMPRESSED_SIZE;
+
+    fn ints(values: [i32; 3]) -> NbtTag {
+        NbtTag::List(values.into_iter().map(NbtTag::Int).collect())
+    }
+
+    fn state(name: &str, properties: &[(&str, &str)]) -> NbtTag {
+        let mut state = NbtCompound::new();
+        state.insert("Name", name);
+        if !properties.is_empty() {
+            let mut compound = NbtCompound::new();
+            for (property, value) in properties {
+                compound.insert(property, *value);
+            }
+            state.insert("Properties", compound);
+        }
+        NbtTag::Compound(state)
+    }
+
+    fn block(pos: [i32; 3], state: i32) -> NbtTag {
+        let mut block = NbtCompound::new();
+        block.insert("pos", ints(pos));
+        block.insert("state", state);
+        NbtTag::Compound(block)
+    }
+
+    fn template() -> StructureTemplate {
+        let mut root = NbtCompound::new();
+        root.insert("DataVersion", 4556);
+        root.insert("size", ints([2, 1, 1]));
+        root.insert(
+            "palette",
+            NbtTag::List(vec![
+                state("minecraft:stone", &[]),
+                state("minecraft:oak_log", &[("axis", "x")]),
+            ]),
+        );
+        root.insert(
+            "blocks",
+            NbtTag::List(vec![block([0, 0, 0], 0), block([1, 0, 0], 1)]),
+        );
+
+        let mut nbt = NbtCompound::new();
+        nbt.insert("id", "minecraft:armor_stand");
+        nbt.insert(
+            "Rotation",
+            NbtTag::List(vec![NbtTag::Float(0.), NbtTag::Float(0.)]),
+        );
+        let mut entity = NbtCompound::new();
+        entity.insert(
+            "pos",
+            NbtTag::List(vec![
+                NbtTag::Double(1.5),
+                NbtTag::Double(0.),
+                NbtTag::Double(0.5),
+            ]),
+        );
+        entity.insert("blockPos", ints([1, 0, 0]));
+        entity.insert("nbt", nbt);
+        root.insert("entities", NbtTag::List(vec![NbtTag::Compound(entity)]));
+
+        StructureTemplate::from_nbt(&root).unwrap()
+    }
+
+    #[test]
+    fn test_place() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let placement = StructurePlacement::new().with_rotation(Rotation::Clockwise90);
+        let entities = template().place(&world, BlockPosition::new(0, 64, 0), &placement);
+
+        assert_eq!(world.get_block(0, 64, 0).id(), Block::Stone.id());
+        let log = world.get_block(0, 64, 1);
+        assert_eq!(log.id(), Block::OakLog.id());
+        assert_eq!(log.property("axis"), Some("z"));
+
+        assert_eq!(entities.len(), 1);
+        let position = entities[0].position();
+        assert_eq!((position.x(), position.z()), (0.5, 1.5));
+        assert_eq!(position.yaw(), 90.);
+    }
+
+    #[test]
+    fn test_integrity() {
+        let dimension = DimensionType::OVERWORLD;
+        let world = World::new(&dimension);
+        let placement = StructurePlacement::new()
+            .with_integrity(0.)
+            .with_entities(false);
+        let entities = template().place(&world, BlockPosition::new(0, 64, 0), &placement);
+
+        assert!(entities.is_empty());
+        assert!(world.get_chunk((0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_structure() {
+        let mut root = NbtCompound::new();
+        root.insert("size", ints([1, 1, 1]));
+        let error = StructureTemplate::from_nbt(&root).unwrap_err();
+        assert!(matches!(error, StructureError::InvalidField("palette")));
+        assert_eq!(
+            error.to_string(),
+            "Missing or invalid structure field: palette"
+        );
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&vec![0; MAX_DECOMPRESSED_SIZE as usize + 1])
+            .unwrap();
+        let bytes = encoder.finish().unwrap();
+        assert!(matches!(
+            StructureTemplate::read(bytes.as_slice()),
+            Err(StructureError::TooLarge)
+        ));
+    }
+}
@@ -0,0 +1,280 @@
+use crate::world::{BlockFace, BlockState};
+
+/// A rotation around the y axis, clockwise when looking down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+impl Rotation {
+    fn quarter_turns(&self) -> i32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::CounterClockwise90 => 3,
+        }
+    }
+
+    /// Rotates a horizontal offset around the origin.
+    pub fn apply(&self, x: i32, z: i32) -> (i32, i32) {
+        match self {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (-z, x),
+            Rotation::Clockwise180 => (-x, -z),
+            Rotation::CounterClockwise90 => (z, -x),
+        }
+    }
+
+    pub fn rotate_face(&self, face: BlockFace) -> BlockFace {
+        if !face.is_horizontal() {
+            return face;
+        }
+        let index = BlockFace::HORIZONTAL
+            .iter()
+            .position(|f| *f == face)
+            .unwrap();
+        BlockFace::HORIZONTAL[(index + self.quarter_turns() as usize) % 4]
+    }
+}
+
+/// A mirror along a vertical plane through the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mirror {
+    #[default]
+    None,
+    /// Flips the z axis, swapping north and south.
+    LeftRight,
+    /// Flips the x axis, swapping east and west.
+    FrontBack,
+}
+
+impl Mirror {
+    /// Mirrors a horizontal offset.
+    pub fn apply(&self, x: i32, z: i32) -> (i32, i32) {
+        match self {
+            Mirror::None => (x, z),
+            Mirror::LeftRight => (x, -z),
+            Mirror::FrontBack => (-x, z),
+        }
+    }
+
+    pub fn mirror_face(&self, face: BlockFace) -> BlockFace {
+        match (self, face) {
+            (Mirror::LeftRight, BlockFace::North | BlockFace::South) => face.opposite(),
+            (Mirror::FrontBack, BlockFace::East | BlockFace::West) => face.opposite(),
+            _ => face,
+        }
+    }
+}
+
+/// How a structure is transformed when it is placed, mirrored first and rotated afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub mirror: Mirror,
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Transforms a position relative to the origin of the structure.
+    pub fn apply(&self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        let (x, z) = self.mirror.apply(x, z);
+        let (x, z) = self.rotation.apply(x, z);
+        (x, y, z)
+    }
+
+    /// Transforms a precise position relative to the origin of the structure, like the position of an entity.
+    ///
+    /// Unlike [`Transform::apply`], the block at the origin keeps covering the same space.
+    pub fn apply_precise(&self, x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+        let (x, z) = match self.mirror {
+            Mirror::None => (x, z),
+            Mirror::LeftRight => (x, 1. - z),
+            Mirror::FrontBack => (1. - x, z),
+        };
+        let (x, z) = match self.rotation {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (1. - z, x),
+            Rotation::Clockwise180 => (1. - x, 1. - z),
+            Rotation::CounterClockwise90 => (z, 1. - x),
+        };
+        (x, y, z)
+    }
+
+    /// Transforms the yaw of an entity.
+    pub fn apply_yaw(&self, yaw: f32) -> f32 {
+        let yaw = match self.mirror {
+            Mirror::None => yaw,
+            Mirror::LeftRight => 180. - yaw,
+            Mirror::FrontBack => -yaw,
+        };
+        (yaw + 90. * self.rotation.quarter_turns() as f32).rem_euclid(360.)
+    }
+
+    pub fn apply_face(&self, face: BlockFace) -> BlockFace {
+        self.rotation.rotate_face(self.mirror.mirror_face(face))
+    }
+
+    /// Returns the state facing the way the block faces after the transform.
+    ///
+    /// Directional properties like `facing`, `axis`, `rotation`, connections to the sides and rail shapes
+    /// are transformed; mirroring also swaps left and right of stairs, doors and chests.
+    pub fn apply_block(&self, block: &BlockState) -> &'static BlockState {
+        let mut state = BlockState::from_id(block.state_id()).unwrap();
+        for (name, value) in block.properties() {
+            let Some(value) = self.apply_property(name, value) else {
+                continue;
+            };
+            state = state.with_property(name, &value).unwrap_or(state);
+        }
+
+        // Connections to the sides are moved between properties rather than changed in place
+        let mut connected = state;
+        for face in BlockFace::HORIZONTAL {
+            if let Some(value) = block.property(face.name()) {
+                let target = self.apply_face(face);
+                connected = connected
+                    .with_property(target.name(), value)
+                    .unwrap_or(connected);
+            }
+        }
+        connected
+    }
+
+    /// Returns the transformed value of a property, or `None` to keep it.
+    fn apply_property(&self, name: &str, value: &str) -> Option<String> {
+        match name {
+            "facing" | "horizontal_facing" => {
+                let face = face_from_name(value)?;
+                Some(self.apply_face(face).name().to_string())
+            }
+            "axis" if self.rotation.quarter_turns() % 2 == 1 => match value {
+                "x" => Some("z".to_string()),
+                "z" => Some("x".to_string()),
+                _ => None,
+            },
+            "rotation" => {
+                let mut rotation: i32 = value.parse().ok()?;
+                rotation = match self.mirror {
+                    Mirror::None => rotation,
+                    Mirror::LeftRight => (24 - rotation) % 16,
+                    Mirror::FrontBack => (16 - rotation) % 16,
+                };
+                Some(((rotation + 4 * self.rotation.quarter_turns()) % 16).to_string())
+            }
+            "shape"
+                if value.contains("_") && !value.contains("left") && !value.contains("right") =>
+            {
+                Some(self.apply_rail_shape(value))
+            }
+            "shape" | "hinge" | "type" if self.mirror != Mirror::None => {
+                if value.contains("left") {
+                    Some(value.replace("left", "right"))
+                } else if value.contains("right") {
+                    Some(value.replace("right", "left"))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Transforms a rail shape like `north_east` or `ascending_west`, keeping the order the game uses.
+    fn apply_rail_shape(&self, shape: &str) -> String {
+        if let Some(face) = shape.strip_prefix("ascending_").and_then(face_from_name) {
+            return format!("ascending_{}", self.apply_face(face).name());
+        }
+
+        let Some((first, second)) = shape.split_once('_') else {
+            return shape.to_string();
+        };
+        let (Some(first), Some(second)) = (face_from_name(first), face_from_name(second)) else {
+            return shape.to_string();
+        };
+        let mut faces = [self.apply_face(first), self.apply_face(second)];
+        // Straight rails are `north_south` and `east_west`, curves start with north or south
+        let order = |face: &BlockFace| match face {
+            BlockFace::North => 0,
+            BlockFace::South => 1,
+            BlockFace::East => 2,
+            _ => 3,
+        };
+        faces.sort_by_key(order);
+        format!("{}_{}", faces[0].name(), faces[1].name())
+    }
+}
+
+fn face_from_name(name: &str) -> Option<BlockFace> {
+    BlockFace::ALL.into_iter().find(|face| face.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Block;
+
+    #[test]
+    fn test_transform_position() {
+        let transform = Transform::new().with_rotation(Rotation::Clockwise90);
+        assert_eq!(transform.apply(1, 5, 0), (0, 5, 1));
+        assert_eq!(transform.apply(0, 5, 1), (-1, 5, 0));
+
+        let transform = transform.with_mirror(Mirror::LeftRight);
+        assert_eq!(transform.apply(0, 0, 1), (1, 0, 0));
+
+        // The center of the block at the origin stays in place
+        let transform = Transform::new().with_rotation(Rotation::Clockwise180);
+        assert_eq!(transform.apply_precise(0.5, 0., 0.5), (0.5, 0., 0.5));
+        assert_eq!(transform.apply_precise(2.5, 0., 0.5), (-1.5, 0., 0.5));
+        assert_eq!(transform.apply_yaw(270.), 90.);
+    }
+
+    #[test]
+    fn test_transform_block() {
+        let transform = Transform::new().with_rotation(Rotation::Clockwise90);
+        let stairs = Block::OakStairs.with_property("facing", "north").unwrap();
+        assert_eq!(
+            transform.apply_block(stairs).property("facing"),
+            Some("east")
+        );
+
+        let log = Block::OakLog.with_property("axis", "x").unwrap();
+        assert_eq!(transform.apply_block(log).property("axis"), Some("z"));
+
+        let sign = Block::OakSign.with_property("rotation", "14").unwrap();
+        assert_eq!(transform.apply_block(sign).property("rotation"), Some("2"));
+
+        let fence = Block::OakFence.with_property("north", "true").unwrap();
+        let rotated = transform.apply_block(fence);
+        assert_eq!(rotated.property("north"), Some("false"));
+        assert_eq!(rotated.property("east"), Some("true"));
+
+        let mirror = Transform::new().with_mirror(Mirror::FrontBack);
+        let door = Block::OakDoor
+            .with_property("facing", "east")
+            .and_then(|door| door.with_property("hinge", "left"))
+            .unwrap();
+        let mirrored = mirror.apply_block(door);
+        assert_eq!(mirrored.property("facing"), Some("west"));
+        assert_eq!(mirrored.property("hinge"), Some("right"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::protocol::packet::GameEventPacket;
+
+/// The weather of a [`World`](crate::world::World).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Thunder,
+}
+
+/// The number of ticks the rain and thunder levels take to fade in or out, the same as in vanilla.
+const FADE_TICKS: u8 = 100;
+
+impl Weather {
+    fn rain_level(&self) -> u8 {
+        match self {
+            Self::Clear => 0,
+            Self::Rain | Self::Thunder => FADE_TICKS,
+        }
+    }
+
+    fn thunder_level(&self) -> u8 {
+        match self {
+            Self::Thunder => FADE_TICKS,
+            Self::Clear | Self::Rain => 0,
+        }
+    }
+}
+
+/// The weather of a world together with the current, fading rain and thunder levels.
+#[derive(Clone)]
+pub(crate) struct WeatherState {
+    weather: Weather,
+    /// The remaining ticks until the weather clears up, or `None` if it lasts forever.
+    remaining: Option<u64>,
+    /// The rain level in steps of `1 / FADE_TICKS`.
+    rain_level: u8,
+    /// The thunder level in steps of `1 / FADE_TICKS`.
+    thunder_level: u8,
+}
+
+impl WeatherState {
+    pub fn new() -> Self {
+        Self {
+            weather: Weather::Clear,
+            remaining: None,
+            rain_level: 0,
+            thunder_level: 0,
+        }
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    /// Changes the weather and returns the packets that announce the change.
+    pub fn set(&mut self, weather: Weather, duration: Option<Duration>) -> Vec<GameEventPacket> {
+        let was_raining = self.weather != Weather::Clear;
+        self.weather = weather;
+        self.remaining = duration.map(|duration| (duration.as_millis() / 50) as u64);
+
+        let is_raining = weather != Weather::Clear;
+        match (was_raining, is_raining) {
+            (false, true) => vec![GameEventPacket::BEGIN_RAINING],
+            (true, false) => vec![GameEventPacket::END_RAINING],
+            _ => vec![],
+        }
+    }
+
+    /// Advances the weather by one tick and returns the packets for the changed levels.
+    pub fn tick(&mut self) -> Vec<GameEventPacket> {
+        let mut packets = vec![];
+
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                packets.extend(self.set(Weather::Clear, None));
+            }
+        }
+
+        if step(&mut self.rain_level, self.weather.rain_level()) {
+            packets.push(GameEventPacket::rain_level(fraction(self.rain_level)));
+        }
+        if step(&mut self.thunder_level, self.weather.thunder_level()) {
+            packets.push(GameEventPacket::thunder_level(fraction(self.thunder_level)));
+        }
+
+        packets
+    }
+
+    /// Returns the packets that synchronize the current weather with a joining player.
+    pub fn packets(&self) -> Vec<GameEventPacket> {
+        if self.weather == Weather::Clear && self.rain_level == 0 {
+            return vec![];
+        }
+
+        vec![
+            GameEventPacket::BEGIN_RAINING,
+            GameEventPacket::rain_level(fraction(self.rain_level)),
+            GameEventPacket::thunder_level(fraction(self.thunder_level)),
+        ]
+    }
+}
+
+/// Moves `level` one step towards `target` and returns if it changed.
+fn step(level: &mut u8, target: u8) -> bool {
+    if *level < target {
+        *level += 1;
+    } else if *level > target {
+        *level -= 1;
+    } else {
+        return false;
+    }
+    true
+}
+
+fn fraction(level: u8) -> f32 {
+    level as f32 / FADE_TICKS as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_transition() {
+        let mut state = WeatherState::new();
+        assert!(state.tick().is_empty());
+        assert!(state.packets().is_empty());
+
+        let packets = state.set(Weather::Thunder, Some(Duration::from_secs(10)));
+        assert_eq!(packets[0].event, GameEventPacket::BEGIN_RAINING.event);
+
+        for _ in 0..100 {
+            assert_eq!(state.tick().len(), 2);
+        }
+        assert_eq!(state.rain_level, FADE_TICKS);
+        assert_eq!(state.thunder_level, FADE_TICKS);
+        assert!(state.tick().is_empty());
+        assert_eq!(state.packets().len(), 3);
+
+        // The weather clears up after 200 ticks
+        for _ in 101..199 {
+            state.tick();
+        }
+        assert_eq!(state.weather(), Weather::Thunder);
+        let packets = state.tick();
+        assert_eq!(state.weather(), Weather::Clear);
+        assert_eq!(packets[0].event, GameEventPacket::END_RAINING.event);
+    }
+}